@@ -0,0 +1,310 @@
+use std::sync::Arc;
+
+use crate::{
+    EventLoop, RawWindowHandle, Rect, Window,
+    dpi::{PhysicalPosition, PhysicalSize, Position, Size},
+    events::EventSystem,
+    monitor::Fullscreen,
+};
+
+/// Where a new window is placed.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum WindowPosition {
+    /// The window manager picks the position.
+    #[default]
+    Automatic,
+
+    /// Centered on the primary monitor.
+    Centered,
+
+    /// The top left corner of the window, relative to the desktop or the parent window.
+    At(Position),
+}
+
+/// Describes a window, that is created with `build` or `build_queued`.
+///
+/// All attributes are applied before the window is shown, so it never appears
+/// in an intermediate state.
+#[derive(Debug, Clone)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct WindowBuilder {
+    title: String,
+    inner_size: Size,
+    position: WindowPosition,
+    min_inner_size: Option<Size>,
+    max_inner_size: Option<Size>,
+    resizable: bool,
+    decorations: bool,
+    visible: bool,
+    transparent: bool,
+    maximized: bool,
+    always_on_top: bool,
+    app_id: Option<String>,
+    parent: Option<RawWindowHandle>,
+    fullscreen: Option<Fullscreen>,
+}
+
+impl Default for WindowBuilder {
+    fn default() -> Self {
+        Self {
+            title: String::from("pane"),
+            inner_size: Size::Logical(crate::dpi::LogicalSize::new(800.0, 600.0)),
+            position: WindowPosition::Automatic,
+            min_inner_size: None,
+            max_inner_size: None,
+            resizable: true,
+            decorations: true,
+            visible: true,
+            transparent: false,
+            maximized: false,
+            always_on_top: false,
+            app_id: None,
+            parent: None,
+            fullscreen: None,
+        }
+    }
+}
+
+impl WindowBuilder {
+    /// Creates a visible, resizable and decorated window of 800 x 600 logical pixels.
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Sets the size of the client area, without the frame of the window manager.
+    #[inline]
+    #[must_use]
+    pub fn with_inner_size(mut self, size: impl Into<Size>) -> Self {
+        self.inner_size = size.into();
+        self
+    }
+
+    /// Places the window at a position.
+    ///
+    /// Ignored on Wayland, where the compositor places all windows.
+    #[inline]
+    #[must_use]
+    pub fn with_position(mut self, position: impl Into<Position>) -> Self {
+        self.position = WindowPosition::At(position.into());
+        self
+    }
+
+    /// Places the window, or leaves the position to the window manager.
+    #[inline]
+    #[must_use]
+    pub const fn with_window_position(mut self, position: WindowPosition) -> Self {
+        self.position = position;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_min_inner_size(mut self, size: impl Into<Size>) -> Self {
+        self.min_inner_size = Some(size.into());
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_max_inner_size(mut self, size: impl Into<Size>) -> Self {
+        self.max_inner_size = Some(size.into());
+        self
+    }
+
+    /// A window, that is not resizable, keeps its inner size.
+    #[inline]
+    #[must_use]
+    pub const fn with_resizable(mut self, resizable: bool) -> Self {
+        self.resizable = resizable;
+        self
+    }
+
+    /// Asks the window manager to draw a title bar and borders.
+    #[inline]
+    #[must_use]
+    pub const fn with_decorations(mut self, decorations: bool) -> Self {
+        self.decorations = decorations;
+        self
+    }
+
+    /// Shows the window right away, otherwise it stays hidden until `Window::show`.
+    #[inline]
+    #[must_use]
+    pub const fn with_visible(mut self, visible: bool) -> Self {
+        self.visible = visible;
+        self
+    }
+
+    /// Uses the high byte of presented pixels as premultiplied alpha.
+    ///
+    /// Needs a compositing window manager on X11.
+    #[inline]
+    #[must_use]
+    pub const fn with_transparent(mut self, transparent: bool) -> Self {
+        self.transparent = transparent;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn with_maximized(mut self, maximized: bool) -> Self {
+        self.maximized = maximized;
+        self
+    }
+
+    /// Keeps the window above all others.
+    ///
+    /// Ignored on Wayland, which has no protocol for it.
+    #[inline]
+    #[must_use]
+    pub const fn with_always_on_top(mut self, always_on_top: bool) -> Self {
+        self.always_on_top = always_on_top;
+        self
+    }
+
+    /// Sets the name, that the desktop groups the windows of an application by.
+    ///
+    /// This is `WM_CLASS` on X11 and the app id on Wayland, it is ignored on Windows.
+    #[inline]
+    #[must_use]
+    pub fn with_app_id(mut self, app_id: impl Into<String>) -> Self {
+        self.app_id = Some(app_id.into());
+        self
+    }
+
+    /// Embeds the window into another one, which has to belong to the same windowing system.
+    #[inline]
+    #[must_use]
+    pub const fn with_parent(mut self, parent: RawWindowHandle) -> Self {
+        self.parent = Some(parent);
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_fullscreen(mut self, fullscreen: Option<Fullscreen>) -> Self {
+        self.fullscreen = fullscreen;
+        self
+    }
+
+    /// Creates the window on an event loop.
+    ///
+    /// # Errors
+    /// Returns an `Error` if creating the window failed, or the windowing system does not
+    /// support one of the attributes.
+    #[inline]
+    pub fn build(
+        self,
+        event_loop: &EventLoop,
+        eventsys: Arc<dyn EventSystem>,
+    ) -> Result<Window, crate::Error> {
+        Window::create_on(&event_loop.event_loop, &self, eventsys)
+    }
+
+    /// Creates the window on an event loop, whose events are collected in a queue
+    /// instead of being passed to an `EventSystem`.
+    ///
+    /// # Errors
+    /// Returns an `Error` if creating the window failed, or the windowing system does not
+    /// support one of the attributes.
+    #[inline]
+    pub fn build_queued(self, event_loop: &EventLoop) -> Result<Window, crate::Error> {
+        Window::create_queued_on(&event_loop.event_loop, &self)
+    }
+
+    /// Converts all sizes into pixels of a monitor.
+    pub(crate) fn attributes(
+        &self,
+        scale_factor: f64,
+        primary_monitor: Option<(PhysicalPosition, PhysicalSize)>,
+    ) -> WindowAttributes {
+        let size = self.inner_size.to_physical(scale_factor);
+
+        let position = match self.position {
+            WindowPosition::Automatic => None,
+            WindowPosition::Centered => primary_monitor.map(|(origin, monitor)| {
+                let center = |origin: i32, monitor: u32, window: u32| {
+                    let offset = (i64::from(monitor) - i64::from(window)) / 2;
+                    i32::try_from(i64::from(origin) + offset).unwrap_or(origin)
+                };
+
+                PhysicalPosition::new(
+                    center(origin.x, monitor.width, size.width),
+                    center(origin.y, monitor.height, size.height),
+                )
+            }),
+            WindowPosition::At(position) => Some(position.to_physical(scale_factor)),
+        };
+
+        let size = size.to_rect();
+
+        // a window, that can't be resized, has the same minimum and maximum size
+        let (min_size, max_size) = if self.resizable {
+            (
+                self.min_inner_size
+                    .map(|size| size.to_physical(scale_factor).to_rect()),
+                self.max_inner_size
+                    .map(|size| size.to_physical(scale_factor).to_rect()),
+            )
+        } else {
+            (Some(size), Some(size))
+        };
+
+        WindowAttributes {
+            title: self.title.clone(),
+            position: position.map(|position| {
+                let clamp = |value: i32| {
+                    i16::try_from(value.clamp(i32::from(i16::MIN), i32::from(i16::MAX)))
+                        .unwrap_or_default()
+                };
+
+                (clamp(position.x), clamp(position.y))
+            }),
+            size,
+            min_size,
+            max_size,
+            resizable: self.resizable,
+            decorations: self.decorations,
+            visible: self.visible,
+            transparent: self.transparent,
+            maximized: self.maximized,
+            always_on_top: self.always_on_top,
+            app_id: self.app_id.clone(),
+            parent: self.parent,
+            fullscreen: self.fullscreen.clone(),
+        }
+    }
+}
+
+/// The attributes of a `WindowBuilder` in pixels, as passed to the backends.
+#[derive(Debug, Clone)]
+// headless windows have no window manager, that most of them are meant for
+#[cfg_attr(feature = "headless", allow(dead_code))]
+#[allow(clippy::struct_excessive_bools)]
+pub(crate) struct WindowAttributes {
+    pub title: String,
+    /// `None` leaves the position to the window manager.
+    pub position: Option<(i16, i16)>,
+    pub size: Rect,
+    pub min_size: Option<Rect>,
+    pub max_size: Option<Rect>,
+    pub resizable: bool,
+    pub decorations: bool,
+    pub visible: bool,
+    pub transparent: bool,
+    pub maximized: bool,
+    pub always_on_top: bool,
+    #[cfg_attr(windows, allow(dead_code))]
+    pub app_id: Option<String>,
+    pub parent: Option<RawWindowHandle>,
+    pub fullscreen: Option<Fullscreen>,
+}
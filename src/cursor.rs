@@ -0,0 +1,52 @@
+/// How the cursor is held by a window, set with `Window::set_cursor_grab`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CursorGrabMode {
+    /// The cursor moves freely.
+    #[default]
+    None,
+
+    /// The cursor can't leave the window.
+    Confined,
+
+    /// The cursor stays in place.
+    ///
+    /// Mouse movement is only reported by `RawMouseMotionEvent`s.
+    Locked,
+}
+
+/// The standard cursor shapes, set with `Window::set_cursor`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CursorIcon {
+    /// The arrow of the platform.
+    #[default]
+    Default,
+
+    /// An I-beam, for selecting text.
+    Text,
+
+    /// A pointing hand, for links.
+    Hand,
+    Crosshair,
+
+    /// Arrows in all four directions, for moving something.
+    Move,
+
+    /// A double arrow from left to right.
+    EwResize,
+
+    /// A double arrow from top to bottom.
+    NsResize,
+
+    /// A double arrow from the top right to the bottom left corner.
+    NeswResize,
+
+    /// A double arrow from the top left to the bottom right corner.
+    NwseResize,
+
+    /// Busy, the application can't be interacted with.
+    Wait,
+
+    /// Busy in the background, the application can still be interacted with.
+    Progress,
+    NotAllowed,
+}
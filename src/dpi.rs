@@ -0,0 +1,302 @@
+use crate::Rect;
+
+/// A size in pixels of the monitor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PhysicalSize {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl PhysicalSize {
+    #[inline]
+    #[must_use]
+    pub const fn new(width: u32, height: u32) -> Self {
+        Self { width, height }
+    }
+
+    /// Divides the size by the scale factor of a monitor.
+    #[inline]
+    #[must_use]
+    pub fn to_logical(self, scale_factor: f64) -> LogicalSize {
+        LogicalSize {
+            width: f64::from(self.width) / scale_factor,
+            height: f64::from(self.height) / scale_factor,
+        }
+    }
+
+    /// Converts the size into a `Rect`, saturating at `u16::MAX`.
+    pub(crate) fn to_rect(self) -> Rect {
+        Rect::new(
+            u16::try_from(self.width).unwrap_or(u16::MAX),
+            u16::try_from(self.height).unwrap_or(u16::MAX),
+        )
+    }
+}
+
+impl From<Rect> for PhysicalSize {
+    fn from(rect: Rect) -> Self {
+        Self::new(u32::from(rect.x), u32::from(rect.y))
+    }
+}
+
+/// A size in pixels at 96 DPI, that grows with the scale factor of the monitor.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct LogicalSize {
+    pub width: f64,
+    pub height: f64,
+}
+
+impl LogicalSize {
+    #[inline]
+    #[must_use]
+    pub const fn new(width: f64, height: f64) -> Self {
+        Self { width, height }
+    }
+
+    /// Multiplies the size by the scale factor of a monitor, rounded to whole pixels.
+    #[inline]
+    #[must_use]
+    pub fn to_physical(self, scale_factor: f64) -> PhysicalSize {
+        PhysicalSize {
+            width: to_pixels(self.width * scale_factor),
+            height: to_pixels(self.height * scale_factor),
+        }
+    }
+}
+
+/// A position in pixels of the monitor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PhysicalPosition {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl PhysicalPosition {
+    #[inline]
+    #[must_use]
+    pub const fn new(x: i32, y: i32) -> Self {
+        Self { x, y }
+    }
+
+    /// Divides the position by the scale factor of a monitor.
+    #[inline]
+    #[must_use]
+    pub fn to_logical(self, scale_factor: f64) -> LogicalPosition {
+        LogicalPosition {
+            x: f64::from(self.x) / scale_factor,
+            y: f64::from(self.y) / scale_factor,
+        }
+    }
+}
+
+impl From<Rect> for PhysicalPosition {
+    fn from(rect: Rect) -> Self {
+        Self::new(i32::from(rect.x), i32::from(rect.y))
+    }
+}
+
+/// A position in pixels at 96 DPI, that grows with the scale factor of the monitor.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct LogicalPosition {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl LogicalPosition {
+    #[inline]
+    #[must_use]
+    pub const fn new(x: f64, y: f64) -> Self {
+        Self { x, y }
+    }
+
+    /// Multiplies the position by the scale factor of a monitor, rounded to whole pixels.
+    #[inline]
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn to_physical(self, scale_factor: f64) -> PhysicalPosition {
+        // float to int casts saturate
+        PhysicalPosition {
+            x: (self.x * scale_factor).round() as i32,
+            y: (self.y * scale_factor).round() as i32,
+        }
+    }
+}
+
+/// A size in either unit, like the initial size of a window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Size {
+    Physical(PhysicalSize),
+    Logical(LogicalSize),
+}
+
+impl Size {
+    #[inline]
+    #[must_use]
+    pub fn to_physical(self, scale_factor: f64) -> PhysicalSize {
+        match self {
+            Self::Physical(size) => size,
+            Self::Logical(size) => size.to_physical(scale_factor),
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn to_logical(self, scale_factor: f64) -> LogicalSize {
+        match self {
+            Self::Physical(size) => size.to_logical(scale_factor),
+            Self::Logical(size) => size,
+        }
+    }
+}
+
+impl From<PhysicalSize> for Size {
+    fn from(size: PhysicalSize) -> Self {
+        Self::Physical(size)
+    }
+}
+
+impl From<LogicalSize> for Size {
+    fn from(size: LogicalSize) -> Self {
+        Self::Logical(size)
+    }
+}
+
+/// A `Rect` is in physical pixels.
+impl From<Rect> for Size {
+    fn from(rect: Rect) -> Self {
+        Self::Physical(rect.into())
+    }
+}
+
+/// A position in either unit, like the initial position of a window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Position {
+    Physical(PhysicalPosition),
+    Logical(LogicalPosition),
+}
+
+impl Position {
+    #[inline]
+    #[must_use]
+    pub fn to_physical(self, scale_factor: f64) -> PhysicalPosition {
+        match self {
+            Self::Physical(position) => position,
+            Self::Logical(position) => position.to_physical(scale_factor),
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn to_logical(self, scale_factor: f64) -> LogicalPosition {
+        match self {
+            Self::Physical(position) => position.to_logical(scale_factor),
+            Self::Logical(position) => position,
+        }
+    }
+}
+
+impl From<PhysicalPosition> for Position {
+    fn from(position: PhysicalPosition) -> Self {
+        Self::Physical(position)
+    }
+}
+
+impl From<LogicalPosition> for Position {
+    fn from(position: LogicalPosition) -> Self {
+        Self::Logical(position)
+    }
+}
+
+/// A `Rect` is in physical pixels.
+impl From<Rect> for Position {
+    fn from(rect: Rect) -> Self {
+        Self::Physical(rect.into())
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+const fn to_pixels(value: f64) -> u32 {
+    // float to int casts saturate, negative sizes become 0
+    value.round() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn logical_size_rounds_to_whole_pixels() {
+        let size = LogicalSize::new(101.0, 33.0);
+
+        assert_eq!(size.to_physical(1.0), PhysicalSize::new(101, 33));
+        // 126.25 and 41.25
+        assert_eq!(size.to_physical(1.25), PhysicalSize::new(126, 41));
+        // 151.5 and 49.5 round away from zero
+        assert_eq!(size.to_physical(1.5), PhysicalSize::new(152, 50));
+    }
+
+    #[test]
+    fn logical_size_saturates() {
+        assert_eq!(
+            LogicalSize::new(-10.0, 1e12).to_physical(1.5),
+            PhysicalSize::new(0, u32::MAX)
+        );
+    }
+
+    #[test]
+    fn logical_position_rounds_to_whole_pixels() {
+        // -15.75 and 4.5
+        assert_eq!(
+            LogicalPosition::new(-10.5, 3.0).to_physical(1.5),
+            PhysicalPosition::new(-16, 5)
+        );
+        assert_eq!(
+            LogicalPosition::new(-1e12, 1e12).to_physical(1.25),
+            PhysicalPosition::new(i32::MIN, i32::MAX)
+        );
+    }
+
+    #[test]
+    fn physical_to_logical() {
+        assert_eq!(
+            PhysicalSize::new(150, 75).to_logical(1.5),
+            LogicalSize::new(100.0, 50.0)
+        );
+        assert_eq!(
+            PhysicalPosition::new(-125, 250).to_logical(1.25),
+            LogicalPosition::new(-100.0, 200.0)
+        );
+    }
+
+    #[test]
+    fn physical_size_to_rect_saturates() {
+        assert_eq!(PhysicalSize::new(640, 480).to_rect(), Rect::new(640, 480));
+        assert_eq!(
+            PhysicalSize::new(70_000, u32::from(u16::MAX)).to_rect(),
+            Rect::new(u16::MAX, u16::MAX)
+        );
+    }
+
+    #[test]
+    fn size_converts_only_the_other_unit() {
+        let physical = Size::from(PhysicalSize::new(100, 50));
+        let logical = Size::from(LogicalSize::new(100.0, 50.0));
+
+        assert_eq!(physical.to_physical(1.5), PhysicalSize::new(100, 50));
+        assert_eq!(physical.to_logical(1.25), LogicalSize::new(80.0, 40.0));
+        // 62.5 rounds up
+        assert_eq!(logical.to_physical(1.25), PhysicalSize::new(125, 63));
+        assert_eq!(logical.to_logical(1.5), LogicalSize::new(100.0, 50.0));
+    }
+
+    #[test]
+    fn position_converts_only_the_other_unit() {
+        let physical = Position::from(PhysicalPosition::new(150, -75));
+        let logical = Position::from(LogicalPosition::new(100.0, -50.0));
+
+        assert_eq!(physical.to_physical(1.25), PhysicalPosition::new(150, -75));
+        assert_eq!(physical.to_logical(1.5), LogicalPosition::new(100.0, -50.0));
+        assert_eq!(logical.to_physical(1.25), PhysicalPosition::new(125, -63));
+        assert_eq!(logical.to_logical(1.5), LogicalPosition::new(100.0, -50.0));
+    }
+}
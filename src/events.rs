@@ -1,22 +1,184 @@
-use crate::inputs::{Key, MouseButton, MouseWheelDirection};
+use std::{any::Any, collections::VecDeque, sync::Mutex};
 
+use crate::{
+    DamageRect, WindowId,
+    inputs::{Key, Modifiers, MouseButton, MouseWheelDirection, ScrollDelta},
+};
+
+/// Receives the events of one or more windows.
+///
+/// Every callback is tagged with the `WindowId` of the window the event originated from.
 pub trait EventSystem: std::fmt::Debug {
-    fn invoke_mouse_button_event(&self, e: MouseButtonEvent);
+    fn invoke_mouse_button_event(&self, window: WindowId, e: MouseButtonEvent);
+
+    fn invoke_mouse_wheel_event(&self, window: WindowId, e: MouseWheelEvent);
+
+    fn invoke_mouse_move_event(&self, window: WindowId, e: MouseMoveEvent);
+
+    fn invoke_key_event(&self, window: WindowId, e: KeyEvent);
+
+    fn invoke_window_resize_event(&self, window: WindowId, e: WindowResizeEvent);
+
+    fn invoke_window_event(&self, window: WindowId, e: WindowEvent);
+
+    fn invoke_text_event(&self, _: WindowId, _: TextEvent) {}
+
+    fn invoke_modifiers_changed_event(&self, _: WindowId, _: ModifiersChangedEvent) {}
+
+    fn invoke_raw_mouse_motion_event(&self, _: WindowId, _: RawMouseMotionEvent) {}
+
+    fn invoke_user_event(&self, _: WindowId, _: UserEvent) {}
+}
+
+/// A single event of a window, returned by `Window::poll_event`.
+#[derive(Debug)]
+pub enum Event {
+    Key(KeyEvent),
+    Text(TextEvent),
+    ModifiersChanged(ModifiersChangedEvent),
+    MouseButton(MouseButtonEvent),
+    MouseMove(MouseMoveEvent),
+    RawMouseMotion(RawMouseMotionEvent),
+    MouseWheel(MouseWheelEvent),
+    WindowResize(WindowResizeEvent),
+    Window(WindowEvent),
+    User(UserEvent),
+}
+
+impl Event {
+    /// Passes the event to the matching callback of an `EventSystem`.
+    #[cfg(any(feature = "headless", target_os = "linux"))]
+    pub(crate) fn dispatch(self, eventsys: &dyn EventSystem, window: WindowId) {
+        match self {
+            Self::Key(e) => eventsys.invoke_key_event(window, e),
+            Self::Text(e) => eventsys.invoke_text_event(window, e),
+            Self::ModifiersChanged(e) => eventsys.invoke_modifiers_changed_event(window, e),
+            Self::MouseButton(e) => eventsys.invoke_mouse_button_event(window, e),
+            Self::MouseMove(e) => eventsys.invoke_mouse_move_event(window, e),
+            Self::RawMouseMotion(e) => eventsys.invoke_raw_mouse_motion_event(window, e),
+            Self::MouseWheel(e) => eventsys.invoke_mouse_wheel_event(window, e),
+            Self::WindowResize(e) => eventsys.invoke_window_resize_event(window, e),
+            Self::Window(e) => eventsys.invoke_window_event(window, e),
+            Self::User(e) => eventsys.invoke_user_event(window, e),
+        }
+    }
+}
+
+/// Stores the events of a window until they are polled.
+///
+/// Used in place of a user provided `EventSystem`.
+#[derive(Debug, Default)]
+pub(crate) struct EventQueue {
+    events: Mutex<VecDeque<Event>>,
+}
+
+impl EventQueue {
+    #[inline]
+    pub(crate) fn push(&self, e: Event) {
+        self.events
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .push_back(e);
+    }
+
+    #[inline]
+    pub(crate) fn pop(&self) -> Option<Event> {
+        self.events
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .pop_front()
+    }
+}
+
+impl EventSystem for EventQueue {
+    fn invoke_mouse_button_event(&self, _: WindowId, e: MouseButtonEvent) {
+        self.push(Event::MouseButton(e));
+    }
+
+    fn invoke_mouse_wheel_event(&self, _: WindowId, e: MouseWheelEvent) {
+        self.push(Event::MouseWheel(e));
+    }
+
+    fn invoke_mouse_move_event(&self, _: WindowId, e: MouseMoveEvent) {
+        self.push(Event::MouseMove(e));
+    }
 
-    fn invoke_mouse_wheel_event(&self, e: MouseWheelEvent);
+    fn invoke_key_event(&self, _: WindowId, e: KeyEvent) {
+        self.push(Event::Key(e));
+    }
 
-    fn invoke_mouse_move_event(&self, e: MouseMoveEvent);
+    fn invoke_window_resize_event(&self, _: WindowId, e: WindowResizeEvent) {
+        self.push(Event::WindowResize(e));
+    }
 
-    fn invoke_key_event(&self, e: KeyEvent);
+    fn invoke_window_event(&self, _: WindowId, e: WindowEvent) {
+        self.push(Event::Window(e));
+    }
 
-    fn invoke_window_resize_event(&self, e: WindowResizeEvent);
+    fn invoke_text_event(&self, _: WindowId, e: TextEvent) {
+        self.push(Event::Text(e));
+    }
 
-    fn invoke_window_event(&self, e: WindowEvent);
+    fn invoke_modifiers_changed_event(&self, _: WindowId, e: ModifiersChangedEvent) {
+        self.push(Event::ModifiersChanged(e));
+    }
+
+    fn invoke_raw_mouse_motion_event(&self, _: WindowId, e: RawMouseMotionEvent) {
+        self.push(Event::RawMouseMotion(e));
+    }
+
+    fn invoke_user_event(&self, _: WindowId, e: UserEvent) {
+        self.push(Event::User(e));
+    }
 }
 
 #[derive(Debug)]
 pub enum WindowEvent {
     CloseWindow,
+
+    /// A monitor was connected, disconnected or changed its mode.
+    ///
+    /// Sent to every window, query `available_monitors` again for the new layout.
+    MonitorsChanged,
+
+    /// The window moved to a monitor with a different DPI, or its DPI was changed.
+    ScaleFactorChanged {
+        scale_factor: f64,
+    },
+
+    /// The window gained or lost the keyboard focus.
+    Focused(bool),
+
+    /// The cursor moved into the client area.
+    CursorEntered,
+
+    /// The cursor moved out of the client area.
+    CursorLeft,
+
+    /// The window became completely hidden by other windows, or visible again.
+    ///
+    /// Minimized windows are occluded as well. Not reported on Wayland.
+    Occluded(bool),
+
+    /// The window was minimized to the taskbar. Not reported on Wayland.
+    Minimized,
+
+    /// The window was restored after it was minimized. Not reported on Wayland.
+    Restored,
+
+    /// The client area moved to a new position on the desktop. Not reported on Wayland.
+    Moved {
+        x: i32,
+        y: i32,
+    },
+
+    /// The contents of the window were damaged, or `Window::request_redraw` was called.
+    ///
+    /// All damage up to the end of `pump_messages` or `wait_events` is merged into one event
+    /// per window. `damage` covers all of it, `None` stands for the whole client area.
+    RedrawRequested {
+        damage: Option<DamageRect>,
+    },
 }
 
 #[derive(Debug)]
@@ -29,17 +191,31 @@ pub struct WindowResizeEvent {
 pub struct MouseButtonEvent {
     pub down: bool,
     pub button: MouseButton,
+    pub modifiers: Modifiers,
 }
 
 #[derive(Debug)]
 pub struct MouseMoveEvent {
     pub x_pos: u32,
     pub y_pos: u32,
+    pub modifiers: Modifiers,
+}
+
+/// Relative motion of the mouse, enabled with `Window::set_raw_mouse_motion`.
+///
+/// Without pointer acceleration and not stopped by the edges of the window or screen.
+#[derive(Debug)]
+pub struct RawMouseMotionEvent {
+    pub dx: f64,
+    pub dy: f64,
 }
 
 #[derive(Debug)]
 pub struct MouseWheelEvent {
+    /// The main direction of the scroll, for those that only care about notches.
     pub direction: MouseWheelDirection,
+    pub delta: ScrollDelta,
+    pub modifiers: Modifiers,
 }
 
 #[derive(Debug)]
@@ -47,4 +223,28 @@ pub struct KeyEvent {
     pub key: Key,
     pub down: bool,
     pub repeat: u32,
+    /// The modifiers before this key was pressed or released.
+    ///
+    /// A change caused by the key itself follows as `ModifiersChangedEvent`.
+    pub modifiers: Modifiers,
+}
+
+/// Text typed by the user, translated with the active keyboard layout.
+///
+/// Follows the `KeyEvent` of the key press, that produced it.
+#[derive(Debug)]
+pub struct TextEvent {
+    pub text: String,
+}
+
+/// The held modifier keys or toggled lock keys changed.
+#[derive(Debug)]
+pub struct ModifiersChangedEvent {
+    pub modifiers: Modifiers,
+}
+
+/// An event sent from another thread through an `EventLoopProxy`.
+#[derive(Debug)]
+pub struct UserEvent {
+    pub payload: Box<dyn Any + Send>,
 }
@@ -0,0 +1,24 @@
+#[derive(Debug)]
+pub enum Error {
+    InvalidTitle,
+    BufferSize { expected: usize, actual: usize },
+    RgbaSize { expected: usize, actual: usize },
+}
+
+impl std::error::Error for Error {}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidTitle => write!(f, "Invalid title: contains a NUL byte"),
+            Self::BufferSize { expected, actual } => write!(
+                f,
+                "Invalid buffer size: expected {expected} pixels, got {actual}"
+            ),
+            Self::RgbaSize { expected, actual } => write!(
+                f,
+                "Invalid RGBA buffer size: expected {expected} bytes, got {actual}"
+            ),
+        }
+    }
+}
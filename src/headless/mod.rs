@@ -0,0 +1,722 @@
+mod err;
+
+pub use err::Error;
+
+/// Headless windows share a single fake monitor.
+pub type MonitorId = u32;
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, VecDeque},
+    rc::Rc,
+    sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    DamageRect, EventLoopImpl, EventLoopProxyImpl, Image, InputInjectorImpl, RawWindowHandle, Rect,
+    UserAttentionType, WindowId, WindowImpl, WindowLevel,
+    builder::WindowAttributes,
+    cursor::{CursorGrabMode, CursorIcon},
+    dpi::PhysicalPosition,
+    events::{
+        Event, EventSystem, KeyEvent, ModifiersChangedEvent, MouseButtonEvent, MouseMoveEvent,
+        MouseWheelEvent, RawMouseMotionEvent, UserEvent, WindowEvent, WindowResizeEvent,
+    },
+    icon::Icon,
+    inputs::{Key, Modifiers, MouseButton, MouseWheelDirection},
+    monitor::{Fullscreen, MonitorHandle, VideoMode},
+};
+
+#[derive(Debug, Default)]
+struct PendingEvents {
+    events: VecDeque<(WindowId, Event)>,
+    woken: bool,
+}
+
+/// Events waiting for the next `pump_messages`, shared with `EventLoopProxy`s on other threads.
+#[derive(Debug, Default)]
+struct Pending {
+    events: Mutex<PendingEvents>,
+    signal: Condvar,
+}
+
+impl Pending {
+    fn lock(&self) -> MutexGuard<'_, PendingEvents> {
+        self.events.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn push(&self, window: WindowId, e: Event) {
+        self.lock().events.push_back((window, e));
+        self.signal.notify_all();
+    }
+
+    fn wake_up(&self) {
+        self.lock().woken = true;
+        self.signal.notify_all();
+    }
+}
+
+/// State shared by all windows of an event loop.
+#[derive(Debug, Default)]
+struct Shared {
+    windows: RefCell<HashMap<WindowId, Arc<dyn EventSystem>>>,
+    pending: Arc<Pending>,
+    next_id: Cell<u64>,
+}
+
+impl Shared {
+    fn pump_messages(&self) {
+        let events = std::mem::take(&mut *self.pending.lock()).events;
+
+        for (window, e) in events {
+            // clone the event system out of the map, so callbacks are free to create or destroy windows
+            let Some(eventsys) = self.windows.borrow().get(&window).cloned() else {
+                continue;
+            };
+
+            e.dispatch(&*eventsys, window);
+        }
+    }
+
+    fn wait_events(&self, timeout: Option<Duration>) {
+        let deadline = timeout.and_then(|timeout| Instant::now().checked_add(timeout));
+        let mut pending = self.pending.lock();
+
+        while pending.events.is_empty() && !pending.woken {
+            pending = match deadline {
+                Some(deadline) => {
+                    let Some(timeout) = deadline.checked_duration_since(Instant::now()) else {
+                        break;
+                    };
+
+                    self.pending
+                        .signal
+                        .wait_timeout(pending, timeout)
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .0
+                }
+                None => self
+                    .pending
+                    .signal
+                    .wait(pending)
+                    .unwrap_or_else(PoisonError::into_inner),
+            };
+        }
+
+        pending.woken = false;
+        drop(pending);
+
+        self.pump_messages();
+    }
+}
+
+/// An event loop without any connection to a windowing system.
+#[derive(Debug)]
+pub struct EventLoop {
+    shared: Rc<Shared>,
+}
+
+impl EventLoopImpl for EventLoop {
+    fn new() -> Result<Self, Error> {
+        Ok(Self {
+            shared: Rc::default(),
+        })
+    }
+
+    fn pump_messages(&mut self) -> Result<(), Error> {
+        self.shared.pump_messages();
+        Ok(())
+    }
+
+    fn wait_events(&mut self, timeout: Option<Duration>) -> Result<(), Error> {
+        self.shared.wait_events(timeout);
+        Ok(())
+    }
+
+    fn available_monitors(&self) -> Vec<MonitorHandle> {
+        vec![monitor()]
+    }
+
+    fn primary_monitor(&self) -> Option<MonitorHandle> {
+        Some(monitor())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EventLoopProxy {
+    window: WindowId,
+    pending: Arc<Pending>,
+}
+
+impl EventLoopProxyImpl for EventLoopProxy {
+    fn wake_up(&self, event: Option<UserEvent>) -> Result<(), Error> {
+        match event {
+            Some(e) => self.pending.push(self.window, Event::User(e)),
+            None => self.pending.wake_up(),
+        }
+
+        Ok(())
+    }
+}
+
+/// Turns input into events of a window, without a windowing system in between.
+#[derive(Debug)]
+pub struct InputInjector {
+    window: WindowId,
+    pending: Arc<Pending>,
+    modifiers: Cell<Modifiers>,
+    raw_mouse_motion: Rc<Cell<bool>>,
+    pointer: Cell<Option<(i32, i32)>>,
+}
+
+impl InputInjectorImpl for InputInjector {
+    fn key(&self, key: Key, down: bool) -> Result<(), Error> {
+        let modifiers = self.modifiers.get();
+
+        let e = KeyEvent {
+            key,
+            down,
+            repeat: 0,
+            modifiers,
+        };
+
+        self.pending.push(self.window, Event::Key(e));
+
+        if let Some(modifier) = key.modifier() {
+            let mut changed = modifiers;
+
+            // lock keys toggle on press, everything else is held down
+            if modifier.intersects(Modifiers::CAPS_LOCK | Modifiers::NUM_LOCK) {
+                if down {
+                    changed.toggle(modifier);
+                }
+            } else {
+                changed.set(modifier, down);
+            }
+
+            if changed != modifiers {
+                self.modifiers.set(changed);
+
+                let e = ModifiersChangedEvent { modifiers: changed };
+                self.pending.push(self.window, Event::ModifiersChanged(e));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn mouse_button(&self, button: MouseButton, down: bool) -> Result<(), Error> {
+        let e = MouseButtonEvent {
+            down,
+            button,
+            modifiers: self.modifiers.get(),
+        };
+
+        self.pending.push(self.window, Event::MouseButton(e));
+        Ok(())
+    }
+
+    #[allow(clippy::cast_sign_loss)]
+    fn move_pointer(&self, x: i32, y: i32) -> Result<(), Error> {
+        let e = MouseMoveEvent {
+            x_pos: x.max(0) as u32,
+            y_pos: y.max(0) as u32,
+            modifiers: self.modifiers.get(),
+        };
+
+        self.pending.push(self.window, Event::MouseMove(e));
+
+        // the motion is only known after the first move
+        if let Some((last_x, last_y)) = self.pointer.replace(Some((x, y)))
+            && self.raw_mouse_motion.get()
+        {
+            let e = RawMouseMotionEvent {
+                dx: f64::from(x - last_x),
+                dy: f64::from(y - last_y),
+            };
+
+            self.pending.push(self.window, Event::RawMouseMotion(e));
+        }
+
+        Ok(())
+    }
+
+    fn scroll(&self, direction: MouseWheelDirection) -> Result<(), Error> {
+        let e = MouseWheelEvent {
+            direction,
+            delta: direction.into(),
+            modifiers: self.modifiers.get(),
+        };
+
+        self.pending.push(self.window, Event::MouseWheel(e));
+        Ok(())
+    }
+}
+
+/// An in-memory window, that only keeps track of its state.
+#[derive(Debug)]
+pub struct Window {
+    shared: Rc<Shared>,
+    id: WindowId,
+    title: RefCell<String>,
+    visible: Cell<bool>,
+    minimized: Cell<bool>,
+    maximized: Cell<bool>,
+    window_level: Cell<WindowLevel>,
+    user_attention: Cell<Option<UserAttentionType>>,
+    position: Cell<PhysicalPosition>,
+    size: Cell<Rect>,
+    framebuffer: RefCell<Vec<u32>>,
+    raw_mouse_motion: Rc<Cell<bool>>,
+    cursor_grab: Cell<CursorGrabMode>,
+    cursor_visible: Cell<bool>,
+    cursor: Cell<Option<CursorIcon>>,
+    icon: RefCell<Option<Icon>>,
+    fullscreen: RefCell<Option<Fullscreen>>,
+    scale_factor: Cell<f64>,
+}
+
+impl WindowImpl for Window {
+    fn create(
+        event_loop: &EventLoop,
+        attributes: &WindowAttributes,
+        eventsys: Arc<dyn EventSystem>,
+    ) -> Result<Self, Error> {
+        let size = attributes.size;
+
+        if attributes.title.contains('\0') {
+            return Err(Error::InvalidTitle);
+        }
+
+        let shared = Rc::clone(&event_loop.shared);
+
+        let id = WindowId::from_raw(shared.next_id.get());
+        shared.next_id.set(shared.next_id.get() + 1);
+
+        shared.windows.borrow_mut().insert(id, eventsys);
+
+        Ok(Self {
+            shared,
+            id,
+            title: RefCell::new(attributes.title.clone()),
+            visible: Cell::new(attributes.visible),
+            minimized: Cell::new(false),
+            maximized: Cell::new(attributes.maximized),
+            window_level: Cell::new(if attributes.always_on_top {
+                WindowLevel::AlwaysOnTop
+            } else {
+                WindowLevel::Normal
+            }),
+            user_attention: Cell::new(None),
+            position: Cell::new(
+                attributes
+                    .position
+                    .map_or_else(PhysicalPosition::default, |(x, y)| {
+                        PhysicalPosition::new(i32::from(x), i32::from(y))
+                    }),
+            ),
+            size: Cell::new(size),
+            framebuffer: RefCell::new(vec![0; usize::from(size.x) * usize::from(size.y)]),
+            raw_mouse_motion: Rc::default(),
+            cursor_grab: Cell::default(),
+            cursor_visible: Cell::new(true),
+            cursor: Cell::new(Some(CursorIcon::Default)),
+            icon: RefCell::new(None),
+            fullscreen: RefCell::new(attributes.fullscreen.clone()),
+            scale_factor: Cell::new(monitor().scale_factor),
+        })
+    }
+
+    fn show(&self) -> Result<(), Error> {
+        self.visible.set(true);
+        Ok(())
+    }
+
+    fn set_visible(&self, visible: bool) -> Result<(), Error> {
+        self.visible.set(visible);
+        Ok(())
+    }
+
+    fn set_minimized(&self, minimized: bool) -> Result<(), Error> {
+        if self.minimized.replace(minimized) != minimized {
+            let e = if minimized {
+                WindowEvent::Minimized
+            } else {
+                WindowEvent::Restored
+            };
+            self.shared.pending.push(self.id, Event::Window(e));
+
+            let e = WindowEvent::Occluded(minimized);
+            self.shared.pending.push(self.id, Event::Window(e));
+        }
+
+        Ok(())
+    }
+
+    fn set_maximized(&self, maximized: bool) -> Result<(), Error> {
+        self.maximized.set(maximized);
+        Ok(())
+    }
+
+    fn is_maximized(&self) -> bool {
+        self.maximized.get()
+    }
+
+    // a headless window has no focus to take
+    fn focus_window(&self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn set_window_level(&self, level: WindowLevel) -> Result<(), Error> {
+        self.window_level.set(level);
+        Ok(())
+    }
+
+    fn request_user_attention(&self, attention: Option<UserAttentionType>) -> Result<(), Error> {
+        self.user_attention.set(attention);
+        Ok(())
+    }
+
+    fn set_title(&self, title: &str) -> Result<(), Error> {
+        if title.contains('\0') {
+            return Err(Error::InvalidTitle);
+        }
+
+        title.clone_into(&mut self.title.borrow_mut());
+        Ok(())
+    }
+
+    fn title(&self) -> String {
+        self.title.borrow().clone()
+    }
+
+    fn destroy(self) {
+        drop(self);
+    }
+
+    fn pump_messages(&mut self) -> Result<(), Error> {
+        self.shared.pump_messages();
+        Ok(())
+    }
+
+    fn wait_events(&mut self, timeout: Option<Duration>) -> Result<(), Error> {
+        self.shared.wait_events(timeout);
+        Ok(())
+    }
+
+    fn create_proxy(&self) -> EventLoopProxy {
+        EventLoopProxy {
+            window: self.id,
+            pending: Arc::clone(&self.shared.pending),
+        }
+    }
+
+    fn inner_size(&self) -> Rect {
+        self.size.get()
+    }
+
+    fn set_inner_size(&self, size: Rect) -> Result<(), Error> {
+        self.resize(size);
+
+        let e = WindowResizeEvent {
+            width: u32::from(size.x),
+            height: u32::from(size.y),
+        };
+        self.shared.pending.push(self.id, Event::WindowResize(e));
+
+        Ok(())
+    }
+
+    fn outer_position(&self) -> Result<PhysicalPosition, Error> {
+        Ok(self.position.get())
+    }
+
+    fn set_outer_position(&self, position: PhysicalPosition) -> Result<(), Error> {
+        if self.position.replace(position) != position {
+            let e = WindowEvent::Moved {
+                x: position.x,
+                y: position.y,
+            };
+            self.shared.pending.push(self.id, Event::Window(e));
+        }
+
+        Ok(())
+    }
+
+    // there is no frame around a headless window
+    fn outer_size(&self) -> Rect {
+        self.size.get()
+    }
+
+    // the limits only apply to resizing by the user, which a headless window never is
+    fn set_min_inner_size(&self, _: Option<Rect>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn set_max_inner_size(&self, _: Option<Rect>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn set_resize_increments(&self, _: Option<Rect>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn set_aspect_ratio(&self, _: Option<(u32, u32)>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn scale_factor(&self) -> f64 {
+        self.scale_factor.get()
+    }
+
+    fn request_redraw(&self) {
+        let mut pending = self.shared.pending.lock();
+
+        let queued = pending.events.iter().any(|(id, e)| {
+            *id == self.id && matches!(e, Event::Window(WindowEvent::RedrawRequested { .. }))
+        });
+
+        if !queued {
+            let e = WindowEvent::RedrawRequested { damage: None };
+            pending.events.push_back((self.id, Event::Window(e)));
+            drop(pending);
+
+            self.shared.pending.signal.notify_all();
+        }
+    }
+
+    fn present_buffer(
+        &mut self,
+        buffer: &[u32],
+        width: u16,
+        height: u16,
+        damage: &[DamageRect],
+    ) -> Result<(), Error> {
+        let expected = usize::from(width) * usize::from(height);
+        if buffer.len() != expected {
+            return Err(Error::BufferSize {
+                expected,
+                actual: buffer.len(),
+            });
+        }
+
+        let size = self.size.get();
+        let mut framebuffer = self.framebuffer.borrow_mut();
+
+        // like a real window, everything outside of the window is cut off
+        for rect in damage
+            .iter()
+            .filter_map(|rect| rect.clamp(width.min(size.x), height.min(size.y)))
+        {
+            for row in usize::from(rect.y)..usize::from(rect.y + rect.height) {
+                let src = row * usize::from(width) + usize::from(rect.x);
+                let dst = row * usize::from(size.x) + usize::from(rect.x);
+                let len = usize::from(rect.width);
+
+                framebuffer[dst..dst + len].copy_from_slice(&buffer[src..src + len]);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn capture(&self) -> Result<Image, Error> {
+        let size = self.size.get();
+
+        let data = self
+            .framebuffer
+            .borrow()
+            .iter()
+            .flat_map(|pixel| {
+                let [blue, green, red, _] = pixel.to_le_bytes();
+                [red, green, blue, u8::MAX]
+            })
+            .collect();
+
+        Ok(Image {
+            width: size.x,
+            height: size.y,
+            data,
+        })
+    }
+
+    fn create_input_injector(&self) -> Result<InputInjector, Error> {
+        Ok(InputInjector {
+            window: self.id,
+            pending: Arc::clone(&self.shared.pending),
+            modifiers: Cell::default(),
+            raw_mouse_motion: Rc::clone(&self.raw_mouse_motion),
+            pointer: Cell::new(None),
+        })
+    }
+
+    fn set_raw_mouse_motion(&self, enabled: bool) -> Result<(), Error> {
+        self.raw_mouse_motion.set(enabled);
+        Ok(())
+    }
+
+    fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), Error> {
+        self.cursor_grab.set(mode);
+        Ok(())
+    }
+
+    fn set_cursor_visible(&self, visible: bool) -> Result<(), Error> {
+        self.cursor_visible.set(visible);
+        Ok(())
+    }
+
+    fn set_cursor(&self, icon: CursorIcon) -> Result<(), Error> {
+        self.cursor.set(Some(icon));
+        Ok(())
+    }
+
+    fn set_custom_cursor(
+        &self,
+        rgba: &[u8],
+        width: u16,
+        height: u16,
+        _: (u16, u16),
+    ) -> Result<(), Error> {
+        crate::icon::check_rgba_size(rgba, width, height)?;
+
+        self.cursor.set(None);
+        Ok(())
+    }
+
+    fn set_icon(&self, icon: Option<Icon>) -> Result<(), Error> {
+        self.icon.replace(icon);
+        Ok(())
+    }
+
+    fn set_fullscreen(&self, fullscreen: Option<Fullscreen>) -> Result<(), Error> {
+        self.fullscreen.replace(fullscreen);
+        Ok(())
+    }
+
+    fn fullscreen(&self) -> Option<Fullscreen> {
+        self.fullscreen.borrow().clone()
+    }
+
+    fn current_monitor(&self) -> Option<MonitorHandle> {
+        Some(monitor())
+    }
+
+    fn available_monitors(&self) -> Vec<MonitorHandle> {
+        vec![monitor()]
+    }
+
+    fn primary_monitor(&self) -> Option<MonitorHandle> {
+        Some(monitor())
+    }
+
+    fn id(&self) -> WindowId {
+        self.id
+    }
+
+    fn raw_handle(&self) -> RawWindowHandle {
+        RawWindowHandle::Headless { window: self.id }
+    }
+}
+
+impl Window {
+    /// Queues an event, that is passed to the `EventSystem` on the next `pump_messages`.
+    pub fn inject_event(&self, e: Event) {
+        // resizing a real window changes its size before the event arrives
+        if let Event::WindowResize(resize) = &e {
+            let size = Rect::new(
+                u16::try_from(resize.width).unwrap_or(u16::MAX),
+                u16::try_from(resize.height).unwrap_or(u16::MAX),
+            );
+
+            self.resize(size);
+        }
+
+        if let Event::Window(WindowEvent::ScaleFactorChanged { scale_factor }) = &e {
+            self.scale_factor.set(*scale_factor);
+        }
+
+        self.shared.pending.push(self.id, e);
+    }
+
+    pub const fn is_visible(&self) -> bool {
+        self.visible.get()
+    }
+
+    pub const fn is_minimized(&self) -> bool {
+        self.minimized.get()
+    }
+
+    pub const fn window_level(&self) -> WindowLevel {
+        self.window_level.get()
+    }
+
+    pub const fn user_attention(&self) -> Option<UserAttentionType> {
+        self.user_attention.get()
+    }
+
+    pub const fn cursor_grab(&self) -> CursorGrabMode {
+        self.cursor_grab.get()
+    }
+
+    pub const fn is_cursor_visible(&self) -> bool {
+        self.cursor_visible.get()
+    }
+
+    pub const fn cursor(&self) -> Option<CursorIcon> {
+        self.cursor.get()
+    }
+
+    pub fn icon(&self) -> Option<Icon> {
+        self.icon.borrow().clone()
+    }
+
+    fn resize(&self, size: Rect) {
+        let old = self.size.replace(size);
+        let mut framebuffer = self.framebuffer.borrow_mut();
+
+        let mut resized = vec![0; usize::from(size.x) * usize::from(size.y)];
+
+        // keep the contents, that still fit into the window
+        let width = usize::from(old.x.min(size.x));
+        for row in 0..usize::from(old.y.min(size.y)) {
+            let src = row * usize::from(old.x);
+            let dst = row * usize::from(size.x);
+
+            resized[dst..dst + width].copy_from_slice(&framebuffer[src..src + width]);
+        }
+
+        *framebuffer = resized;
+    }
+}
+
+impl Drop for Window {
+    fn drop(&mut self) {
+        self.shared.windows.borrow_mut().remove(&self.id);
+    }
+}
+
+/// A 24 inch 1080p monitor, that can be switched to 720p.
+fn monitor() -> MonitorHandle {
+    let video_mode = |width, height| VideoMode {
+        monitor: 0,
+        native: 0,
+        size: Rect::new(width, height),
+        bit_depth: 24,
+        refresh_rate_millihertz: 60_000,
+    };
+
+    MonitorHandle {
+        id: 0,
+        name: String::from("HEADLESS-1"),
+        position: (0, 0),
+        size: Rect::new(1920, 1080),
+        physical_size_mm: (531, 299),
+        scale_factor: 1.0,
+        refresh_rate_millihertz: Some(60_000),
+        video_modes: vec![video_mode(1920, 1080), video_mode(1280, 720)],
+    }
+}
+
+#[cfg(test)]
+mod tests;
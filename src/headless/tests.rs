@@ -0,0 +1,276 @@
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::{
+    Error, EventLoop, Rect, Window, WindowId,
+    dpi::{PhysicalPosition, PhysicalSize},
+    events::{
+        Event, EventSystem, KeyEvent, ModifiersChangedEvent, MouseButtonEvent, MouseMoveEvent,
+        MouseWheelEvent, WindowEvent, WindowResizeEvent,
+    },
+    inputs::{Key, Modifiers},
+};
+
+/// Collects every event passed to it, in order.
+#[derive(Debug, Default)]
+struct Recorder {
+    events: Mutex<Vec<(WindowId, Event)>>,
+}
+
+impl Recorder {
+    fn push(&self, window: WindowId, e: Event) {
+        self.events
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push((window, e));
+    }
+
+    fn take(&self) -> Vec<(WindowId, Event)> {
+        std::mem::take(&mut *self.events.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+impl EventSystem for Recorder {
+    fn invoke_mouse_button_event(&self, window: WindowId, e: MouseButtonEvent) {
+        self.push(window, Event::MouseButton(e));
+    }
+
+    fn invoke_mouse_wheel_event(&self, window: WindowId, e: MouseWheelEvent) {
+        self.push(window, Event::MouseWheel(e));
+    }
+
+    fn invoke_mouse_move_event(&self, window: WindowId, e: MouseMoveEvent) {
+        self.push(window, Event::MouseMove(e));
+    }
+
+    fn invoke_key_event(&self, window: WindowId, e: KeyEvent) {
+        self.push(window, Event::Key(e));
+    }
+
+    fn invoke_window_resize_event(&self, window: WindowId, e: WindowResizeEvent) {
+        self.push(window, Event::WindowResize(e));
+    }
+
+    fn invoke_window_event(&self, window: WindowId, e: WindowEvent) {
+        self.push(window, Event::Window(e));
+    }
+
+    fn invoke_modifiers_changed_event(&self, window: WindowId, e: ModifiersChangedEvent) {
+        self.push(window, Event::ModifiersChanged(e));
+    }
+}
+
+fn window(event_loop: &EventLoop, recorder: &Arc<Recorder>, width: u32, height: u32) -> Window {
+    event_loop
+        .create_window(
+            "test",
+            Arc::clone(recorder) as Arc<dyn EventSystem>,
+            PhysicalPosition::new(0, 0),
+            PhysicalSize::new(width, height),
+        )
+        .unwrap()
+}
+
+#[test]
+fn injected_events_arrive_on_pump_messages() {
+    let mut event_loop = EventLoop::new().unwrap();
+    let recorder = Arc::new(Recorder::default());
+    let window = window(&event_loop, &recorder, 64, 48);
+
+    window.inject_event(Event::Key(KeyEvent {
+        key: Key::A,
+        down: true,
+        repeat: 0,
+        modifiers: Modifiers::SHIFT,
+    }));
+    window.inject_event(Event::MouseMove(MouseMoveEvent {
+        x_pos: 10,
+        y_pos: 20,
+        modifiers: Modifiers::empty(),
+    }));
+
+    // nothing is delivered before the event loop runs
+    assert!(recorder.take().is_empty());
+
+    event_loop.pump_messages().unwrap();
+
+    let events = recorder.take();
+    assert_eq!(events.len(), 2);
+
+    assert!(events.iter().all(|(id, _)| *id == window.id()));
+    assert!(matches!(
+        events[0].1,
+        Event::Key(KeyEvent {
+            key: Key::A,
+            down: true,
+            modifiers: Modifiers::SHIFT,
+            ..
+        })
+    ));
+    assert!(matches!(
+        events[1].1,
+        Event::MouseMove(MouseMoveEvent {
+            x_pos: 10,
+            y_pos: 20,
+            ..
+        })
+    ));
+
+    event_loop.pump_messages().unwrap();
+    assert!(recorder.take().is_empty());
+}
+
+#[test]
+fn injected_input_is_routed_to_its_window() {
+    let mut event_loop = EventLoop::new().unwrap();
+    let first = Arc::new(Recorder::default());
+    let second = Arc::new(Recorder::default());
+    let _first_window = window(&event_loop, &first, 64, 48);
+    let second_window = window(&event_loop, &second, 64, 48);
+
+    let injector = second_window.create_input_injector().unwrap();
+    injector.key(Key::LShift, true).unwrap();
+    injector.move_pointer(3, 4).unwrap();
+
+    event_loop.pump_messages().unwrap();
+
+    assert!(first.take().is_empty());
+
+    let events = second.take();
+    assert!(matches!(
+        events[0].1,
+        Event::Key(KeyEvent {
+            key: Key::LShift,
+            down: true,
+            ..
+        })
+    ));
+    assert!(matches!(
+        events[1].1,
+        Event::ModifiersChanged(ModifiersChangedEvent {
+            modifiers: Modifiers::SHIFT
+        })
+    ));
+    assert!(matches!(
+        events[2].1,
+        Event::MouseMove(MouseMoveEvent {
+            x_pos: 3,
+            y_pos: 4,
+            modifiers: Modifiers::SHIFT,
+        })
+    ));
+}
+
+#[test]
+fn window_state() {
+    let event_loop = EventLoop::new().unwrap();
+    let recorder = Arc::new(Recorder::default());
+    let window = window(&event_loop, &recorder, 64, 48);
+
+    assert_eq!(window.title(), "test");
+    assert_eq!(window.inner_size(), Rect::new(64, 48));
+    assert!(!window.is_visible());
+
+    window.set_title("renamed").unwrap();
+    assert_eq!(window.title(), "renamed");
+    assert!(window.set_title("nul\0").is_err());
+    assert_eq!(window.title(), "renamed");
+
+    window.show().unwrap();
+    assert!(window.is_visible());
+
+    window.set_inner_size(PhysicalSize::new(32, 16)).unwrap();
+    assert_eq!(window.inner_size(), Rect::new(32, 16));
+}
+
+#[test]
+fn injected_resize_saturates() {
+    let mut event_loop = EventLoop::new().unwrap();
+    let recorder = Arc::new(Recorder::default());
+    let window = window(&event_loop, &recorder, 64, 48);
+
+    window.inject_event(Event::WindowResize(WindowResizeEvent {
+        width: 70_000,
+        height: 16,
+    }));
+    assert_eq!(window.inner_size(), Rect::new(u16::MAX, 16));
+
+    // the event itself is passed on unchanged
+    event_loop.pump_messages().unwrap();
+    assert!(matches!(
+        recorder.take()[..],
+        [(
+            _,
+            Event::WindowResize(WindowResizeEvent {
+                width: 70_000,
+                height: 16
+            })
+        )]
+    ));
+}
+
+#[test]
+fn custom_cursor_checks_the_size_in_bytes() {
+    let event_loop = EventLoop::new().unwrap();
+    let recorder = Arc::new(Recorder::default());
+    let window = window(&event_loop, &recorder, 64, 48);
+
+    assert!(matches!(
+        window.set_custom_cursor(&[0; 15], 2, 2, (0, 0)),
+        Err(Error::RgbaSize {
+            expected: 16,
+            actual: 15
+        })
+    ));
+    assert!(window.set_custom_cursor(&[0; 16], 2, 2, (1, 1)).is_ok());
+}
+
+#[test]
+fn present_buffer_and_capture() {
+    let event_loop = EventLoop::new().unwrap();
+    let recorder = Arc::new(Recorder::default());
+    let mut window = window(&event_loop, &recorder, 2, 2);
+
+    let buffer = [0x00FF_0000, 0x0000_FF00, 0x0000_00FF, 0x0012_3456];
+    window.present_buffer(&buffer, 2, 2).unwrap();
+
+    let image = window.capture().unwrap();
+    assert_eq!((image.width, image.height), (2, 2));
+    assert_eq!(
+        image.data,
+        [
+            0xFF, 0x00, 0x00, 0xFF, //
+            0x00, 0xFF, 0x00, 0xFF, //
+            0x00, 0x00, 0xFF, 0xFF, //
+            0x12, 0x34, 0x56, 0xFF,
+        ]
+    );
+
+    // a buffer of the wrong size is rejected, and the contents are kept
+    assert!(window.present_buffer(&buffer[..3], 2, 2).is_err());
+    assert_eq!(window.capture().unwrap().data, image.data);
+}
+
+#[test]
+fn redraw_requests_are_coalesced() {
+    let mut event_loop = EventLoop::new().unwrap();
+    let recorder = Arc::new(Recorder::default());
+    let window = window(&event_loop, &recorder, 64, 48);
+
+    window.request_redraw();
+    window.request_redraw();
+    window.request_redraw();
+
+    event_loop.pump_messages().unwrap();
+
+    let events = recorder.take();
+    assert_eq!(events.len(), 1);
+    assert!(matches!(
+        events[0].1,
+        Event::Window(WindowEvent::RedrawRequested { damage: None })
+    ));
+
+    // once delivered, the next request is a new event
+    window.request_redraw();
+    event_loop.pump_messages().unwrap();
+    assert_eq!(recorder.take().len(), 1);
+}
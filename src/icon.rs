@@ -0,0 +1,93 @@
+use crate::{Error, Image};
+
+/// A window icon, set with `Window::set_icon`.
+///
+/// Holds one or more sizes of the same image, the windowing system picks the one
+/// that fits best, for example in the title bar or a task switcher.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Icon {
+    images: Vec<Image>,
+}
+
+impl Icon {
+    /// Creates an icon from 8 bit RGBA pixels, stored row by row.
+    ///
+    /// # Errors
+    /// Returns an `Error` if `rgba` doesn't hold `width` * `height` pixels.
+    pub fn from_rgba(rgba: Vec<u8>, width: u16, height: u16) -> Result<Self, Error> {
+        Ok(Self {
+            images: vec![image(rgba, width, height)?],
+        })
+    }
+
+    /// Adds another size of the icon.
+    ///
+    /// # Errors
+    /// Returns an `Error` if `rgba` doesn't hold `width` * `height` pixels.
+    pub fn with_size(mut self, rgba: Vec<u8>, width: u16, height: u16) -> Result<Self, Error> {
+        self.images.push(image(rgba, width, height)?);
+        Ok(self)
+    }
+
+    /// Returns all sizes of the icon, in the order they were added.
+    #[must_use]
+    pub fn images(&self) -> &[Image] {
+        &self.images
+    }
+}
+
+fn image(data: Vec<u8>, width: u16, height: u16) -> Result<Image, Error> {
+    check_rgba_size(&data, width, height)?;
+
+    Ok(Image {
+        width,
+        height,
+        data,
+    })
+}
+
+/// Checks that `rgba` holds `width` * `height` pixels of 4 bytes each, like icons and cursors.
+pub(crate) fn check_rgba_size(rgba: &[u8], width: u16, height: u16) -> Result<(), Error> {
+    let expected = usize::from(width) * usize::from(height) * 4;
+    if rgba.len() != expected {
+        return Err(Error::RgbaSize {
+            expected,
+            actual: rgba.len(),
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_rgba_checks_the_size_in_bytes() {
+        assert!(matches!(
+            Icon::from_rgba(vec![0; 15], 2, 2),
+            Err(Error::RgbaSize {
+                expected: 16,
+                actual: 15
+            })
+        ));
+        assert!(Icon::from_rgba(vec![0; 16], 2, 2).is_ok());
+    }
+
+    #[test]
+    fn with_size_keeps_the_order() {
+        let icon = Icon::from_rgba(vec![0; 4], 1, 1)
+            .and_then(|icon| icon.with_size(vec![0; 64], 4, 4))
+            .and_then(|icon| icon.with_size(vec![0; 16], 2, 2))
+            .unwrap();
+
+        let sizes: Vec<_> = icon
+            .images()
+            .iter()
+            .map(|image| (image.width, image.height))
+            .collect();
+        assert_eq!(sizes, [(1, 1), (4, 4), (2, 2)]);
+        assert!(icon.with_size(vec![0; 3], 1, 1).is_err());
+    }
+}
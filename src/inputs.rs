@@ -158,6 +158,22 @@ pub enum Key {
     Unidentified = 0x0,
 }
 
+impl Key {
+    /// Returns the modifier, that is changed by this key.
+    #[cfg(any(feature = "headless", target_os = "linux"))]
+    pub(crate) const fn modifier(self) -> Option<Modifiers> {
+        match self {
+            Self::Shift | Self::LShift | Self::RShift => Some(Modifiers::SHIFT),
+            Self::Ctrl | Self::LCtrl | Self::RCtrl => Some(Modifiers::CTRL),
+            Self::Menu | Self::LAlt | Self::RAlt => Some(Modifiers::ALT),
+            Self::LWin | Self::RWin => Some(Modifiers::SUPER),
+            Self::Caps => Some(Modifiers::CAPS_LOCK),
+            Self::NumLock => Some(Modifiers::NUM_LOCK),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u32)]
 pub enum MouseButton {
@@ -186,4 +202,131 @@ impl MouseButton {
 pub enum MouseWheelDirection {
     Up = 1,
     Down = -1,
+
+    /// Horizontal scrolling, as done by tilting the wheel or on touchpads.
+    Left = -2,
+    Right = 2,
+}
+
+/// The distance of a scroll, positive values scroll up or right.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScrollDelta {
+    /// Lines, one per notch of a mouse wheel.
+    ///
+    /// High-resolution wheels report fractions of a notch.
+    Lines { x: f64, y: f64 },
+
+    /// Pixels, as reported by touchpads.
+    Pixels { x: f64, y: f64 },
+}
+
+impl ScrollDelta {
+    /// Returns the direction of the larger axis, or `None` for an empty delta.
+    #[must_use]
+    pub fn direction(self) -> Option<MouseWheelDirection> {
+        let (Self::Lines { x, y } | Self::Pixels { x, y }) = self;
+
+        if x.abs() > y.abs() {
+            Some(if x < 0.0 {
+                MouseWheelDirection::Left
+            } else {
+                MouseWheelDirection::Right
+            })
+        } else if y > 0.0 {
+            Some(MouseWheelDirection::Up)
+        } else if y < 0.0 {
+            Some(MouseWheelDirection::Down)
+        } else {
+            None
+        }
+    }
+}
+
+impl From<MouseWheelDirection> for ScrollDelta {
+    /// A single notch of a mouse wheel.
+    fn from(direction: MouseWheelDirection) -> Self {
+        let (x, y) = match direction {
+            MouseWheelDirection::Up => (0.0, 1.0),
+            MouseWheelDirection::Down => (0.0, -1.0),
+            MouseWheelDirection::Left => (-1.0, 0.0),
+            MouseWheelDirection::Right => (1.0, 0.0),
+        };
+
+        Self::Lines { x, y }
+    }
+}
+
+bitflags::bitflags! {
+    /// Modifier keys held down and lock keys toggled on, while an input event happened.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+    pub struct Modifiers: u8 {
+        const SHIFT = 1 << 0;
+        const CTRL = 1 << 1;
+        const ALT = 1 << 2;
+        /// The Windows or Command key.
+        const SUPER = 1 << 3;
+        const CAPS_LOCK = 1 << 4;
+        const NUM_LOCK = 1 << 5;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn direction_of_the_larger_axis() {
+        let delta = |x, y| ScrollDelta::Pixels { x, y };
+
+        assert_eq!(delta(0.5, 3.0).direction(), Some(MouseWheelDirection::Up));
+        assert_eq!(
+            delta(-0.5, -3.0).direction(),
+            Some(MouseWheelDirection::Down)
+        );
+        assert_eq!(
+            delta(-3.0, 0.5).direction(),
+            Some(MouseWheelDirection::Left)
+        );
+        assert_eq!(
+            delta(3.0, -0.5).direction(),
+            Some(MouseWheelDirection::Right)
+        );
+
+        // vertical scrolling wins a tie
+        assert_eq!(
+            delta(2.0, -2.0).direction(),
+            Some(MouseWheelDirection::Down)
+        );
+    }
+
+    #[test]
+    fn zero_delta_has_no_direction() {
+        assert_eq!(ScrollDelta::Lines { x: 0.0, y: 0.0 }.direction(), None);
+        assert_eq!(ScrollDelta::Pixels { x: -0.0, y: 0.0 }.direction(), None);
+    }
+
+    #[test]
+    fn direction_round_trip() {
+        // positive values scroll up or right
+        assert_eq!(
+            ScrollDelta::from(MouseWheelDirection::Up),
+            ScrollDelta::Lines { x: 0.0, y: 1.0 }
+        );
+        assert_eq!(
+            ScrollDelta::from(MouseWheelDirection::Left),
+            ScrollDelta::Lines { x: -1.0, y: 0.0 }
+        );
+
+        for direction in [
+            MouseWheelDirection::Up,
+            MouseWheelDirection::Down,
+            MouseWheelDirection::Left,
+            MouseWheelDirection::Right,
+        ] {
+            let delta = ScrollDelta::from(direction);
+
+            assert!(matches!(delta, ScrollDelta::Lines { .. }));
+            assert_eq!(delta.direction(), Some(direction));
+        }
+    }
 }
@@ -3,56 +3,305 @@
 #![allow(clippy::module_name_repetitions)]
 #![allow(clippy::struct_field_names)]
 
+pub mod builder;
+pub mod cursor;
+pub mod dpi;
 pub mod events;
+pub mod icon;
 pub mod inputs;
+pub mod monitor;
 
-use std::{ffi::c_void, sync::Arc};
+use std::{any::Any, ffi::c_void, sync::Arc, time::Duration};
 
-#[cfg(target_os = "windows")]
+#[cfg(feature = "headless")]
+mod headless;
+
+#[cfg(feature = "headless")]
+pub use headless::Error;
+#[cfg(feature = "headless")]
+use headless::{
+    EventLoop as TargetEventLoop, EventLoopProxy as TargetEventLoopProxy,
+    InputInjector as TargetInputInjector, MonitorId as TargetMonitorId, Window as TargetWindow,
+};
+
+#[cfg(all(target_os = "windows", not(feature = "headless")))]
 mod win32;
 
-#[cfg(target_os = "windows")]
+#[cfg(all(target_os = "windows", not(feature = "headless")))]
 pub use win32::Error;
-#[cfg(target_os = "windows")]
-use win32::Window as TargetWindow;
+#[cfg(all(target_os = "windows", not(feature = "headless")))]
+use win32::{
+    EventLoop as TargetEventLoop, EventLoopProxy as TargetEventLoopProxy,
+    InputInjector as TargetInputInjector, MonitorId as TargetMonitorId, Window as TargetWindow,
+};
 
-#[cfg(target_os = "linux")]
+#[cfg(all(target_os = "linux", not(feature = "headless")))]
 mod linux;
 
-#[cfg(target_os = "linux")]
-use linux::Window as TargetWindow;
+#[cfg(all(target_os = "linux", not(feature = "headless")))]
+use linux::{
+    EventLoop as TargetEventLoop, EventLoopProxy as TargetEventLoopProxy,
+    InputInjector as TargetInputInjector, MonitorId as TargetMonitorId, Window as TargetWindow,
+};
 
-#[cfg(target_os = "linux")]
+#[cfg(all(target_os = "linux", not(feature = "headless")))]
 pub use linux::Error;
 
+trait EventLoopImpl {
+    fn new() -> Result<Self, Error>
+    where
+        Self: Sized;
+
+    fn pump_messages(&mut self) -> Result<(), Error>;
+
+    fn wait_events(&mut self, timeout: Option<Duration>) -> Result<(), Error>;
+
+    fn available_monitors(&self) -> Vec<monitor::MonitorHandle>;
+
+    fn primary_monitor(&self) -> Option<monitor::MonitorHandle>;
+}
+
+trait EventLoopProxyImpl {
+    fn wake_up(&self, event: Option<events::UserEvent>) -> Result<(), Error>;
+}
+
+trait InputInjectorImpl {
+    fn key(&self, key: inputs::Key, down: bool) -> Result<(), Error>;
+
+    fn mouse_button(&self, button: inputs::MouseButton, down: bool) -> Result<(), Error>;
+
+    fn move_pointer(&self, x: i32, y: i32) -> Result<(), Error>;
+
+    fn scroll(&self, direction: inputs::MouseWheelDirection) -> Result<(), Error>;
+}
+
 trait WindowImpl {
     fn create(
-        title: &str,
+        event_loop: &TargetEventLoop,
+        attributes: &builder::WindowAttributes,
         eventsys: Arc<dyn events::EventSystem>,
-        window_pos_x: i16,
-        window_pos_y: i16,
-        window_width: u16,
-        window_height: u16,
     ) -> Result<Self, Error>
     where
         Self: Sized;
 
     fn show(&self) -> Result<(), Error>;
 
+    fn set_visible(&self, visible: bool) -> Result<(), Error>;
+
+    fn set_minimized(&self, minimized: bool) -> Result<(), Error>;
+
+    fn set_maximized(&self, maximized: bool) -> Result<(), Error>;
+
+    fn is_maximized(&self) -> bool;
+
+    fn focus_window(&self) -> Result<(), Error>;
+
+    fn set_window_level(&self, level: WindowLevel) -> Result<(), Error>;
+
+    fn request_user_attention(&self, attention: Option<UserAttentionType>) -> Result<(), Error>;
+
     fn set_title(&self, title: &str) -> Result<(), Error>;
 
+    fn title(&self) -> String;
+
     fn destroy(self);
 
     fn pump_messages(&mut self) -> Result<(), Error>;
 
+    fn wait_events(&mut self, timeout: Option<Duration>) -> Result<(), Error>;
+
+    fn create_proxy(&self) -> TargetEventLoopProxy;
+
     fn inner_size(&self) -> Rect;
 
+    fn set_inner_size(&self, size: Rect) -> Result<(), Error>;
+
+    fn outer_position(&self) -> Result<dpi::PhysicalPosition, Error>;
+
+    fn set_outer_position(&self, position: dpi::PhysicalPosition) -> Result<(), Error>;
+
+    fn outer_size(&self) -> Rect;
+
+    fn set_min_inner_size(&self, size: Option<Rect>) -> Result<(), Error>;
+
+    fn set_max_inner_size(&self, size: Option<Rect>) -> Result<(), Error>;
+
+    fn set_resize_increments(&self, increments: Option<Rect>) -> Result<(), Error>;
+
+    fn set_aspect_ratio(&self, ratio: Option<(u32, u32)>) -> Result<(), Error>;
+
+    fn scale_factor(&self) -> f64;
+
+    fn request_redraw(&self);
+
+    fn present_buffer(
+        &mut self,
+        buffer: &[u32],
+        width: u16,
+        height: u16,
+        damage: &[DamageRect],
+    ) -> Result<(), Error>;
+
+    fn capture(&self) -> Result<Image, Error>;
+
+    fn create_input_injector(&self) -> Result<TargetInputInjector, Error>;
+
+    fn set_raw_mouse_motion(&self, enabled: bool) -> Result<(), Error>;
+
+    fn set_cursor_grab(&self, mode: cursor::CursorGrabMode) -> Result<(), Error>;
+
+    fn set_cursor_visible(&self, visible: bool) -> Result<(), Error>;
+
+    fn set_cursor(&self, icon: cursor::CursorIcon) -> Result<(), Error>;
+
+    fn set_custom_cursor(
+        &self,
+        rgba: &[u8],
+        width: u16,
+        height: u16,
+        hotspot: (u16, u16),
+    ) -> Result<(), Error>;
+
+    fn set_icon(&self, icon: Option<icon::Icon>) -> Result<(), Error>;
+
+    fn set_fullscreen(&self, fullscreen: Option<monitor::Fullscreen>) -> Result<(), Error>;
+
+    fn fullscreen(&self) -> Option<monitor::Fullscreen>;
+
+    fn current_monitor(&self) -> Option<monitor::MonitorHandle>;
+
+    fn available_monitors(&self) -> Vec<monitor::MonitorHandle>;
+
+    fn primary_monitor(&self) -> Option<monitor::MonitorHandle>;
+
+    fn id(&self) -> WindowId;
+
     fn raw_handle(&self) -> RawWindowHandle;
 }
 
+/// Owns the connection to the windowing system.
+///
+/// Any number of windows can be created on a single `EventLoop`,
+/// and all of them are processed by one call to `pump_messages`.
+#[derive(Debug)]
+pub struct EventLoop {
+    event_loop: TargetEventLoop,
+}
+
+impl EventLoop {
+    /// Connects to the windowing system.
+    ///
+    /// # Errors
+    /// Returns an `Error` if connecting to the windowing system failed.
+    #[inline]
+    pub fn new() -> Result<Self, Error> {
+        let event_loop = <TargetEventLoop as EventLoopImpl>::new()?;
+
+        Ok(Self { event_loop })
+    }
+
+    /// Creates a new Window on this event loop, that stays hidden until `Window::show`.
+    ///
+    /// Use a `WindowBuilder` for any other attributes.
+    ///
+    /// # Errors
+    /// Returns an `Error` if creating the window failed.
+    #[inline]
+    pub fn create_window(
+        &self,
+        name: &str,
+        eventsys: Arc<dyn events::EventSystem>,
+        pos: impl Into<dpi::Position>,
+        size: impl Into<dpi::Size>,
+    ) -> Result<Window, Error> {
+        Window::builder(name, pos, size).build(self, eventsys)
+    }
+
+    /// Creates a new Window on this event loop, whose events are collected in a queue
+    /// instead of being passed to an `EventSystem`.
+    ///
+    /// Use `Window::poll_event` or `Window::events` to receive them.
+    ///
+    /// # Errors
+    /// Returns an `Error` if creating the window failed.
+    #[inline]
+    pub fn create_queued_window(
+        &self,
+        name: &str,
+        pos: impl Into<dpi::Position>,
+        size: impl Into<dpi::Size>,
+    ) -> Result<Window, Error> {
+        Window::builder(name, pos, size).build_queued(self)
+    }
+
+    /// Processes the events of all windows created on this event loop.
+    ///
+    /// # Errors
+    #[inline]
+    pub fn pump_messages(&mut self) -> Result<(), Error> {
+        <TargetEventLoop as EventLoopImpl>::pump_messages(&mut self.event_loop)
+    }
+
+    /// Blocks until an event arrives or `timeout` expires, then processes the events
+    /// of all windows created on this event loop.
+    ///
+    /// Waits indefinitely if `timeout` is `None`.
+    ///
+    /// # Errors
+    #[inline]
+    pub fn wait_events(&mut self, timeout: Option<Duration>) -> Result<(), Error> {
+        <TargetEventLoop as EventLoopImpl>::wait_events(&mut self.event_loop, timeout)
+    }
+
+    /// Returns all monitors of the desktop.
+    ///
+    /// Returns an empty list, if the windowing system does not report monitors.
+    #[inline]
+    #[must_use]
+    pub fn available_monitors(&self) -> Vec<monitor::MonitorHandle> {
+        <TargetEventLoop as EventLoopImpl>::available_monitors(&self.event_loop)
+    }
+
+    /// Returns the monitor, that the user marked as the main one.
+    #[inline]
+    #[must_use]
+    pub fn primary_monitor(&self) -> Option<monitor::MonitorHandle> {
+        <TargetEventLoop as EventLoopImpl>::primary_monitor(&self.event_loop)
+    }
+}
+
+/// Wakes up the event loop of a window from any thread.
+#[derive(Debug, Clone)]
+pub struct EventLoopProxy {
+    proxy: TargetEventLoopProxy,
+}
+
+impl EventLoopProxy {
+    /// Wakes up the event loop without sending an event.
+    ///
+    /// # Errors
+    #[inline]
+    pub fn wake_up(&self) -> Result<(), Error> {
+        <TargetEventLoopProxy as EventLoopProxyImpl>::wake_up(&self.proxy, None)
+    }
+
+    /// Wakes up the event loop and delivers `payload` as a `UserEvent` to the window.
+    ///
+    /// # Errors
+    #[inline]
+    pub fn send_event<T: Any + Send>(&self, payload: T) -> Result<(), Error> {
+        let event = events::UserEvent {
+            payload: Box::new(payload),
+        };
+
+        <TargetEventLoopProxy as EventLoopProxyImpl>::wake_up(&self.proxy, Some(event))
+    }
+}
+
 #[derive(Debug)]
 pub struct Window {
     window: TargetWindow,
+    queue: Option<Arc<events::EventQueue>>,
 }
 
 impl Window {
@@ -64,20 +313,87 @@ impl Window {
     pub fn create(
         name: &str,
         eventsys: Arc<dyn events::EventSystem>,
-        pos: Rect,
-        size: Rect,
+        pos: impl Into<dpi::Position>,
+        size: impl Into<dpi::Size>,
     ) -> Result<Self, Error> {
-        #[allow(clippy::cast_possible_wrap)]
-        let window = <TargetWindow as WindowImpl>::create(
-            name,
-            eventsys,
-            pos.x as i16,
-            pos.y as i16,
-            size.x,
-            size.y,
-        )?;
+        let event_loop = <TargetEventLoop as EventLoopImpl>::new()?;
 
-        Ok(Self { window })
+        Self::create_on(&event_loop, &Self::builder(name, pos, size), eventsys)
+    }
+
+    /// Creates a new Window, whose events are collected in a queue
+    /// instead of being passed to an `EventSystem`.
+    ///
+    /// Use `poll_event` or `events` to receive them.
+    ///
+    /// # Errors
+    /// Returns an `Error` if creating the window failed.
+    #[inline]
+    pub fn create_queued(
+        name: &str,
+        pos: impl Into<dpi::Position>,
+        size: impl Into<dpi::Size>,
+    ) -> Result<Self, Error> {
+        let event_loop = <TargetEventLoop as EventLoopImpl>::new()?;
+
+        Self::create_queued_on(&event_loop, &Self::builder(name, pos, size))
+    }
+
+    /// The attributes of the `create` functions, which predate `WindowBuilder`.
+    fn builder(
+        name: &str,
+        pos: impl Into<dpi::Position>,
+        size: impl Into<dpi::Size>,
+    ) -> builder::WindowBuilder {
+        builder::WindowBuilder::new()
+            .with_title(name)
+            .with_position(pos)
+            .with_inner_size(size)
+            .with_visible(false)
+    }
+
+    fn create_queued_on(
+        event_loop: &TargetEventLoop,
+        builder: &builder::WindowBuilder,
+    ) -> Result<Self, Error> {
+        let queue = Arc::new(events::EventQueue::default());
+
+        let mut window = Self::create_on(event_loop, builder, queue.clone())?;
+        window.queue = Some(queue);
+
+        Ok(window)
+    }
+
+    fn create_on(
+        event_loop: &TargetEventLoop,
+        builder: &builder::WindowBuilder,
+        eventsys: Arc<dyn events::EventSystem>,
+    ) -> Result<Self, Error> {
+        // the window isn't on any monitor yet, so logical units are scaled for the primary one
+        let primary_monitor = <TargetEventLoop as EventLoopImpl>::primary_monitor(event_loop);
+        let scale_factor = primary_monitor
+            .as_ref()
+            .map_or(1.0, monitor::MonitorHandle::scale_factor);
+
+        let attributes = builder.attributes(
+            scale_factor,
+            primary_monitor.map(|monitor| {
+                let (x, y) = monitor.position();
+                let size = monitor.size();
+
+                (
+                    dpi::PhysicalPosition::new(x, y),
+                    dpi::PhysicalSize::from(size),
+                )
+            }),
+        );
+
+        let window = <TargetWindow as WindowImpl>::create(event_loop, &attributes, eventsys)?;
+
+        Ok(Self {
+            window,
+            queue: None,
+        })
     }
 
     /// Presents the window to the user.
@@ -88,14 +404,95 @@ impl Window {
         <TargetWindow as WindowImpl>::show(&self.window)
     }
 
+    /// Shows or hides the window, a hidden window has no entry in the taskbar.
+    ///
+    /// # Errors
+    /// Returns an `Error` if the request could not be sent.
+    #[inline]
+    pub fn set_visible(&self, visible: bool) -> Result<(), Error> {
+        <TargetWindow as WindowImpl>::set_visible(&self.window, visible)
+    }
+
+    /// Minimizes the window to the taskbar, or restores it.
+    ///
+    /// # Errors
+    /// Returns an `Error` if the request could not be sent, or the windowing system
+    /// does not let windows restore themselves, like Wayland.
+    #[inline]
+    pub fn set_minimized(&self, minimized: bool) -> Result<(), Error> {
+        <TargetWindow as WindowImpl>::set_minimized(&self.window, minimized)
+    }
+
+    /// Maximizes the window, or restores its previous size.
+    ///
+    /// # Errors
+    /// Returns an `Error` if the request could not be sent.
+    #[inline]
+    pub fn set_maximized(&self, maximized: bool) -> Result<(), Error> {
+        <TargetWindow as WindowImpl>::set_maximized(&self.window, maximized)
+    }
+
+    /// Returns `true`, if the window manager has maximized the window.
+    #[inline]
+    #[must_use]
+    pub fn is_maximized(&self) -> bool {
+        <TargetWindow as WindowImpl>::is_maximized(&self.window)
+    }
+
+    /// Raises the window and gives it the keyboard focus.
+    ///
+    /// Window managers may refuse to take the focus away from another application,
+    /// and highlight the window instead.
+    ///
+    /// # Errors
+    /// Returns an `Error` if the windowing system does not let windows focus themselves,
+    /// like Wayland.
+    #[inline]
+    pub fn focus_window(&self) -> Result<(), Error> {
+        <TargetWindow as WindowImpl>::focus_window(&self.window)
+    }
+
+    /// Keeps the window above or below all normal windows.
+    ///
+    /// # Errors
+    /// Returns an `Error` if the windowing system does not support window levels,
+    /// like Wayland.
+    #[inline]
+    pub fn set_window_level(&self, level: WindowLevel) -> Result<(), Error> {
+        <TargetWindow as WindowImpl>::set_window_level(&self.window, level)
+    }
+
+    /// Highlights the window in the taskbar, until it gets the focus.
+    ///
+    /// `None` removes the highlight again.
+    ///
+    /// # Errors
+    /// Returns an `Error` if the windowing system has no way to ask for attention,
+    /// like Wayland.
+    #[inline]
+    pub fn request_user_attention(
+        &self,
+        attention: Option<UserAttentionType>,
+    ) -> Result<(), Error> {
+        <TargetWindow as WindowImpl>::request_user_attention(&self.window, attention)
+    }
+
     /// Sets the title of the window.
     ///
     /// # Errors
+    /// Returns an `Error` if the title contains a NUL byte.
     #[inline]
     pub fn set_title(&self, title: &str) -> Result<(), crate::Error> {
         <TargetWindow as WindowImpl>::set_title(&self.window, title)
     }
 
+    /// Returns the title set by `create` or `set_title`.
+    #[inline]
+    #[must_use]
+    pub fn title(&self) -> String {
+        <TargetWindow as WindowImpl>::title(&self.window)
+    }
+
     /// Destroys the window.
     #[inline]
     pub fn destroy(self) {
@@ -110,6 +507,42 @@ impl Window {
         <TargetWindow as WindowImpl>::pump_messages(&mut self.window)
     }
 
+    /// Blocks until an event arrives or `timeout` expires, then processes window events.
+    ///
+    /// Waits indefinitely if `timeout` is `None`.
+    ///
+    /// # Errors
+    #[inline]
+    pub fn wait_events(&mut self, timeout: Option<Duration>) -> Result<(), Error> {
+        <TargetWindow as WindowImpl>::wait_events(&mut self.window, timeout)
+    }
+
+    /// Creates a proxy that can wake up this window's event loop from another thread.
+    #[inline]
+    #[must_use]
+    pub fn create_proxy(&self) -> EventLoopProxy {
+        EventLoopProxy {
+            proxy: <TargetWindow as WindowImpl>::create_proxy(&self.window),
+        }
+    }
+
+    /// Removes the oldest event from the queue filled by `pump_messages`.
+    ///
+    /// Always returns `None` for windows created with an `EventSystem`.
+    #[inline]
+    #[must_use]
+    pub fn poll_event(&self) -> Option<events::Event> {
+        self.queue.as_ref().and_then(|queue| queue.pop())
+    }
+
+    /// Returns an iterator that drains the queue filled by `pump_messages`.
+    ///
+    /// Always empty for windows created with an `EventSystem`.
+    #[inline]
+    pub fn events(&self) -> impl Iterator<Item = events::Event> + '_ {
+        std::iter::from_fn(|| self.poll_event())
+    }
+
     /// Returns the screen size.
     #[inline]
     #[must_use]
@@ -117,6 +550,289 @@ impl Window {
         <TargetWindow as WindowImpl>::inner_size(&self.window)
     }
 
+    /// Resizes the client area, which is reported by an `Event::WindowResize`.
+    ///
+    /// Logical sizes are scaled for the monitor the window is on.
+    ///
+    /// # Errors
+    /// Returns an `Error` if the request could not be sent.
+    #[inline]
+    pub fn set_inner_size(&self, size: impl Into<dpi::Size>) -> Result<(), Error> {
+        let size = size.into().to_physical(self.scale_factor()).to_rect();
+
+        <TargetWindow as WindowImpl>::set_inner_size(&self.window, size)
+    }
+
+    /// Returns the position of the top left corner of the frame, on the desktop.
+    ///
+    /// # Errors
+    /// Returns an `Error` if the windowing system does not reveal window positions,
+    /// like Wayland.
+    #[inline]
+    pub fn outer_position(&self) -> Result<dpi::PhysicalPosition, Error> {
+        <TargetWindow as WindowImpl>::outer_position(&self.window)
+    }
+
+    /// Moves the window, so the top left corner of its frame is at `position`.
+    ///
+    /// # Errors
+    /// Returns an `Error` if the windowing system does not let windows move themselves,
+    /// like Wayland.
+    #[inline]
+    pub fn set_outer_position(&self, position: impl Into<dpi::Position>) -> Result<(), Error> {
+        let position = position.into().to_physical(self.scale_factor());
+
+        <TargetWindow as WindowImpl>::set_outer_position(&self.window, position)
+    }
+
+    /// Returns the size of the window including the frame, that the window manager draws
+    /// around it.
+    #[inline]
+    #[must_use]
+    pub fn outer_size(&self) -> Rect {
+        <TargetWindow as WindowImpl>::outer_size(&self.window)
+    }
+
+    /// Keeps the user from making the client area smaller than `size`, `None` removes the limit.
+    ///
+    /// # Errors
+    /// Returns an `Error` if the request could not be sent.
+    #[inline]
+    pub fn set_min_inner_size(&self, size: Option<dpi::Size>) -> Result<(), Error> {
+        let size = size.map(|size| size.to_physical(self.scale_factor()).to_rect());
+
+        <TargetWindow as WindowImpl>::set_min_inner_size(&self.window, size)
+    }
+
+    /// Keeps the user from making the client area larger than `size`, `None` removes the limit.
+    ///
+    /// # Errors
+    /// Returns an `Error` if the request could not be sent.
+    #[inline]
+    pub fn set_max_inner_size(&self, size: Option<dpi::Size>) -> Result<(), Error> {
+        let size = size.map(|size| size.to_physical(self.scale_factor()).to_rect());
+
+        <TargetWindow as WindowImpl>::set_max_inner_size(&self.window, size)
+    }
+
+    /// Lets the user resize the window only in steps of `increments`, like the cells of a
+    /// terminal. `None` allows any size.
+    ///
+    /// # Errors
+    /// Returns an `Error` if the windowing system does not support resize increments,
+    /// like Wayland.
+    #[inline]
+    pub fn set_resize_increments(&self, increments: Option<dpi::Size>) -> Result<(), Error> {
+        let increments =
+            increments.map(|increments| increments.to_physical(self.scale_factor()).to_rect());
+
+        <TargetWindow as WindowImpl>::set_resize_increments(&self.window, increments)
+    }
+
+    /// Keeps the width and height of the client area at a ratio of `(width, height)` while
+    /// the user resizes it, like `(16, 9)`. `None`, or a ratio with a zero in it, removes it.
+    ///
+    /// # Errors
+    /// Returns an `Error` if the windowing system does not support aspect ratios,
+    /// like Wayland.
+    #[inline]
+    pub fn set_aspect_ratio(&self, ratio: Option<(u32, u32)>) -> Result<(), Error> {
+        let ratio = ratio.filter(|&(width, height)| width != 0 && height != 0);
+
+        <TargetWindow as WindowImpl>::set_aspect_ratio(&self.window, ratio)
+    }
+
+    /// Returns the factor, that converts logical sizes into the pixels of `inner_size`.
+    ///
+    /// Changes with the monitor the window is on, which is reported by
+    /// `WindowEvent::ScaleFactorChanged`.
+    #[inline]
+    #[must_use]
+    pub fn scale_factor(&self) -> f64 {
+        <TargetWindow as WindowImpl>::scale_factor(&self.window)
+    }
+
+    /// Schedules a `WindowEvent::RedrawRequested` for the whole client area.
+    ///
+    /// Multiple requests before the event loop runs again result in one event.
+    #[inline]
+    pub fn request_redraw(&self) {
+        <TargetWindow as WindowImpl>::request_redraw(&self.window);
+    }
+
+    /// Copies a XRGB8888 pixel buffer of `width` x `height` pixels into the window.
+    ///
+    /// Intended for software rendering, without going through a graphics API.
+    ///
+    /// # Errors
+    /// Returns an `Error` if `buffer` does not contain exactly `width * height` pixels.
+    #[inline]
+    pub fn present_buffer(&mut self, buffer: &[u32], width: u16, height: u16) -> Result<(), Error> {
+        let damage = DamageRect::new(0, 0, width, height);
+
+        self.present_buffer_with_damage(buffer, width, height, &[damage])
+    }
+
+    /// Like `present_buffer`, but only copies the `damage` areas of the buffer into the window.
+    ///
+    /// # Errors
+    /// Returns an `Error` if `buffer` does not contain exactly `width * height` pixels.
+    #[inline]
+    pub fn present_buffer_with_damage(
+        &mut self,
+        buffer: &[u32],
+        width: u16,
+        height: u16,
+        damage: &[DamageRect],
+    ) -> Result<(), Error> {
+        <TargetWindow as WindowImpl>::present_buffer(
+            &mut self.window,
+            buffer,
+            width,
+            height,
+            damage,
+        )
+    }
+
+    /// Reads back what the window currently shows.
+    ///
+    /// The window has to be visible on screen.
+    ///
+    /// # Errors
+    /// Returns an `Error` if the contents of the window could not be read.
+    #[inline]
+    pub fn capture(&self) -> Result<Image, Error> {
+        <TargetWindow as WindowImpl>::capture(&self.window)
+    }
+
+    /// Creates an `InputInjector`, that generates input for this window like a real user would.
+    ///
+    /// # Errors
+    /// Returns an `Error` if the windowing system does not support generating input.
+    #[inline]
+    pub fn create_input_injector(&self) -> Result<InputInjector, Error> {
+        let injector = <TargetWindow as WindowImpl>::create_input_injector(&self.window)?;
+
+        Ok(InputInjector { injector })
+    }
+
+    /// Enables or disables `RawMouseMotionEvent`s for this window, they are disabled by default.
+    ///
+    /// # Errors
+    /// Returns an `Error` if the windowing system does not report raw mouse motion.
+    #[inline]
+    pub fn set_raw_mouse_motion(&self, enabled: bool) -> Result<(), Error> {
+        <TargetWindow as WindowImpl>::set_raw_mouse_motion(&self.window, enabled)
+    }
+
+    /// Confines the cursor to this window or locks it in place.
+    ///
+    /// The grab is released while the window is not focused and taken again once it is.
+    ///
+    /// # Errors
+    /// Returns an `Error` if the cursor could not be grabbed, for example because another
+    /// application holds it.
+    #[inline]
+    pub fn set_cursor_grab(&self, mode: cursor::CursorGrabMode) -> Result<(), Error> {
+        <TargetWindow as WindowImpl>::set_cursor_grab(&self.window, mode)
+    }
+
+    /// Shows or hides the cursor, while it is over this window.
+    ///
+    /// # Errors
+    /// Returns an `Error` if the cursor could not be changed.
+    #[inline]
+    pub fn set_cursor_visible(&self, visible: bool) -> Result<(), Error> {
+        <TargetWindow as WindowImpl>::set_cursor_visible(&self.window, visible)
+    }
+
+    /// Changes the shape of the cursor, while it is over this window.
+    ///
+    /// On X11 the shape is loaded from the cursor theme, with the core cursor font as fallback.
+    ///
+    /// # Errors
+    /// Returns an `Error` if the cursor could not be loaded.
+    #[inline]
+    pub fn set_cursor(&self, icon: cursor::CursorIcon) -> Result<(), Error> {
+        <TargetWindow as WindowImpl>::set_cursor(&self.window, icon)
+    }
+
+    /// Changes the cursor to an image, while it is over this window.
+    ///
+    /// `rgba` holds `width` * `height` pixels of 4 bytes each, without premultiplied alpha.
+    /// The `hotspot` is the pixel, that points at the cursor position.
+    ///
+    /// # Errors
+    /// Returns an `Error` if `rgba` has the wrong size or the cursor could not be created.
+    #[inline]
+    pub fn set_custom_cursor(
+        &self,
+        rgba: &[u8],
+        width: u16,
+        height: u16,
+        hotspot: (u16, u16),
+    ) -> Result<(), Error> {
+        <TargetWindow as WindowImpl>::set_custom_cursor(&self.window, rgba, width, height, hotspot)
+    }
+
+    /// Sets the icon of this window, shown in its title bar and task switchers.
+    ///
+    /// `None` restores the default icon.
+    ///
+    /// # Errors
+    /// Returns an `Error` if the icon could not be set.
+    #[inline]
+    pub fn set_icon(&self, icon: Option<icon::Icon>) -> Result<(), Error> {
+        <TargetWindow as WindowImpl>::set_icon(&self.window, icon)
+    }
+
+    /// Makes the window cover a monitor, or restores it with `None`.
+    ///
+    /// # Errors
+    /// Returns an `Error` if the video mode could not be switched, or the windowing system
+    /// does not support the kind of fullscreen.
+    #[inline]
+    pub fn set_fullscreen(&self, fullscreen: Option<monitor::Fullscreen>) -> Result<(), Error> {
+        <TargetWindow as WindowImpl>::set_fullscreen(&self.window, fullscreen)
+    }
+
+    /// Returns the fullscreen state set by `set_fullscreen`.
+    #[inline]
+    #[must_use]
+    pub fn fullscreen(&self) -> Option<monitor::Fullscreen> {
+        <TargetWindow as WindowImpl>::fullscreen(&self.window)
+    }
+
+    /// Returns the monitor, that the window is on.
+    ///
+    /// Returns `None`, if the windowing system does not report monitors.
+    #[inline]
+    #[must_use]
+    pub fn current_monitor(&self) -> Option<monitor::MonitorHandle> {
+        <TargetWindow as WindowImpl>::current_monitor(&self.window)
+    }
+
+    /// Returns all monitors of the desktop, like `EventLoop::available_monitors`.
+    #[inline]
+    #[must_use]
+    pub fn available_monitors(&self) -> Vec<monitor::MonitorHandle> {
+        <TargetWindow as WindowImpl>::available_monitors(&self.window)
+    }
+
+    /// Returns the monitor, that the user marked as the main one.
+    #[inline]
+    #[must_use]
+    pub fn primary_monitor(&self) -> Option<monitor::MonitorHandle> {
+        <TargetWindow as WindowImpl>::primary_monitor(&self.window)
+    }
+
+    /// Returns the id that tags all events of this window.
+    #[inline]
+    #[must_use]
+    pub fn id(&self) -> WindowId {
+        <TargetWindow as WindowImpl>::id(&self.window)
+    }
+
     /// Returns a `RawWindowHandle`,  mostly used by graphics APIs.
     ///
     /// # Error
@@ -126,6 +842,173 @@ impl Window {
     }
 }
 
+#[cfg(feature = "headless")]
+impl Window {
+    /// Queues a synthetic event, that is passed to the window's `EventSystem`
+    /// on the next `pump_messages`.
+    ///
+    /// Injecting a `Event::WindowResize` also resizes the window, and a
+    /// `WindowEvent::ScaleFactorChanged` changes its `scale_factor`.
+    #[inline]
+    pub fn inject_event(&self, event: events::Event) {
+        self.window.inject_event(event);
+    }
+
+    /// Returns `true` while the window is shown.
+    #[inline]
+    #[must_use]
+    pub const fn is_visible(&self) -> bool {
+        self.window.is_visible()
+    }
+
+    /// Returns `true` after `set_minimized(true)`.
+    #[inline]
+    #[must_use]
+    pub const fn is_minimized(&self) -> bool {
+        self.window.is_minimized()
+    }
+
+    /// Returns the level set by `set_window_level`.
+    #[inline]
+    #[must_use]
+    pub const fn window_level(&self) -> WindowLevel {
+        self.window.window_level()
+    }
+
+    /// Returns the attention requested by `request_user_attention`.
+    #[inline]
+    #[must_use]
+    pub const fn user_attention(&self) -> Option<UserAttentionType> {
+        self.window.user_attention()
+    }
+
+    /// Returns the mode set by `set_cursor_grab`.
+    #[inline]
+    #[must_use]
+    pub const fn cursor_grab(&self) -> cursor::CursorGrabMode {
+        self.window.cursor_grab()
+    }
+
+    /// Returns `false` after the cursor was hidden with `set_cursor_visible`.
+    #[inline]
+    #[must_use]
+    pub const fn is_cursor_visible(&self) -> bool {
+        self.window.is_cursor_visible()
+    }
+
+    /// Returns the shape set by `set_cursor`, or `None` after `set_custom_cursor`.
+    #[inline]
+    #[must_use]
+    pub const fn cursor(&self) -> Option<cursor::CursorIcon> {
+        self.window.cursor()
+    }
+
+    /// Returns the icon set by `set_icon`.
+    #[inline]
+    #[must_use]
+    pub fn icon(&self) -> Option<icon::Icon> {
+        self.window.icon()
+    }
+}
+
+/// Generates synthetic input, that round-trips through the windowing system
+/// and arrives at the window on the next `pump_messages`.
+///
+/// Key events are only delivered to the window that has the keyboard focus.
+#[derive(Debug)]
+pub struct InputInjector {
+    injector: TargetInputInjector,
+}
+
+impl InputInjector {
+    /// Presses or releases a key.
+    ///
+    /// # Errors
+    /// Returns an `Error` if the key can not be generated.
+    #[inline]
+    pub fn key(&self, key: inputs::Key, down: bool) -> Result<(), Error> {
+        <TargetInputInjector as InputInjectorImpl>::key(&self.injector, key, down)
+    }
+
+    /// Presses and releases a key.
+    ///
+    /// # Errors
+    /// Returns an `Error` if the key can not be generated.
+    #[inline]
+    pub fn tap_key(&self, key: inputs::Key) -> Result<(), Error> {
+        self.key(key, true)?;
+        self.key(key, false)
+    }
+
+    /// Presses or releases a mouse button.
+    ///
+    /// # Errors
+    #[inline]
+    pub fn mouse_button(&self, button: inputs::MouseButton, down: bool) -> Result<(), Error> {
+        <TargetInputInjector as InputInjectorImpl>::mouse_button(&self.injector, button, down)
+    }
+
+    /// Presses and releases a mouse button.
+    ///
+    /// # Errors
+    #[inline]
+    pub fn click(&self, button: inputs::MouseButton) -> Result<(), Error> {
+        self.mouse_button(button, true)?;
+        self.mouse_button(button, false)
+    }
+
+    /// Moves the pointer to a position relative to the window.
+    ///
+    /// # Errors
+    #[inline]
+    pub fn move_pointer(&self, x: i32, y: i32) -> Result<(), Error> {
+        <TargetInputInjector as InputInjectorImpl>::move_pointer(&self.injector, x, y)
+    }
+
+    /// Scrolls the mouse wheel by one step.
+    ///
+    /// # Errors
+    #[inline]
+    pub fn scroll(&self, direction: inputs::MouseWheelDirection) -> Result<(), Error> {
+        <TargetInputInjector as InputInjectorImpl>::scroll(&self.injector, direction)
+    }
+}
+
+/// Identifies the window an event belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WindowId(u64);
+
+impl WindowId {
+    #[inline]
+    #[must_use]
+    pub(crate) const fn from_raw(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+/// Where a window is stacked, relative to other windows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WindowLevel {
+    #[default]
+    Normal,
+
+    /// Above all normal windows, like a picture in picture video.
+    AlwaysOnTop,
+
+    /// Below all normal windows, like a desktop widget.
+    AlwaysOnBottom,
+}
+
+/// How urgently a window asks for the attention of the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserAttentionType {
+    /// Flashes the window or sets it urgent, until it is focused.
+    Critical,
+
+    /// Highlights the window in the taskbar.
+    Informational,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Rect {
     pub x: u16,
@@ -140,6 +1023,51 @@ impl Rect {
     }
 }
 
+/// A rectangular area of a window in pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DamageRect {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+}
+
+impl DamageRect {
+    #[inline]
+    #[must_use]
+    pub const fn new(x: u16, y: u16, width: u16, height: u16) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Cuts off the parts outside of a `width` x `height` area.
+    ///
+    /// Returns `None` if nothing is left.
+    pub(crate) fn clamp(self, width: u16, height: u16) -> Option<Self> {
+        let right = self.x.saturating_add(self.width).min(width);
+        let bottom = self.y.saturating_add(self.height).min(height);
+
+        (self.x < right && self.y < bottom).then(|| Self {
+            x: self.x,
+            y: self.y,
+            width: right - self.x,
+            height: bottom - self.y,
+        })
+    }
+}
+
+/// An image with 8 bit RGBA pixels, stored row by row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: u16,
+    pub height: u16,
+    pub data: Vec<u8>,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RawWindowHandle {
     Win32 {
@@ -150,4 +1078,45 @@ pub enum RawWindowHandle {
         connection: *mut c_void,
         window: u32,
     },
+    Wayland {
+        display: *mut c_void,
+        surface: *mut c_void,
+    },
+    Headless {
+        window: WindowId,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn damage_inside_is_kept() {
+        let damage = DamageRect::new(4, 8, 16, 16);
+        assert_eq!(damage.clamp(64, 48), Some(damage));
+    }
+
+    #[test]
+    fn damage_outside_is_dropped() {
+        assert_eq!(DamageRect::new(64, 0, 8, 8).clamp(64, 48), None);
+        assert_eq!(DamageRect::new(0, 100, 8, 8).clamp(64, 48), None);
+        assert_eq!(DamageRect::new(4, 4, 0, 8).clamp(64, 48), None);
+    }
+
+    #[test]
+    fn damage_overlapping_the_edge_is_cut() {
+        assert_eq!(
+            DamageRect::new(60, 40, 10, 10).clamp(64, 48),
+            Some(DamageRect::new(60, 40, 4, 8))
+        );
+    }
+
+    #[test]
+    fn damage_past_u16_max_saturates() {
+        assert_eq!(
+            DamageRect::new(65_000, 0, 1_000, u16::MAX).clamp(u16::MAX, 48),
+            Some(DamageRect::new(65_000, 0, 535, 48))
+        );
+    }
 }
@@ -1,48 +1,126 @@
 #[derive(Debug)]
-pub enum X11Error {
+pub enum Error {
     Connection(x11rb::errors::ConnectionError),
     Id(x11rb::errors::ReplyOrIdError),
     Connect(x11rb::errors::ConnectError),
     Reply(x11rb::errors::ReplyError),
+    Poll(std::io::Error),
+    WakeUp(std::io::Error),
+    Parse(x11rb::errors::ParseError),
+    Unsupported(&'static str),
+    InvalidTitle,
+    Grab(x11rb::protocol::xproto::GrabStatus),
+    VideoMode(x11rb::protocol::randr::SetConfig),
+    BufferSize { expected: usize, actual: usize },
+    RgbaSize { expected: usize, actual: usize },
+    WaylandConnect(wayland_client::ConnectError),
+    WaylandGlobal(wayland_client::globals::GlobalError),
+    WaylandBind(wayland_client::globals::BindError),
+    WaylandDispatch(wayland_client::DispatchError),
+    Wayland(wayland_client::backend::WaylandError),
+    Shm(std::io::Error),
 }
 
-impl std::error::Error for X11Error {}
+impl std::error::Error for Error {}
 
-impl std::fmt::Display for X11Error {
+impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Connection(err) => write!(f, "{err}"),
             Self::Connect(err) => write!(f, "{err}"),
             Self::Id(err) => write!(f, "{err}"),
             Self::Reply(err) => write!(f, "{err}"),
+            Self::Poll(err) => write!(f, "Failed to wait for events: {err}"),
+            Self::WakeUp(err) => write!(f, "Failed to wake up event loop: {err}"),
+            Self::Parse(err) => write!(f, "{err}"),
+            Self::Unsupported(what) => write!(f, "Unsupported {what}"),
+            Self::InvalidTitle => write!(f, "Invalid title: contains a NUL byte"),
+            Self::Grab(status) => write!(f, "Failed to grab the pointer: {status:?}"),
+            Self::VideoMode(status) => write!(f, "Failed to switch the video mode: {status:?}"),
+            Self::BufferSize { expected, actual } => write!(
+                f,
+                "Invalid buffer size: expected {expected} pixels, got {actual}"
+            ),
+            Self::RgbaSize { expected, actual } => write!(
+                f,
+                "Invalid RGBA buffer size: expected {expected} bytes, got {actual}"
+            ),
+            Self::WaylandConnect(err) => write!(f, "{err}"),
+            Self::WaylandGlobal(err) => write!(f, "{err}"),
+            Self::WaylandBind(err) => write!(f, "{err}"),
+            Self::WaylandDispatch(err) => write!(f, "{err}"),
+            Self::Wayland(err) => write!(f, "{err}"),
+            Self::Shm(err) => write!(f, "Failed to create shared memory: {err}"),
         }
     }
 }
 
 #[cfg(target_os = "linux")]
-impl From<x11rb::errors::ReplyOrIdError> for X11Error {
+impl From<x11rb::errors::ReplyOrIdError> for Error {
     fn from(value: x11rb::errors::ReplyOrIdError) -> Self {
         Self::Id(value)
     }
 }
 
 #[cfg(target_os = "linux")]
-impl From<x11rb::errors::ConnectError> for X11Error {
+impl From<x11rb::errors::ConnectError> for Error {
     fn from(value: x11rb::errors::ConnectError) -> Self {
         Self::Connect(value)
     }
 }
 
 #[cfg(target_os = "linux")]
-impl From<x11rb::errors::ConnectionError> for X11Error {
+impl From<x11rb::errors::ConnectionError> for Error {
     fn from(value: x11rb::errors::ConnectionError) -> Self {
         Self::Connection(value)
     }
 }
 
 #[cfg(target_os = "linux")]
-impl From<x11rb::errors::ReplyError> for X11Error {
+impl From<x11rb::errors::ReplyError> for Error {
     fn from(value: x11rb::errors::ReplyError) -> Self {
         Self::Reply(value)
     }
 }
+
+#[cfg(target_os = "linux")]
+impl From<x11rb::errors::ParseError> for Error {
+    fn from(value: x11rb::errors::ParseError) -> Self {
+        Self::Parse(value)
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl From<wayland_client::ConnectError> for Error {
+    fn from(value: wayland_client::ConnectError) -> Self {
+        Self::WaylandConnect(value)
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl From<wayland_client::globals::GlobalError> for Error {
+    fn from(value: wayland_client::globals::GlobalError) -> Self {
+        Self::WaylandGlobal(value)
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl From<wayland_client::globals::BindError> for Error {
+    fn from(value: wayland_client::globals::BindError) -> Self {
+        Self::WaylandBind(value)
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl From<wayland_client::DispatchError> for Error {
+    fn from(value: wayland_client::DispatchError) -> Self {
+        Self::WaylandDispatch(value)
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl From<wayland_client::backend::WaylandError> for Error {
+    fn from(value: wayland_client::backend::WaylandError) -> Self {
+        Self::Wayland(value)
+    }
+}
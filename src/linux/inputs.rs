@@ -1,4 +1,4 @@
-use crate::inputs::{Key, MouseButton};
+use crate::inputs::{Key, Modifiers, MouseButton, MouseWheelDirection};
 
 static MAPPING: [Key; 255] = [
     Key::Unidentified,
@@ -14,7 +14,7 @@ static MAPPING: [Key; 255] = [
     Key::Digit9,
     Key::Digit0,
     Key::Minus,
-    Key::NumEqual,
+    Key::Plus,
     Key::BackSpace,
     Key::Tab,
     Key::Q,
@@ -30,7 +30,7 @@ static MAPPING: [Key; 255] = [
     Key::Bracket,
     Key::Bracket,
     Key::Enter,
-    Key::Ctrl,
+    Key::LCtrl,
     Key::A,
     Key::S,
     Key::D,
@@ -42,7 +42,7 @@ static MAPPING: [Key; 255] = [
     Key::L,
     Key::Semicolon,
     Key::Unidentified,
-    Key::Unidentified,
+    Key::Grave,
     Key::LShift,
     Key::Unidentified,
     Key::Z,
@@ -100,7 +100,7 @@ static MAPPING: [Key; 255] = [
     Key::Enter,
     Key::RCtrl,
     Key::Divide,
-    Key::Print,
+    Key::Snapshot,
     Key::RAlt,
     Key::Unidentified,
     Key::Home,
@@ -122,15 +122,13 @@ static MAPPING: [Key; 255] = [
     Key::Unidentified,
     Key::Pause,
     Key::Unidentified,
-    Key::Comma,
-    Key::Unidentified,
-    Key::Unidentified,
+    Key::Seperator,
     Key::Unidentified,
     Key::Unidentified,
     Key::Unidentified,
-    Key::Menu,
-    Key::Unidentified,
-    Key::Unidentified,
+    Key::LWin,
+    Key::RWin,
+    Key::Apps,
     Key::Unidentified,
     Key::Unidentified,
     Key::Unidentified,
@@ -141,9 +139,11 @@ static MAPPING: [Key; 255] = [
     Key::Unidentified,
     Key::Unidentified,
     Key::Unidentified,
+    Key::Help,
     Key::Unidentified,
     Key::Unidentified,
     Key::Unidentified,
+    Key::Sleep,
     Key::Unidentified,
     Key::Unidentified,
     Key::Unidentified,
@@ -196,6 +196,7 @@ static MAPPING: [Key; 255] = [
     Key::F22,
     Key::F23,
     Key::F24,
+    Key::ModeChange,
     Key::Unidentified,
     Key::Unidentified,
     Key::Unidentified,
@@ -210,8 +211,7 @@ static MAPPING: [Key; 255] = [
     Key::Unidentified,
     Key::Unidentified,
     Key::Unidentified,
-    Key::Unidentified,
-    Key::Unidentified,
+    Key::Print,
     Key::Unidentified,
     Key::Unidentified,
     Key::Unidentified,
@@ -267,6 +267,36 @@ pub fn x_translate_key(key: u8) -> Key {
     MAPPING[key as usize]
 }
 
+/// Translates a linux evdev keycode, as used by Wayland.
+pub fn evdev_translate_key(code: u32) -> Key {
+    usize::try_from(code)
+        .ok()
+        .and_then(|code| MAPPING.get(code))
+        .copied()
+        .unwrap_or(Key::Unidentified)
+}
+
+/// Reverse of `x_translate_key`.
+///
+/// Returns `None` for keys that have no keycode, which are `Clear`, `Accept`, `Select`
+/// and `Execute`. Their evdev codes, if any, are beyond the 255 keycodes of X11.
+pub fn x_keycode(key: Key) -> Option<u8> {
+    // the generic modifiers and the old names of the page keys share a keycode
+    let key = match key {
+        Key::Unidentified => return None,
+        Key::Shift => Key::LShift,
+        Key::Ctrl => Key::LCtrl,
+        Key::Menu => Key::LAlt,
+        Key::Prior => Key::PageUp,
+        Key::Next => Key::PageDown,
+        key => key,
+    };
+
+    let index = MAPPING.iter().position(|mapped| *mapped == key)?;
+
+    u8::try_from(index + 8).ok()
+}
+
 pub const fn x_translate_button(button: u8) -> MouseButton {
     match button {
         1 => MouseButton::Left,
@@ -275,3 +305,240 @@ pub const fn x_translate_button(button: u8) -> MouseButton {
         _ => MouseButton::Custom(button as _),
     }
 }
+
+/// Returns the scroll direction of the buttons, that the core protocol reports scrolling with.
+pub const fn x_wheel_direction(button: u8) -> Option<MouseWheelDirection> {
+    match button {
+        4 => Some(MouseWheelDirection::Up),
+        5 => Some(MouseWheelDirection::Down),
+        6 => Some(MouseWheelDirection::Left),
+        7 => Some(MouseWheelDirection::Right),
+        _ => None,
+    }
+}
+
+/// Translates a linux evdev button code, as used by Wayland.
+pub fn evdev_translate_button(button: u32) -> MouseButton {
+    match button {
+        0x110 => MouseButton::Left,
+        0x111 => MouseButton::Right,
+        0x112 => MouseButton::Middle,
+        // BTN_SIDE and following, numbered like the X11 buttons 8 and up
+        _ => MouseButton::Custom(
+            button
+                .checked_sub(0x113 - 8)
+                .and_then(|button| u8::try_from(button).ok())
+                .unwrap_or(u8::MAX),
+        ),
+    }
+}
+
+/// Reverse of `x_translate_button`.
+pub const fn x_button(button: MouseButton) -> u8 {
+    match button {
+        MouseButton::Left => 1,
+        MouseButton::Middle => 2,
+        MouseButton::Right => 3,
+        MouseButton::Custom(button) => button,
+    }
+}
+
+/// Translates a modifier mask, as found in X11 input events and Wayland modifier events.
+///
+/// Both use the bits of the X11 core modifiers, with Alt on Mod1, Num Lock on Mod2 and Super on Mod4.
+pub fn translate_modifiers(mask: u32) -> Modifiers {
+    const MAPPING: [(u32, Modifiers); 6] = [
+        (1 << 0, Modifiers::SHIFT),
+        (1 << 1, Modifiers::CAPS_LOCK),
+        (1 << 2, Modifiers::CTRL),
+        (1 << 3, Modifiers::ALT),
+        (1 << 4, Modifiers::NUM_LOCK),
+        (1 << 6, Modifiers::SUPER),
+    ];
+
+    MAPPING
+        .into_iter()
+        .filter(|(bit, _)| mask & bit != 0)
+        .fold(Modifiers::empty(), |modifiers, (_, modifier)| {
+            modifiers | modifier
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEYS: [Key; 137] = [
+        Key::BackSpace,
+        Key::Tab,
+        Key::Clear,
+        Key::Enter,
+        Key::Shift,
+        Key::Ctrl,
+        Key::Menu,
+        Key::Pause,
+        Key::Caps,
+        Key::Esc,
+        Key::Convert,
+        Key::NonConvert,
+        Key::Accept,
+        Key::ModeChange,
+        Key::Space,
+        Key::Prior,
+        Key::Next,
+        Key::End,
+        Key::Home,
+        Key::Left,
+        Key::Up,
+        Key::Right,
+        Key::Down,
+        Key::Select,
+        Key::Print,
+        Key::Execute,
+        Key::Snapshot,
+        Key::Insert,
+        Key::Delete,
+        Key::Help,
+        Key::Digit0,
+        Key::Digit1,
+        Key::Digit2,
+        Key::Digit3,
+        Key::Digit4,
+        Key::Digit5,
+        Key::Digit6,
+        Key::Digit7,
+        Key::Digit8,
+        Key::Digit9,
+        Key::A,
+        Key::B,
+        Key::C,
+        Key::D,
+        Key::E,
+        Key::F,
+        Key::G,
+        Key::H,
+        Key::I,
+        Key::J,
+        Key::K,
+        Key::L,
+        Key::M,
+        Key::N,
+        Key::O,
+        Key::P,
+        Key::Q,
+        Key::R,
+        Key::S,
+        Key::T,
+        Key::U,
+        Key::V,
+        Key::W,
+        Key::X,
+        Key::Y,
+        Key::Z,
+        Key::LWin,
+        Key::RWin,
+        Key::Apps,
+        Key::Sleep,
+        Key::Num0,
+        Key::Num1,
+        Key::Num2,
+        Key::Num3,
+        Key::Num4,
+        Key::Num5,
+        Key::Num6,
+        Key::Num7,
+        Key::Num8,
+        Key::Num9,
+        Key::Multiply,
+        Key::Add,
+        Key::Seperator,
+        Key::Subtract,
+        Key::Decimal,
+        Key::Divide,
+        Key::F1,
+        Key::F2,
+        Key::F3,
+        Key::F4,
+        Key::F5,
+        Key::F6,
+        Key::F7,
+        Key::F8,
+        Key::F9,
+        Key::F10,
+        Key::F11,
+        Key::F12,
+        Key::F13,
+        Key::F14,
+        Key::F15,
+        Key::F16,
+        Key::F17,
+        Key::F18,
+        Key::F19,
+        Key::F20,
+        Key::F21,
+        Key::F22,
+        Key::F23,
+        Key::F24,
+        Key::NumLock,
+        Key::Scroll,
+        Key::NumEqual,
+        Key::LShift,
+        Key::RShift,
+        Key::LCtrl,
+        Key::RCtrl,
+        Key::LAlt,
+        Key::RAlt,
+        Key::VolumeMute,
+        Key::VolumeDown,
+        Key::VolumeUp,
+        Key::MediaNext,
+        Key::MediaPause,
+        Key::MediaPrev,
+        Key::MediaStop,
+        Key::Semicolon,
+        Key::Plus,
+        Key::Comma,
+        Key::Minus,
+        Key::Period,
+        Key::Slash,
+        Key::Grave,
+        Key::Bracket,
+        Key::PageDown,
+        Key::PageUp,
+        Key::Unidentified,
+    ];
+
+    #[test]
+    fn every_key_round_trips_through_its_keycode() {
+        let mut missing = Vec::new();
+
+        for key in KEYS {
+            let Some(keycode) = x_keycode(key) else {
+                missing.push(key);
+                continue;
+            };
+
+            let expected = match key {
+                Key::Shift => Key::LShift,
+                Key::Ctrl => Key::LCtrl,
+                Key::Menu => Key::LAlt,
+                Key::Prior => Key::PageUp,
+                Key::Next => Key::PageDown,
+                key => key,
+            };
+
+            assert_eq!(x_translate_key(keycode), expected, "{key:?}");
+        }
+
+        assert_eq!(
+            missing,
+            [
+                Key::Clear,
+                Key::Accept,
+                Key::Select,
+                Key::Execute,
+                Key::Unidentified
+            ]
+        );
+    }
+}
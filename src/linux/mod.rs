@@ -1,6 +1,424 @@
 mod err;
 mod inputs;
+mod poll;
+mod wayland;
 mod x11;
+mod xkb;
 
-pub use err::X11Error as Error;
-pub use x11::Window;
+pub use err::Error;
+pub use x11::InputInjector;
+
+/// The `RandR` output of a monitor.
+pub type MonitorId = u32;
+
+use std::{sync::Arc, time::Duration};
+
+use crate::{
+    DamageRect, EventLoopImpl, EventLoopProxyImpl, Image, RawWindowHandle, Rect, UserAttentionType,
+    WindowId, WindowImpl, WindowLevel,
+    builder::WindowAttributes,
+    dpi::PhysicalPosition,
+    events::EventSystem,
+    monitor::{Fullscreen, MonitorHandle},
+};
+
+/// The display server connection, chosen at runtime.
+///
+/// Wayland is used if `WAYLAND_DISPLAY` is set, with X11 as the fallback.
+#[derive(Debug)]
+pub enum EventLoop {
+    X11(x11::EventLoop),
+    Wayland(wayland::EventLoop),
+}
+
+impl EventLoopImpl for EventLoop {
+    fn new() -> Result<Self, Error> {
+        if std::env::var_os("WAYLAND_DISPLAY").is_some_and(|display| !display.is_empty()) {
+            match wayland::EventLoop::new() {
+                Ok(event_loop) => return Ok(Self::Wayland(event_loop)),
+                Err(err) => log::warn!("failed to connect to Wayland, falling back to X11: {err}"),
+            }
+        }
+
+        Ok(Self::X11(x11::EventLoop::new()?))
+    }
+
+    fn pump_messages(&mut self) -> Result<(), Error> {
+        match self {
+            Self::X11(event_loop) => event_loop.pump_messages(),
+            Self::Wayland(event_loop) => event_loop.pump_messages(),
+        }
+    }
+
+    fn wait_events(&mut self, timeout: Option<Duration>) -> Result<(), Error> {
+        match self {
+            Self::X11(event_loop) => event_loop.wait_events(timeout),
+            Self::Wayland(event_loop) => event_loop.wait_events(timeout),
+        }
+    }
+
+    fn available_monitors(&self) -> Vec<MonitorHandle> {
+        match self {
+            Self::X11(event_loop) => event_loop.available_monitors(),
+            Self::Wayland(event_loop) => event_loop.available_monitors(),
+        }
+    }
+
+    fn primary_monitor(&self) -> Option<MonitorHandle> {
+        match self {
+            Self::X11(event_loop) => event_loop.primary_monitor(),
+            Self::Wayland(event_loop) => event_loop.primary_monitor(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum EventLoopProxy {
+    X11(x11::EventLoopProxy),
+    Wayland(wayland::EventLoopProxy),
+}
+
+impl EventLoopProxyImpl for EventLoopProxy {
+    fn wake_up(&self, event: Option<crate::events::UserEvent>) -> Result<(), Error> {
+        match self {
+            Self::X11(proxy) => proxy.wake_up(event),
+            Self::Wayland(proxy) => proxy.wake_up(event),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Window {
+    X11(x11::Window),
+    Wayland(wayland::Window),
+}
+
+impl WindowImpl for Window {
+    fn create(
+        event_loop: &EventLoop,
+        attributes: &WindowAttributes,
+        eventsys: Arc<dyn EventSystem>,
+    ) -> Result<Self, Error> {
+        if attributes.title.contains('\0') {
+            return Err(Error::InvalidTitle);
+        }
+
+        match event_loop {
+            EventLoop::X11(event_loop) => {
+                x11::Window::create(event_loop, attributes, eventsys).map(Self::X11)
+            }
+            EventLoop::Wayland(event_loop) => {
+                wayland::Window::create(event_loop, attributes, eventsys).map(Self::Wayland)
+            }
+        }
+    }
+
+    fn show(&self) -> Result<(), Error> {
+        match self {
+            Self::X11(window) => window.show(),
+            Self::Wayland(window) => window.show(),
+        }
+    }
+
+    fn set_visible(&self, visible: bool) -> Result<(), Error> {
+        match self {
+            Self::X11(window) => window.set_visible(visible),
+            Self::Wayland(window) => window.set_visible(visible),
+        }
+    }
+
+    fn set_minimized(&self, minimized: bool) -> Result<(), Error> {
+        match self {
+            Self::X11(window) => window.set_minimized(minimized),
+            Self::Wayland(window) => window.set_minimized(minimized),
+        }
+    }
+
+    fn set_maximized(&self, maximized: bool) -> Result<(), Error> {
+        match self {
+            Self::X11(window) => window.set_maximized(maximized),
+            Self::Wayland(window) => window.set_maximized(maximized),
+        }
+    }
+
+    fn is_maximized(&self) -> bool {
+        match self {
+            Self::X11(window) => window.is_maximized(),
+            Self::Wayland(window) => window.is_maximized(),
+        }
+    }
+
+    fn focus_window(&self) -> Result<(), Error> {
+        match self {
+            Self::X11(window) => window.focus_window(),
+            // activation needs a token from xdg-activation, which is not bound
+            Self::Wayland(_) => Err(Error::Unsupported("focusing windows on Wayland")),
+        }
+    }
+
+    fn set_window_level(&self, level: WindowLevel) -> Result<(), Error> {
+        match self {
+            Self::X11(window) => window.set_window_level(level),
+            Self::Wayland(_) if level == WindowLevel::Normal => Ok(()),
+            Self::Wayland(_) => Err(Error::Unsupported("window levels on Wayland")),
+        }
+    }
+
+    fn request_user_attention(&self, attention: Option<UserAttentionType>) -> Result<(), Error> {
+        match self {
+            Self::X11(window) => window.request_user_attention(attention),
+            Self::Wayland(_) => Err(Error::Unsupported("user attention on Wayland")),
+        }
+    }
+
+    fn set_title(&self, title: &str) -> Result<(), Error> {
+        if title.contains('\0') {
+            return Err(Error::InvalidTitle);
+        }
+
+        match self {
+            Self::X11(window) => window.set_title(title),
+            Self::Wayland(window) => window.set_title(title),
+        }
+    }
+
+    fn title(&self) -> String {
+        match self {
+            Self::X11(window) => window.title(),
+            Self::Wayland(window) => window.title(),
+        }
+    }
+
+    fn destroy(self) {
+        drop(self);
+    }
+
+    fn pump_messages(&mut self) -> Result<(), Error> {
+        match self {
+            Self::X11(window) => window.pump_messages(),
+            Self::Wayland(window) => window.pump_messages(),
+        }
+    }
+
+    fn wait_events(&mut self, timeout: Option<Duration>) -> Result<(), Error> {
+        match self {
+            Self::X11(window) => window.wait_events(timeout),
+            Self::Wayland(window) => window.wait_events(timeout),
+        }
+    }
+
+    fn create_proxy(&self) -> EventLoopProxy {
+        match self {
+            Self::X11(window) => EventLoopProxy::X11(window.create_proxy()),
+            Self::Wayland(window) => EventLoopProxy::Wayland(window.create_proxy()),
+        }
+    }
+
+    fn inner_size(&self) -> Rect {
+        match self {
+            Self::X11(window) => window.inner_size(),
+            Self::Wayland(window) => window.inner_size(),
+        }
+    }
+
+    fn set_inner_size(&self, size: Rect) -> Result<(), Error> {
+        match self {
+            Self::X11(window) => window.set_inner_size(size),
+            Self::Wayland(window) => {
+                window.set_inner_size(size);
+                Ok(())
+            }
+        }
+    }
+
+    fn outer_position(&self) -> Result<PhysicalPosition, Error> {
+        match self {
+            Self::X11(window) => window.outer_position(),
+            // clients don't know where the compositor put their surfaces
+            Self::Wayland(_) => Err(Error::Unsupported("window positions on Wayland")),
+        }
+    }
+
+    fn set_outer_position(&self, position: PhysicalPosition) -> Result<(), Error> {
+        match self {
+            Self::X11(window) => window.set_outer_position(position),
+            Self::Wayland(_) => Err(Error::Unsupported("window positions on Wayland")),
+        }
+    }
+
+    fn outer_size(&self) -> Rect {
+        match self {
+            Self::X11(window) => window.outer_size(),
+            // without server side decorations, the surface is all there is
+            Self::Wayland(window) => window.inner_size(),
+        }
+    }
+
+    fn set_min_inner_size(&self, size: Option<Rect>) -> Result<(), Error> {
+        match self {
+            Self::X11(window) => window.set_min_inner_size(size),
+            Self::Wayland(window) => window.set_min_inner_size(size),
+        }
+    }
+
+    fn set_max_inner_size(&self, size: Option<Rect>) -> Result<(), Error> {
+        match self {
+            Self::X11(window) => window.set_max_inner_size(size),
+            Self::Wayland(window) => window.set_max_inner_size(size),
+        }
+    }
+
+    fn set_resize_increments(&self, increments: Option<Rect>) -> Result<(), Error> {
+        match self {
+            Self::X11(window) => window.set_resize_increments(increments),
+            Self::Wayland(_) => Err(Error::Unsupported("resize increments on Wayland")),
+        }
+    }
+
+    fn set_aspect_ratio(&self, ratio: Option<(u32, u32)>) -> Result<(), Error> {
+        match self {
+            Self::X11(window) => window.set_aspect_ratio(ratio),
+            Self::Wayland(_) => Err(Error::Unsupported("aspect ratios on Wayland")),
+        }
+    }
+
+    fn scale_factor(&self) -> f64 {
+        match self {
+            Self::X11(window) => window.scale_factor(),
+            // buffers are not scaled, wl_surface.set_buffer_scale is never set
+            Self::Wayland(_) => 1.0,
+        }
+    }
+
+    fn request_redraw(&self) {
+        match self {
+            Self::X11(window) => window.request_redraw(),
+            Self::Wayland(window) => window.request_redraw(),
+        }
+    }
+
+    fn present_buffer(
+        &mut self,
+        buffer: &[u32],
+        width: u16,
+        height: u16,
+        damage: &[DamageRect],
+    ) -> Result<(), Error> {
+        match self {
+            Self::X11(window) => window.present_buffer(buffer, width, height, damage),
+            Self::Wayland(window) => window.present_buffer(buffer, width, height, damage),
+        }
+    }
+
+    fn capture(&self) -> Result<Image, Error> {
+        match self {
+            Self::X11(window) => window.capture(),
+            Self::Wayland(window) => Ok(window.capture()),
+        }
+    }
+
+    fn create_input_injector(&self) -> Result<InputInjector, Error> {
+        match self {
+            Self::X11(window) => window.create_input_injector(),
+            // there is no common protocol for synthetic input
+            Self::Wayland(_) => Err(Error::Unsupported("input injection on Wayland")),
+        }
+    }
+
+    fn set_raw_mouse_motion(&self, enabled: bool) -> Result<(), Error> {
+        match self {
+            Self::X11(window) => window.set_raw_mouse_motion(enabled),
+            Self::Wayland(_) => Err(Error::Unsupported("raw mouse motion on Wayland")),
+        }
+    }
+
+    fn set_cursor_grab(&self, mode: crate::cursor::CursorGrabMode) -> Result<(), Error> {
+        match self {
+            Self::X11(window) => window.set_cursor_grab(mode),
+            Self::Wayland(_) => Err(Error::Unsupported("cursor grab on Wayland")),
+        }
+    }
+
+    fn set_cursor_visible(&self, visible: bool) -> Result<(), Error> {
+        match self {
+            Self::X11(window) => window.set_cursor_visible(visible),
+            Self::Wayland(_) => Err(Error::Unsupported("cursor visibility on Wayland")),
+        }
+    }
+
+    fn set_cursor(&self, icon: crate::cursor::CursorIcon) -> Result<(), Error> {
+        match self {
+            Self::X11(window) => window.set_cursor(icon),
+            Self::Wayland(_) => Err(Error::Unsupported("cursor shapes on Wayland")),
+        }
+    }
+
+    fn set_custom_cursor(
+        &self,
+        rgba: &[u8],
+        width: u16,
+        height: u16,
+        hotspot: (u16, u16),
+    ) -> Result<(), Error> {
+        match self {
+            Self::X11(window) => window.set_custom_cursor(rgba, width, height, hotspot),
+            Self::Wayland(_) => Err(Error::Unsupported("custom cursors on Wayland")),
+        }
+    }
+
+    fn set_icon(&self, icon: Option<crate::icon::Icon>) -> Result<(), Error> {
+        match self {
+            Self::X11(window) => window.set_icon(icon.as_ref()),
+            Self::Wayland(_) => Err(Error::Unsupported("window icons on Wayland")),
+        }
+    }
+
+    fn set_fullscreen(&self, fullscreen: Option<Fullscreen>) -> Result<(), Error> {
+        match self {
+            Self::X11(window) => window.set_fullscreen(fullscreen),
+            Self::Wayland(window) => window.set_fullscreen(fullscreen),
+        }
+    }
+
+    fn fullscreen(&self) -> Option<Fullscreen> {
+        match self {
+            Self::X11(window) => window.fullscreen(),
+            Self::Wayland(window) => window.fullscreen(),
+        }
+    }
+
+    fn current_monitor(&self) -> Option<MonitorHandle> {
+        match self {
+            Self::X11(window) => window.current_monitor(),
+            Self::Wayland(_) => None,
+        }
+    }
+
+    fn available_monitors(&self) -> Vec<MonitorHandle> {
+        match self {
+            Self::X11(window) => window.available_monitors(),
+            Self::Wayland(_) => Vec::new(),
+        }
+    }
+
+    fn primary_monitor(&self) -> Option<MonitorHandle> {
+        match self {
+            Self::X11(window) => window.primary_monitor(),
+            Self::Wayland(_) => None,
+        }
+    }
+
+    fn id(&self) -> WindowId {
+        match self {
+            Self::X11(window) => window.id(),
+            Self::Wayland(window) => window.id(),
+        }
+    }
+
+    fn raw_handle(&self) -> RawWindowHandle {
+        match self {
+            Self::X11(window) => window.raw_handle(),
+            Self::Wayland(window) => window.raw_handle(),
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use std::{
+    os::fd::{AsRawFd, BorrowedFd},
+    time::Duration,
+};
+
+use super::Error;
+
+/// Blocks until one of `fds` becomes readable or the timeout expires.
+///
+/// Returns which of the file descriptors are readable.
+pub fn wait_readable<const N: usize>(
+    fds: [BorrowedFd<'_>; N],
+    timeout: Option<Duration>,
+) -> Result<[bool; N], Error> {
+    // round up, so short timeouts don't turn into busy waiting
+    let timeout = timeout.map_or(-1, |timeout| {
+        i32::try_from(timeout.as_micros().div_ceil(1000)).unwrap_or(i32::MAX)
+    });
+
+    let mut pollfds = fds.map(|fd| libc::pollfd {
+        fd: fd.as_raw_fd(),
+        events: libc::POLLIN,
+        revents: 0,
+    });
+
+    // SAFETY:
+    // pollfds is a valid pointer to exactly N pollfd structs.
+    #[allow(clippy::cast_possible_truncation)]
+    let res = unsafe { libc::poll(pollfds.as_mut_ptr(), N as libc::nfds_t, timeout) };
+
+    if res < 0 {
+        let err = std::io::Error::last_os_error();
+
+        // a signal counts as a spurious wake up
+        if err.kind() != std::io::ErrorKind::Interrupted {
+            return Err(Error::Poll(err));
+        }
+    }
+
+    Ok(pollfds.map(|fd| fd.revents != 0))
+}
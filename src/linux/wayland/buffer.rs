@@ -0,0 +1,213 @@
+use std::{
+    os::fd::{AsFd as _, AsRawFd as _, FromRawFd as _, OwnedFd},
+    ptr::NonNull,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use wayland_client::protocol::{wl_buffer::WlBuffer, wl_shm, wl_shm_pool::WlShmPool};
+
+use crate::Error;
+
+use super::connection::WlConnection;
+
+/// The `wl_shm` buffers of a single surface.
+///
+/// Buffers still in use by the compositor are never written to, a new one is created instead.
+#[derive(Debug)]
+pub struct BufferPool {
+    buffers: Vec<ShmBuffer>,
+    current: Option<usize>,
+    /// The high byte of the pixels is alpha, instead of being ignored.
+    transparent: bool,
+}
+
+impl BufferPool {
+    pub const fn new(transparent: bool) -> Self {
+        Self {
+            buffers: Vec::new(),
+            current: None,
+            transparent,
+        }
+    }
+
+    /// Copies an XRGB8888 buffer into a free `wl_buffer`, which becomes the current one.
+    pub fn write(
+        &mut self,
+        wlconn: &WlConnection,
+        buffer: &[u32],
+        width: u16,
+        height: u16,
+    ) -> Result<&ShmBuffer, Error> {
+        // buffers of an old size are no longer needed, once the compositor is done with them
+        let current = self.current().map(|shm| shm.buffer.clone());
+        self.buffers
+            .retain(|shm| shm.is_busy() || (shm.width == width && shm.height == height));
+        self.current =
+            current.and_then(|buffer| self.buffers.iter().position(|shm| shm.buffer == buffer));
+
+        let free = self
+            .buffers
+            .iter()
+            .position(|shm| !shm.is_busy() && shm.width == width && shm.height == height);
+
+        let index = if let Some(index) = free {
+            index
+        } else {
+            let format = if self.transparent {
+                wl_shm::Format::Argb8888
+            } else {
+                wl_shm::Format::Xrgb8888
+            };
+
+            self.buffers
+                .push(ShmBuffer::new(wlconn, width, height, format)?);
+            self.buffers.len() - 1
+        };
+
+        let shm = &self.buffers[index];
+
+        // SAFETY:
+        // The mapping is exactly width * height pixels long and not read by the compositor, as the buffer is not busy.
+        unsafe {
+            std::ptr::copy_nonoverlapping(buffer.as_ptr(), shm.addr.as_ptr(), buffer.len());
+        };
+
+        self.current = Some(index);
+
+        Ok(shm)
+    }
+
+    /// Returns the buffer written last.
+    pub fn current(&self) -> Option<&ShmBuffer> {
+        self.current.and_then(|index| self.buffers.get(index))
+    }
+
+    pub fn clear(&mut self) {
+        self.current = None;
+        self.buffers.clear();
+    }
+}
+
+/// A `wl_buffer` backed by its own memfd.
+#[derive(Debug)]
+pub struct ShmBuffer {
+    pool: WlShmPool,
+    pub buffer: WlBuffer,
+    addr: NonNull<u32>,
+    len: usize,
+    pub width: u16,
+    pub height: u16,
+    busy: Arc<AtomicBool>,
+}
+
+impl ShmBuffer {
+    fn new(
+        wlconn: &WlConnection,
+        width: u16,
+        height: u16,
+        format: wl_shm::Format,
+    ) -> Result<Self, Error> {
+        let len = usize::from(width) * usize::from(height);
+        let size = len * 4;
+
+        // SAFETY:
+        // the name is a valid C string.
+        let fd = unsafe { libc::memfd_create(c"pane-buffer".as_ptr(), libc::MFD_CLOEXEC) };
+        if fd < 0 {
+            return Err(Error::Shm(std::io::Error::last_os_error()));
+        }
+
+        // SAFETY:
+        // fd was just created and is owned by nobody else.
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+        #[allow(clippy::cast_possible_wrap)]
+        // SAFETY:
+        // fd is a valid memfd.
+        if unsafe { libc::ftruncate(fd.as_raw_fd(), size as libc::off_t) } < 0 {
+            return Err(Error::Shm(std::io::Error::last_os_error()));
+        }
+
+        // SAFETY:
+        // fd is a valid memfd of exactly size bytes.
+        let addr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                size,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                fd.as_raw_fd(),
+                0,
+            )
+        };
+
+        if addr == libc::MAP_FAILED {
+            return Err(Error::Shm(std::io::Error::last_os_error()));
+        }
+
+        let Some(addr) = NonNull::new(addr.cast::<u32>()) else {
+            return Err(Error::Unsupported("shared memory mapping"));
+        };
+
+        let busy = Arc::new(AtomicBool::new(false));
+
+        #[allow(clippy::cast_possible_wrap, clippy::cast_possible_truncation)]
+        let (pool, buffer) = {
+            let pool = wlconn
+                .shm
+                .create_pool(fd.as_fd(), size as i32, &wlconn.qh, ());
+
+            // the layout of wl_shm xrgb8888 and argb8888 matches the u32 pixels of `present_buffer`
+            let buffer = pool.create_buffer(
+                0,
+                i32::from(width),
+                i32::from(height),
+                i32::from(width) * 4,
+                format,
+                &wlconn.qh,
+                Arc::clone(&busy),
+            );
+
+            (pool, buffer)
+        };
+
+        Ok(Self {
+            pool,
+            buffer,
+            addr,
+            len,
+            width,
+            height,
+            busy,
+        })
+    }
+
+    pub fn is_busy(&self) -> bool {
+        self.busy.load(Ordering::Acquire)
+    }
+
+    /// Marks the buffer as read by the compositor, until it is released.
+    pub fn attached(&self) {
+        self.busy.store(true, Ordering::Release);
+    }
+
+    pub const fn pixels(&self) -> &[u32] {
+        // SAFETY:
+        // The mapping is valid for len pixels, as long as self lives.
+        unsafe { std::slice::from_raw_parts(self.addr.as_ptr(), self.len) }
+    }
+}
+
+impl Drop for ShmBuffer {
+    fn drop(&mut self) {
+        self.buffer.destroy();
+        self.pool.destroy();
+
+        // SAFETY:
+        // addr was returned by mmap with a size of len pixels.
+        unsafe { libc::munmap(self.addr.as_ptr().cast(), self.len * 4) };
+    }
+}
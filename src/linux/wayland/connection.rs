@@ -0,0 +1,319 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, VecDeque},
+    os::fd::{AsFd as _, AsRawFd as _, FromRawFd as _, OwnedFd},
+    rc::Rc,
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
+
+use wayland_client::{
+    Connection, EventQueue, QueueHandle,
+    backend::WaylandError,
+    globals::registry_queue_init,
+    protocol::{wl_compositor::WlCompositor, wl_seat::WlSeat, wl_shm::WlShm},
+};
+use wayland_protocols::xdg::shell::client::xdg_wm_base::XdgWmBase;
+
+use crate::{
+    Error, WindowId,
+    events::{EventSystem, UserEvent},
+    linux::poll,
+    monitor::MonitorHandle,
+};
+
+use super::state::{State, WindowState};
+
+type UserEvents = Arc<Mutex<VecDeque<(WindowId, UserEvent)>>>;
+
+/// A single Wayland connection, shared by every window created on it.
+///
+/// Incoming events are routed to the `EventSystem` of the window they belong to.
+#[derive(Debug)]
+pub struct WlConnection {
+    pub conn: Connection,
+    pub qh: QueueHandle<State>,
+    pub compositor: WlCompositor,
+    pub wm_base: XdgWmBase,
+    pub shm: WlShm,
+    pub state: RefCell<State>,
+    queue: RefCell<EventQueue<State>>,
+    windows: RefCell<HashMap<WindowId, Arc<dyn EventSystem>>>,
+    waker: Arc<Waker>,
+    user_events: UserEvents,
+    _seat: Option<WlSeat>,
+}
+
+impl WlConnection {
+    pub fn connect() -> Result<Self, Error> {
+        let conn = Connection::connect_to_env()?;
+
+        let (globals, queue) = registry_queue_init::<State>(&conn)?;
+        let qh = queue.handle();
+
+        let compositor = globals.bind(&qh, 1..=4, ())?;
+        let wm_base = globals.bind(&qh, 1..=1, ())?;
+        let shm = globals.bind(&qh, 1..=1, ())?;
+
+        // a compositor without input devices is still usable, e.g. for rendering
+        let seat = globals
+            .bind(&qh, 1..=7, ())
+            .inspect_err(|err| log::warn!("no wl_seat available: {err}"))
+            .ok();
+
+        Ok(Self {
+            conn,
+            qh,
+            compositor,
+            wm_base,
+            shm,
+            state: RefCell::new(State::new()),
+            queue: RefCell::new(queue),
+            windows: RefCell::new(HashMap::new()),
+            waker: Arc::new(Waker::new()?),
+            user_events: Arc::default(),
+            _seat: seat,
+        })
+    }
+
+    pub fn register(&self, window: WindowId, size: crate::Rect, eventsys: Arc<dyn EventSystem>) {
+        self.state
+            .borrow_mut()
+            .windows
+            .insert(window, WindowState::new(size));
+        self.windows.borrow_mut().insert(window, eventsys);
+    }
+
+    pub fn unregister(&self, window: WindowId) {
+        self.state.borrow_mut().windows.remove(&window);
+        self.windows.borrow_mut().remove(&window);
+    }
+
+    pub fn flush(&self) -> Result<(), Error> {
+        match self.conn.flush() {
+            // the socket is full, the rest is sent with the next flush
+            Err(WaylandError::Io(err)) if err.kind() == std::io::ErrorKind::WouldBlock => Ok(()),
+            res => Ok(res?),
+        }
+    }
+
+    /// Blocks until at least one event was handled, without passing it on to the `EventSystem`s.
+    pub fn blocking_dispatch(&self) -> Result<(), Error> {
+        let mut state = self.state.borrow_mut();
+        self.queue.borrow_mut().blocking_dispatch(&mut state)?;
+
+        Ok(())
+    }
+
+    /// Processes all pending events of every window on this connection.
+    pub fn pump_messages(&self) -> Result<(), Error> {
+        self.flush()?;
+
+        if let Some(guard) = self.queue.borrow().prepare_read() {
+            read(guard)?;
+        }
+
+        self.dispatch_pending()
+    }
+
+    /// Blocks until at least one event arrives or the timeout expires, then processes all pending events.
+    pub fn wait_events(&self, timeout: Option<Duration>) -> Result<(), Error> {
+        self.flush()?;
+
+        // events may already be queued, e.g. by `show`
+        let guard = self.queue.borrow().prepare_read();
+
+        if let Some(guard) = guard {
+            let has_events = !self.state.borrow().events.is_empty();
+
+            if has_events {
+                drop(guard);
+            } else {
+                let [readable, _] =
+                    poll::wait_readable([guard.connection_fd(), self.waker.fd.as_fd()], timeout)?;
+
+                if readable {
+                    read(guard)?;
+                }
+            }
+        }
+
+        self.dispatch_pending()
+    }
+
+    pub fn create_proxy(&self, window: WindowId) -> EventLoopProxy {
+        EventLoopProxy {
+            window,
+            waker: Arc::clone(&self.waker),
+            user_events: Arc::clone(&self.user_events),
+        }
+    }
+
+    fn dispatch_pending(&self) -> Result<(), Error> {
+        {
+            let mut state = self.state.borrow_mut();
+            self.queue.borrow_mut().dispatch_pending(&mut state)?;
+        }
+
+        self.waker.reset();
+        self.dispatch_user_events();
+
+        self.dispatch_events();
+
+        Ok(())
+    }
+
+    fn dispatch_events(&self) {
+        loop {
+            // the state is only borrowed briefly, so callbacks are free to create or destroy windows
+            let Some((window, e)) = self.state.borrow_mut().events.pop_front() else {
+                return;
+            };
+
+            let Some(eventsys) = self.windows.borrow().get(&window).cloned() else {
+                continue;
+            };
+
+            // closing is up to the application, the other windows keep receiving events
+            e.dispatch(&*eventsys, window);
+        }
+    }
+
+    fn dispatch_user_events(&self) {
+        let user_events = std::mem::take(
+            &mut *self
+                .user_events
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner),
+        );
+
+        for (window, e) in user_events {
+            let Some(eventsys) = self.windows.borrow().get(&window).cloned() else {
+                continue;
+            };
+
+            eventsys.invoke_user_event(window, e);
+        }
+    }
+}
+
+/// Reads events from the socket into the queue, without blocking.
+fn read(guard: wayland_client::backend::ReadEventsGuard) -> Result<(), Error> {
+    match guard.read() {
+        Ok(_) => Ok(()),
+        Err(WaylandError::Io(err)) if err.kind() == std::io::ErrorKind::WouldBlock => Ok(()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// An eventfd, that interrupts `wait_events` from other threads.
+#[derive(Debug)]
+struct Waker {
+    fd: OwnedFd,
+}
+
+impl Waker {
+    fn new() -> Result<Self, Error> {
+        // SAFETY:
+        // eventfd has no memory safety requirements.
+        let fd = unsafe { libc::eventfd(0, libc::EFD_CLOEXEC | libc::EFD_NONBLOCK) };
+
+        if fd < 0 {
+            return Err(Error::WakeUp(std::io::Error::last_os_error()));
+        }
+
+        // SAFETY:
+        // fd was just created and is owned by nobody else.
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+        Ok(Self { fd })
+    }
+
+    fn wake(&self) -> Result<(), Error> {
+        let value = 1u64;
+
+        // SAFETY:
+        // value is valid for 8 bytes.
+        let res = unsafe { libc::write(self.fd.as_raw_fd(), (&raw const value).cast(), 8) };
+
+        if res < 0 {
+            let err = std::io::Error::last_os_error();
+
+            // the counter is full, so the event loop is woken up anyway
+            if err.kind() != std::io::ErrorKind::WouldBlock {
+                return Err(Error::WakeUp(err));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn reset(&self) {
+        let mut value = 0u64;
+
+        // SAFETY:
+        // value is valid for 8 bytes.
+        // Reading fails with EAGAIN, if the counter was not set.
+        unsafe { libc::read(self.fd.as_raw_fd(), (&raw mut value).cast(), 8) };
+    }
+}
+
+/// Wakes up the event loop of a window from another thread.
+#[derive(Debug, Clone)]
+pub struct EventLoopProxy {
+    window: WindowId,
+    waker: Arc<Waker>,
+    user_events: UserEvents,
+}
+
+impl crate::EventLoopProxyImpl for EventLoopProxy {
+    fn wake_up(&self, event: Option<UserEvent>) -> Result<(), Error> {
+        if let Some(event) = event {
+            self.user_events
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push_back((self.window, event));
+        }
+
+        self.waker.wake()
+    }
+}
+
+/// Owns a single Wayland connection that any number of windows can be created on.
+#[derive(Debug)]
+pub struct EventLoop {
+    wlconn: Rc<WlConnection>,
+}
+
+impl crate::EventLoopImpl for EventLoop {
+    fn new() -> Result<Self, Error> {
+        let wlconn = WlConnection::connect()?;
+
+        Ok(Self {
+            wlconn: Rc::new(wlconn),
+        })
+    }
+
+    fn pump_messages(&mut self) -> Result<(), Error> {
+        self.wlconn.pump_messages()
+    }
+
+    fn wait_events(&mut self, timeout: Option<Duration>) -> Result<(), Error> {
+        self.wlconn.wait_events(timeout)
+    }
+
+    // wl_output is not bound yet, so no monitors are known
+    fn available_monitors(&self) -> Vec<MonitorHandle> {
+        Vec::new()
+    }
+
+    fn primary_monitor(&self) -> Option<MonitorHandle> {
+        None
+    }
+}
+
+impl EventLoop {
+    pub fn connection(&self) -> Rc<WlConnection> {
+        Rc::clone(&self.wlconn)
+    }
+}
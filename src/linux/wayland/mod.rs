@@ -0,0 +1,406 @@
+mod buffer;
+mod connection;
+mod state;
+
+pub use connection::{EventLoop, EventLoopProxy};
+
+use std::{cell::Cell, cell::RefCell, rc::Rc, sync::Arc, time::Duration};
+
+use wayland_client::{Proxy as _, protocol::wl_surface::WlSurface};
+use wayland_protocols::xdg::shell::client::{xdg_surface::XdgSurface, xdg_toplevel::XdgToplevel};
+
+use crate::{
+    DamageRect, Error, Rect, WindowId,
+    builder::WindowAttributes,
+    events::{Event, EventSystem, WindowResizeEvent},
+    monitor::Fullscreen,
+};
+
+use self::{
+    buffer::{BufferPool, ShmBuffer},
+    connection::WlConnection,
+};
+
+/// An `xdg_toplevel` surface.
+///
+/// Wayland leaves the placement of windows to the compositor, so the requested position is ignored.
+#[derive(Debug)]
+pub struct Window {
+    wlconn: Rc<WlConnection>,
+    id: WindowId,
+    surface: WlSurface,
+    xdg_surface: XdgSurface,
+    toplevel: XdgToplevel,
+    buffers: RefCell<BufferPool>,
+    shown: Cell<bool>,
+    title: RefCell<String>,
+    fullscreen: RefCell<Option<Fullscreen>>,
+}
+
+impl Window {
+    /// Decorations and always on top have no protocol in the core of Wayland, they are ignored.
+    pub fn create(
+        event_loop: &EventLoop,
+        attributes: &WindowAttributes,
+        eventsys: Arc<dyn EventSystem>,
+    ) -> Result<Self, Error> {
+        // a parent has to be an xdg_toplevel of this connection, not any surface
+        if attributes.parent.is_some() {
+            return Err(Error::Unsupported("parent windows on Wayland"));
+        }
+
+        let wlconn = event_loop.connection();
+        let qh = &wlconn.qh;
+
+        let surface = wlconn.compositor.create_surface(qh, ());
+        let id = state::surface_id(&surface);
+
+        wlconn.register(id, attributes.size, eventsys);
+
+        let xdg_surface = wlconn.wm_base.get_xdg_surface(&surface, qh, id);
+        let toplevel = xdg_surface.get_toplevel(qh, id);
+        toplevel.set_title(attributes.title.clone());
+
+        if let Some(app_id) = &attributes.app_id {
+            toplevel.set_app_id(app_id.clone());
+        }
+        if let Some(size) = attributes.min_size {
+            toplevel.set_min_size(i32::from(size.x), i32::from(size.y));
+        }
+        if let Some(size) = attributes.max_size {
+            toplevel.set_max_size(i32::from(size.x), i32::from(size.y));
+        }
+        if attributes.maximized {
+            toplevel.set_maximized();
+        }
+
+        let window = Self {
+            wlconn,
+            id,
+            surface,
+            xdg_surface,
+            toplevel,
+            buffers: RefCell::new(BufferPool::new(attributes.transparent)),
+            shown: Cell::new(false),
+            title: RefCell::new(attributes.title.clone()),
+            fullscreen: RefCell::new(None),
+        };
+
+        if attributes.fullscreen.is_some() {
+            window.set_fullscreen(attributes.fullscreen.clone())?;
+        }
+
+        if attributes.visible {
+            window.show()?;
+        }
+
+        Ok(window)
+    }
+
+    pub fn show(&self) -> Result<(), Error> {
+        if self.shown.replace(true) {
+            return Ok(());
+        }
+
+        // the initial commit without a buffer asks the compositor for a configure
+        self.surface.commit();
+        self.wlconn.flush()?;
+
+        // buffers can only be attached, once the configure was acknowledged
+        while !self.is_configured() {
+            self.wlconn.blocking_dispatch()?;
+        }
+
+        let mut buffers = self.buffers.borrow_mut();
+
+        // like on X11, the window starts out black
+        if buffers.current().is_none() {
+            let size = self.inner_size();
+            let (width, height) = (size.x.max(1), size.y.max(1));
+            let black = vec![0; usize::from(width) * usize::from(height)];
+
+            buffers.write(&self.wlconn, &black, width, height)?;
+        }
+
+        if let Some(buffer) = buffers.current() {
+            self.commit(
+                buffer,
+                &[DamageRect::new(0, 0, buffer.width, buffer.height)],
+            );
+        }
+
+        self.wlconn.flush()
+    }
+
+    /// A surface without a buffer is unmapped, and has to be configured again to be shown.
+    pub fn set_visible(&self, visible: bool) -> Result<(), Error> {
+        if visible {
+            return self.show();
+        }
+
+        if !self.shown.replace(false) {
+            return Ok(());
+        }
+
+        if let Some(window) = self.wlconn.state.borrow_mut().windows.get_mut(&self.id) {
+            window.configured = false;
+        }
+
+        self.surface.attach(None, 0, 0);
+        self.surface.commit();
+
+        self.wlconn.flush()
+    }
+
+    /// There is no request to restore a minimized window, only the user can.
+    pub fn set_minimized(&self, minimized: bool) -> Result<(), Error> {
+        if !minimized {
+            return Err(Error::Unsupported("restoring minimized windows on Wayland"));
+        }
+
+        self.toplevel.set_minimized();
+        self.wlconn.flush()
+    }
+
+    pub fn set_maximized(&self, maximized: bool) -> Result<(), Error> {
+        if maximized {
+            self.toplevel.set_maximized();
+        } else {
+            self.toplevel.unset_maximized();
+        }
+
+        self.wlconn.flush()
+    }
+
+    pub fn is_maximized(&self) -> bool {
+        self.wlconn
+            .state
+            .borrow()
+            .windows
+            .get(&self.id)
+            .is_some_and(|window| window.maximized)
+    }
+
+    /// The compositor keeps the last buffer, so redraws are only ever requested by us.
+    pub fn request_redraw(&self) {
+        self.wlconn.state.borrow_mut().request_redraw(self.id);
+    }
+
+    pub fn set_title(&self, title: &str) -> Result<(), Error> {
+        title.clone_into(&mut self.title.borrow_mut());
+        self.toplevel.set_title(title.to_owned());
+        self.wlconn.flush()
+    }
+
+    pub fn title(&self) -> String {
+        self.title.borrow().clone()
+    }
+
+    /// The compositor picks the output, there are no handles for them yet.
+    pub fn set_fullscreen(&self, fullscreen: Option<Fullscreen>) -> Result<(), Error> {
+        match &fullscreen {
+            None => self.toplevel.unset_fullscreen(),
+            Some(Fullscreen::Borderless(None)) => self.toplevel.set_fullscreen(None),
+            Some(Fullscreen::Borderless(Some(_))) => {
+                return Err(Error::Unsupported(
+                    "fullscreen on a chosen monitor on Wayland",
+                ));
+            }
+            Some(Fullscreen::Exclusive(_)) => {
+                return Err(Error::Unsupported("exclusive fullscreen on Wayland"));
+            }
+        }
+
+        self.fullscreen.replace(fullscreen);
+        self.wlconn.flush()
+    }
+
+    pub fn fullscreen(&self) -> Option<Fullscreen> {
+        self.fullscreen.borrow().clone()
+    }
+
+    pub fn pump_messages(&self) -> Result<(), Error> {
+        self.wlconn.pump_messages()
+    }
+
+    pub fn wait_events(&self, timeout: Option<Duration>) -> Result<(), Error> {
+        self.wlconn.wait_events(timeout)
+    }
+
+    pub fn create_proxy(&self) -> EventLoopProxy {
+        self.wlconn.create_proxy(self.id)
+    }
+
+    pub fn inner_size(&self) -> Rect {
+        self.wlconn
+            .state
+            .borrow()
+            .windows
+            .get(&self.id)
+            .map_or(Rect::new(0, 0), |window| window.size)
+    }
+
+    /// The surface takes the size of the next presented buffer, the resize event asks for it.
+    ///
+    /// Maximized and fullscreen windows keep the size of the compositor.
+    pub fn set_inner_size(&self, size: Rect) {
+        let mut state = self.wlconn.state.borrow_mut();
+
+        if let Some(window) = state.windows.get_mut(&self.id)
+            && window.size != size
+        {
+            window.size = size;
+
+            let e = WindowResizeEvent {
+                width: u32::from(size.x),
+                height: u32::from(size.y),
+            };
+
+            state.events.push_back((self.id, Event::WindowResize(e)));
+        }
+    }
+
+    pub fn set_min_inner_size(&self, size: Option<Rect>) -> Result<(), Error> {
+        // zero removes the limit
+        let size = size.unwrap_or(Rect::new(0, 0));
+        self.toplevel
+            .set_min_size(i32::from(size.x), i32::from(size.y));
+
+        self.commit_state()
+    }
+
+    pub fn set_max_inner_size(&self, size: Option<Rect>) -> Result<(), Error> {
+        let size = size.unwrap_or(Rect::new(0, 0));
+        self.toplevel
+            .set_max_size(i32::from(size.x), i32::from(size.y));
+
+        self.commit_state()
+    }
+
+    /// Applies double buffered toplevel state, which a hidden window sends with its first commit.
+    fn commit_state(&self) -> Result<(), Error> {
+        if self.shown.get() {
+            self.surface.commit();
+        }
+
+        self.wlconn.flush()
+    }
+
+    pub fn present_buffer(
+        &self,
+        buffer: &[u32],
+        width: u16,
+        height: u16,
+        damage: &[DamageRect],
+    ) -> Result<(), Error> {
+        let expected = usize::from(width) * usize::from(height);
+        if buffer.len() != expected {
+            return Err(Error::BufferSize {
+                expected,
+                actual: buffer.len(),
+            });
+        }
+
+        // there are no empty wl_buffers
+        if expected == 0 {
+            return Ok(());
+        }
+
+        let damage = damage
+            .iter()
+            .filter_map(|rect| rect.clamp(width, height))
+            .collect::<Vec<_>>();
+
+        let mut buffers = self.buffers.borrow_mut();
+        let buffer = buffers.write(&self.wlconn, buffer, width, height)?;
+
+        // before `show` the buffer is kept, until the surface can be mapped
+        if self.shown.get() {
+            self.commit(buffer, &damage);
+            self.wlconn.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the contents last presented, since Wayland has no way to read back a surface.
+    pub fn capture(&self) -> crate::Image {
+        let buffers = self.buffers.borrow();
+
+        let Some(buffer) = buffers.current() else {
+            let size = self.inner_size();
+
+            return crate::Image {
+                width: size.x,
+                height: size.y,
+                data: [0, 0, 0, u8::MAX].repeat(usize::from(size.x) * usize::from(size.y)),
+            };
+        };
+
+        let data = buffer
+            .pixels()
+            .iter()
+            .flat_map(|pixel| {
+                let [blue, green, red, _] = pixel.to_le_bytes();
+                [red, green, blue, u8::MAX]
+            })
+            .collect();
+
+        crate::Image {
+            width: buffer.width,
+            height: buffer.height,
+            data,
+        }
+    }
+
+    pub const fn id(&self) -> WindowId {
+        self.id
+    }
+
+    pub fn raw_handle(&self) -> crate::RawWindowHandle {
+        crate::RawWindowHandle::Wayland {
+            display: self.wlconn.conn.backend().display_ptr().cast(),
+            surface: self.surface.id().as_ptr().cast(),
+        }
+    }
+
+    fn is_configured(&self) -> bool {
+        self.wlconn
+            .state
+            .borrow()
+            .windows
+            .get(&self.id)
+            .is_some_and(|window| window.configured)
+    }
+
+    fn commit(&self, buffer: &ShmBuffer, damage: &[DamageRect]) {
+        self.surface.attach(Some(&buffer.buffer), 0, 0);
+
+        for rect in damage {
+            let (x, y) = (i32::from(rect.x), i32::from(rect.y));
+            let (width, height) = (i32::from(rect.width), i32::from(rect.height));
+
+            // without a scale, buffer and surface coordinates are the same
+            if self.surface.version() >= 4 {
+                self.surface.damage_buffer(x, y, width, height);
+            } else {
+                self.surface.damage(x, y, width, height);
+            }
+        }
+
+        buffer.attached();
+        self.surface.commit();
+    }
+}
+
+impl Drop for Window {
+    fn drop(&mut self) {
+        self.toplevel.destroy();
+        self.xdg_surface.destroy();
+        self.buffers.get_mut().clear();
+        self.surface.destroy();
+
+        self.wlconn.unregister(self.id);
+        _ = self.wlconn.flush();
+    }
+}
@@ -0,0 +1,528 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    ffi::CStr,
+    os::fd::{AsRawFd as _, OwnedFd},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use wayland_client::{
+    Connection, Dispatch, Proxy, QueueHandle, WEnum,
+    globals::GlobalListContents,
+    protocol::{
+        wl_buffer::{self, WlBuffer},
+        wl_compositor::WlCompositor,
+        wl_keyboard::{self, KeyState, KeymapFormat, WlKeyboard},
+        wl_pointer::{self, Axis, ButtonState, WlPointer},
+        wl_registry::WlRegistry,
+        wl_seat::{self, Capability, WlSeat},
+        wl_shm::WlShm,
+        wl_shm_pool::WlShmPool,
+        wl_surface::WlSurface,
+    },
+};
+use wayland_protocols::xdg::shell::client::{
+    xdg_surface::{self, XdgSurface},
+    xdg_toplevel::{self, XdgToplevel},
+    xdg_wm_base::{self, XdgWmBase},
+};
+
+use crate::{
+    Rect, WindowId,
+    events::{
+        Event, KeyEvent, ModifiersChangedEvent, MouseButtonEvent, MouseMoveEvent, MouseWheelEvent,
+        TextEvent, WindowEvent, WindowResizeEvent,
+    },
+    inputs::{Modifiers, ScrollDelta},
+    linux::{
+        inputs::{evdev_translate_button, evdev_translate_key, translate_modifiers},
+        xkb::Keyboard,
+    },
+};
+
+/// Everything the Wayland event handlers need to keep track of.
+///
+/// Handlers only collect events, they are passed on to the `EventSystem`s once dispatching is done.
+#[derive(Debug, Default)]
+pub struct State {
+    pub windows: HashMap<WindowId, WindowState>,
+    pub events: VecDeque<(WindowId, Event)>,
+    pointer: Option<WlPointer>,
+    keyboard: Option<WlKeyboard>,
+    pointer_focus: Option<WindowId>,
+    keyboard_focus: Option<WindowId>,
+    discrete_scroll: Option<(Axis, i32)>,
+    modifiers: Modifiers,
+    xkb: Option<Keyboard>,
+}
+
+#[derive(Debug)]
+pub struct WindowState {
+    pub size: Rect,
+    pub configured: bool,
+    pub maximized: bool,
+    pending_size: Option<Rect>,
+}
+
+impl WindowState {
+    pub const fn new(size: Rect) -> Self {
+        Self {
+            size,
+            configured: false,
+            maximized: false,
+            pending_size: None,
+        }
+    }
+}
+
+impl State {
+    pub fn new() -> Self {
+        Self {
+            xkb: Keyboard::new(),
+            ..Self::default()
+        }
+    }
+
+    fn push(&mut self, window: WindowId, e: Event) {
+        self.events.push_back((window, e));
+    }
+
+    /// Queues a `RedrawRequested` for the whole window, unless one is queued already.
+    pub fn request_redraw(&mut self, window: WindowId) {
+        let queued = self.events.iter().any(|(id, e)| {
+            *id == window && matches!(e, Event::Window(WindowEvent::RedrawRequested { .. }))
+        });
+
+        if !queued {
+            let e = WindowEvent::RedrawRequested { damage: None };
+            self.push(window, Event::Window(e));
+        }
+    }
+
+    /// Returns the window of a surface, if it is one of ours.
+    fn window(&self, surface: &WlSurface) -> Option<WindowId> {
+        let window = surface_id(surface);
+
+        self.windows.contains_key(&window).then_some(window)
+    }
+}
+
+pub fn surface_id(surface: &WlSurface) -> WindowId {
+    WindowId::from_raw(u64::from(surface.id().protocol_id()))
+}
+
+impl Dispatch<WlRegistry, GlobalListContents> for State {
+    fn event(
+        _: &mut Self,
+        _: &WlRegistry,
+        _: <WlRegistry as Proxy>::Event,
+        _: &GlobalListContents,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<XdgWmBase, ()> for State {
+    fn event(
+        _: &mut Self,
+        wm_base: &XdgWmBase,
+        event: xdg_wm_base::Event,
+        (): &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        // the compositor checks if we are still responsive
+        if let xdg_wm_base::Event::Ping { serial } = event {
+            wm_base.pong(serial);
+        }
+    }
+}
+
+impl Dispatch<XdgSurface, WindowId> for State {
+    fn event(
+        state: &mut Self,
+        xdg_surface: &XdgSurface,
+        event: xdg_surface::Event,
+        window: &WindowId,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let xdg_surface::Event::Configure { serial } = event else {
+            return;
+        };
+
+        xdg_surface.ack_configure(serial);
+
+        let Some(window_state) = state.windows.get_mut(window) else {
+            return;
+        };
+
+        // the compositor shows nothing, until the first buffer is attached
+        let mut redraw = !std::mem::replace(&mut window_state.configured, true);
+
+        // the toplevel configure only takes effect with the surface configure that follows it
+        if let Some(size) = window_state.pending_size.take()
+            && size != window_state.size
+        {
+            window_state.size = size;
+            redraw = true;
+
+            let e = WindowResizeEvent {
+                width: u32::from(size.x),
+                height: u32::from(size.y),
+            };
+
+            state.push(*window, Event::WindowResize(e));
+        }
+
+        if redraw {
+            state.request_redraw(*window);
+        }
+    }
+}
+
+impl Dispatch<XdgToplevel, WindowId> for State {
+    fn event(
+        state: &mut Self,
+        _: &XdgToplevel,
+        event: xdg_toplevel::Event,
+        window: &WindowId,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            xdg_toplevel::Event::Configure {
+                width,
+                height,
+                states,
+            } => {
+                let Some(window_state) = state.windows.get_mut(window) else {
+                    return;
+                };
+
+                // an array of u32 in native byte order
+                window_state.maximized = states
+                    .chunks_exact(4)
+                    .map(|bytes| u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+                    .any(|value| value == xdg_toplevel::State::Maximized as u32);
+
+                // a size of zero leaves it up to us
+                if width > 0 && height > 0 {
+                    let width = u16::try_from(width).unwrap_or(u16::MAX);
+                    let height = u16::try_from(height).unwrap_or(u16::MAX);
+
+                    window_state.pending_size = Some(Rect::new(width, height));
+                }
+            }
+
+            xdg_toplevel::Event::Close => {
+                state.push(*window, Event::Window(WindowEvent::CloseWindow));
+            }
+
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<WlSeat, ()> for State {
+    fn event(
+        state: &mut Self,
+        seat: &WlSeat,
+        event: wl_seat::Event,
+        (): &(),
+        _: &Connection,
+        qh: &QueueHandle<Self>,
+    ) {
+        let wl_seat::Event::Capabilities {
+            capabilities: WEnum::Value(capabilities),
+        } = event
+        else {
+            return;
+        };
+
+        if capabilities.contains(Capability::Pointer) {
+            state
+                .pointer
+                .get_or_insert_with(|| seat.get_pointer(qh, ()));
+        } else if let Some(pointer) = state.pointer.take()
+            && pointer.version() >= 3
+        {
+            pointer.release();
+        }
+
+        if capabilities.contains(Capability::Keyboard) {
+            state
+                .keyboard
+                .get_or_insert_with(|| seat.get_keyboard(qh, ()));
+        } else if let Some(keyboard) = state.keyboard.take()
+            && keyboard.version() >= 3
+        {
+            keyboard.release();
+        }
+    }
+}
+
+impl Dispatch<WlPointer, ()> for State {
+    fn event(
+        state: &mut Self,
+        _: &WlPointer,
+        event: wl_pointer::Event,
+        (): &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            wl_pointer::Event::Enter {
+                surface,
+                surface_x,
+                surface_y,
+                ..
+            } => {
+                state.pointer_focus = state.window(&surface);
+
+                if let Some(window) = state.pointer_focus {
+                    state.push(window, Event::Window(WindowEvent::CursorEntered));
+
+                    let e = mouse_move(surface_x, surface_y, state.modifiers);
+                    state.push(window, Event::MouseMove(e));
+                }
+            }
+
+            wl_pointer::Event::Leave { .. } => {
+                if let Some(window) = state.pointer_focus.take() {
+                    state.push(window, Event::Window(WindowEvent::CursorLeft));
+                }
+            }
+
+            wl_pointer::Event::Motion {
+                surface_x,
+                surface_y,
+                ..
+            } => {
+                if let Some(window) = state.pointer_focus {
+                    let e = mouse_move(surface_x, surface_y, state.modifiers);
+                    state.push(window, Event::MouseMove(e));
+                }
+            }
+
+            wl_pointer::Event::Button {
+                button,
+                state: WEnum::Value(button_state),
+                ..
+            } => {
+                if let Some(window) = state.pointer_focus {
+                    let e = MouseButtonEvent {
+                        down: button_state == ButtonState::Pressed,
+                        button: evdev_translate_button(button),
+                        modifiers: state.modifiers,
+                    };
+
+                    state.push(window, Event::MouseButton(e));
+                }
+            }
+
+            // mouse wheels send their notches right before the axis event
+            wl_pointer::Event::AxisDiscrete {
+                axis: WEnum::Value(axis),
+                discrete,
+            } => state.discrete_scroll = Some((axis, discrete)),
+
+            wl_pointer::Event::Axis {
+                axis: WEnum::Value(axis),
+                value,
+                ..
+            } => {
+                let discrete = state
+                    .discrete_scroll
+                    .take()
+                    .filter(|(discrete_axis, _)| *discrete_axis == axis)
+                    .map(|(_, discrete)| f64::from(discrete));
+
+                // positive values scroll down or right
+                let delta = match (axis, discrete) {
+                    (Axis::VerticalScroll, Some(lines)) => ScrollDelta::Lines { x: 0.0, y: -lines },
+                    (Axis::VerticalScroll, None) => ScrollDelta::Pixels { x: 0.0, y: -value },
+                    (Axis::HorizontalScroll, Some(lines)) => {
+                        ScrollDelta::Lines { x: lines, y: 0.0 }
+                    }
+                    (Axis::HorizontalScroll, None) => ScrollDelta::Pixels { x: value, y: 0.0 },
+                    _ => return,
+                };
+
+                if let Some(window) = state.pointer_focus
+                    && let Some(direction) = delta.direction()
+                {
+                    let e = MouseWheelEvent {
+                        direction,
+                        delta,
+                        modifiers: state.modifiers,
+                    };
+
+                    state.push(window, Event::MouseWheel(e));
+                }
+            }
+
+            _ => {}
+        }
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+const fn mouse_move(x: f64, y: f64, modifiers: Modifiers) -> MouseMoveEvent {
+    // the pointer can be slightly outside of the surface, while a button is held
+    MouseMoveEvent {
+        x_pos: x.max(0.0) as u32,
+        y_pos: y.max(0.0) as u32,
+        modifiers,
+    }
+}
+
+impl Dispatch<WlKeyboard, ()> for State {
+    fn event(
+        state: &mut Self,
+        _: &WlKeyboard,
+        event: wl_keyboard::Event,
+        (): &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            wl_keyboard::Event::Enter { surface, .. } => {
+                state.keyboard_focus = state.window(&surface);
+
+                if let Some(window) = state.keyboard_focus {
+                    state.push(window, Event::Window(WindowEvent::Focused(true)));
+                }
+            }
+
+            wl_keyboard::Event::Leave { .. } => {
+                if let Some(window) = state.keyboard_focus.take() {
+                    state.push(window, Event::Window(WindowEvent::Focused(false)));
+                }
+            }
+
+            wl_keyboard::Event::Keymap {
+                format: WEnum::Value(KeymapFormat::XkbV1),
+                fd,
+                size,
+            } => {
+                if let Some(xkb) = &mut state.xkb {
+                    load_keymap(xkb, &fd, size);
+                }
+            }
+
+            wl_keyboard::Event::Modifiers {
+                mods_depressed,
+                mods_latched,
+                mods_locked,
+                group,
+                ..
+            } => {
+                if let Some(xkb) = &state.xkb {
+                    xkb.update_mask(mods_depressed, mods_latched, mods_locked, group);
+                }
+
+                let modifiers = translate_modifiers(mods_depressed | mods_latched | mods_locked);
+
+                if modifiers != state.modifiers {
+                    state.modifiers = modifiers;
+
+                    if let Some(window) = state.keyboard_focus {
+                        let e = ModifiersChangedEvent { modifiers };
+                        state.push(window, Event::ModifiersChanged(e));
+                    }
+                }
+            }
+
+            wl_keyboard::Event::Key {
+                key,
+                state: WEnum::Value(key_state),
+                ..
+            } => {
+                let Some(window) = state.keyboard_focus else {
+                    return;
+                };
+
+                let down = key_state != KeyState::Released;
+
+                let e = KeyEvent {
+                    key: evdev_translate_key(key),
+                    down,
+                    repeat: u32::from(key_state == KeyState::Repeated),
+                    modifiers: state.modifiers,
+                };
+
+                state.push(window, Event::Key(e));
+
+                // xkb keycodes are offset by 8 from evdev ones
+                if down && let Some(text) = state.xkb.as_ref().and_then(|xkb| xkb.key_text(key + 8))
+                {
+                    state.push(window, Event::Text(TextEvent { text }));
+                }
+            }
+
+            _ => {}
+        }
+    }
+}
+
+/// Maps the keymap sent by the compositor and loads it.
+fn load_keymap(xkb: &mut Keyboard, fd: &OwnedFd, size: u32) {
+    let size = size as usize;
+
+    // SAFETY:
+    // fd is a keymap of size bytes, that may only be mapped privately.
+    let addr = unsafe {
+        libc::mmap(
+            std::ptr::null_mut(),
+            size,
+            libc::PROT_READ,
+            libc::MAP_PRIVATE,
+            fd.as_raw_fd(),
+            0,
+        )
+    };
+
+    if addr == libc::MAP_FAILED {
+        log::warn!("failed to map keymap: {}", std::io::Error::last_os_error());
+        return;
+    }
+
+    // SAFETY:
+    // the mapping is valid for size bytes.
+    let keymap = unsafe { std::slice::from_raw_parts(addr.cast::<u8>(), size) };
+
+    // the keymap is NUL terminated
+    match CStr::from_bytes_until_nul(keymap) {
+        Ok(keymap) => _ = xkb.load_keymap(keymap),
+        Err(err) => log::warn!("invalid keymap: {err}"),
+    }
+
+    // SAFETY:
+    // addr was returned by mmap with a size of size bytes.
+    unsafe { libc::munmap(addr, size) };
+}
+
+impl Dispatch<WlBuffer, Arc<AtomicBool>> for State {
+    fn event(
+        _: &mut Self,
+        _: &WlBuffer,
+        event: wl_buffer::Event,
+        busy: &Arc<AtomicBool>,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        // the compositor is done reading, the buffer can be reused
+        if matches!(event, wl_buffer::Event::Release) {
+            busy.store(false, Ordering::Release);
+        }
+    }
+}
+
+wayland_client::delegate_noop!(State: WlCompositor);
+wayland_client::delegate_noop!(State: WlShmPool);
+wayland_client::delegate_noop!(State: ignore WlShm);
+wayland_client::delegate_noop!(State: ignore WlSurface);
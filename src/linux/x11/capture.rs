@@ -0,0 +1,45 @@
+use x11rb::{
+    image::{Image, PixelLayout},
+    protocol::xproto::ConnectionExt as _,
+};
+
+use crate::Error;
+
+use super::connection::XConnection;
+
+/// Reads back the current contents of a window as RGBA8.
+///
+/// The window has to be mapped, otherwise the server responds with a `Match` error.
+pub fn capture(xconn: &XConnection, window: u32) -> Result<crate::Image, Error> {
+    let geometry = xconn.conn.get_geometry(window)?.reply()?;
+
+    let (image, visual) = Image::get(&*xconn.conn, window, 0, 0, geometry.width, geometry.height)?;
+
+    let layout = xconn
+        .visual_type(visual)
+        .ok_or(Error::Unsupported("window visual"))
+        .and_then(|visual| Ok(PixelLayout::from_visual_type(visual)?))?;
+
+    let mut data =
+        Vec::with_capacity(usize::from(geometry.width) * usize::from(geometry.height) * 4);
+
+    for y in 0..image.height() {
+        for x in 0..image.width() {
+            let (red, green, blue) = layout.decode(image.get_pixel(x, y));
+
+            // decoded components are scaled to 16 bits
+            data.extend_from_slice(&[
+                (red >> 8) as u8,
+                (green >> 8) as u8,
+                (blue >> 8) as u8,
+                u8::MAX,
+            ]);
+        }
+    }
+
+    Ok(crate::Image {
+        width: geometry.width,
+        height: geometry.height,
+        data,
+    })
+}
@@ -0,0 +1,1226 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet, VecDeque},
+    os::fd::AsFd as _,
+    rc::Rc,
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
+
+use x11rb::{
+    CURRENT_TIME,
+    connection::Connection as _,
+    protocol::{
+        Event,
+        randr::{ConnectionExt as _, NotifyMask},
+        xinput::{
+            self, ConnectionExt as _, Device, PointerEventFlags, RawMotionEvent, XIEventMask,
+        },
+        xproto::{
+            AtomEnum, ClientMessageEvent, ConnectionExt as _, EventMask, GrabMode, GrabStatus,
+            KeyPressEvent, Mapping, NotifyDetail, NotifyMode, Screen, Visibility, Visualtype,
+        },
+    },
+    xcb_ffi::XCBConnection,
+};
+
+use crate::events::{
+    EventSystem, KeyEvent, ModifiersChangedEvent, MouseButtonEvent, MouseMoveEvent,
+    MouseWheelEvent, RawMouseMotionEvent, TextEvent, UserEvent, WindowEvent, WindowResizeEvent,
+};
+use crate::{
+    DamageRect, Error, Rect, WindowId,
+    cursor::{CursorGrabMode, CursorIcon},
+    inputs::Modifiers,
+    linux::{inputs::translate_modifiers, poll, xkb::Keyboard},
+    monitor::MonitorHandle,
+};
+
+use super::{
+    cursor::{CursorTheme, create_blank_cursor},
+    monitor::{self, has_randr},
+    xinput::{Scrolling, raw_motion},
+};
+
+x11rb::atom_manager! {
+    pub Atoms: AtomsCookie {
+        WM_PROTOCOLS,
+        WM_DELETE_WINDOW,
+        WM_CHANGE_STATE,
+        _NET_WM_ICON,
+        _NET_WM_ICON_NAME,
+        _NET_WM_NAME,
+        _NET_WM_STATE,
+        _NET_WM_STATE_FULLSCREEN,
+        _NET_WM_FULLSCREEN_MONITORS,
+        _NET_FRAME_EXTENTS,
+        _NET_WM_STATE_MAXIMIZED_VERT,
+        _NET_WM_STATE_MAXIMIZED_HORZ,
+        _NET_WM_STATE_ABOVE,
+        _NET_WM_STATE_BELOW,
+        _NET_WM_STATE_DEMANDS_ATTENTION,
+        _NET_WM_STATE_HIDDEN,
+        _NET_ACTIVE_WINDOW,
+        _MOTIF_WM_HINTS,
+        UTF8_STRING,
+        _PANE_WAKE_UP,
+    }
+}
+
+type UserEvents = Arc<Mutex<VecDeque<(u32, UserEvent)>>>;
+
+/// A single X11 connection, shared by every window created on it.
+///
+/// Incoming events are routed to the `EventSystem` of the window they belong to.
+#[derive(Debug)]
+pub struct XConnection {
+    pub conn: Arc<XCBConnection>,
+    pub screen_num: usize,
+    pub atoms: Atoms,
+    windows: RefCell<HashMap<u32, Arc<dyn EventSystem>>>,
+    user_events: UserEvents,
+    keyboard: RefCell<Option<Keyboard>>,
+    modifiers: Cell<Modifiers>,
+    scrolling: RefCell<Option<Scrolling>>,
+    raw_motion: RefCell<HashSet<u32>>,
+    grab: Cell<Option<Grab>>,
+    blank_cursor: Cell<Option<u32>>,
+    cursor_theme: RefCell<Option<CursorTheme>>,
+    randr: bool,
+    xft_dpi: Option<f64>,
+    /// All monitors, reloaded when `RandR` reports a change.
+    monitors: RefCell<Vec<monitor::Monitor>>,
+    /// The scale factor of every window, taken from the monitor it was last moved to.
+    scale_factors: RefCell<HashMap<u32, f64>>,
+    reported: RefCell<HashMap<u32, Reported>>,
+    /// The damage of every window until the next `RedrawRequested`, `None` for all of it.
+    redraws: RefCell<HashMap<u32, Option<DamageRect>>>,
+}
+
+/// The state of a window, as it was last reported to its `EventSystem`.
+///
+/// X11 repeats events without a change, like a `ConfigureNotify` for every restack.
+#[derive(Debug, Default)]
+struct Reported {
+    /// The position and size of the last `ConfigureNotify`.
+    configure: Option<(i16, i16, u16, u16)>,
+    position: Option<(i32, i32)>,
+    occluded: bool,
+    minimized: bool,
+}
+
+/// The pointer grab of a window, there can only be one per client.
+#[derive(Debug, Clone, Copy)]
+struct Grab {
+    window: u32,
+    mode: CursorGrabMode,
+    /// Where a locked pointer is kept.
+    center: (i16, i16),
+    /// Grabs are released while the window is not focused.
+    active: bool,
+}
+
+impl XConnection {
+    pub fn connect() -> Result<Self, Error> {
+        // use raw xcb connection instead of rust connection to interface with vulkan
+        let (conn, screen_num) = XCBConnection::connect(None)?;
+
+        // load atoms
+        let atoms = Atoms::new(&conn)?.reply()?;
+
+        let scrolling = Scrolling::new(&conn)?;
+        let randr = has_randr(&conn)?;
+        let root = conn.setup().roots[screen_num].root;
+
+        // monitors are reported on the root window
+        if randr {
+            conn.randr_select_input(root, NotifyMask::SCREEN_CHANGE)?;
+        }
+
+        let xft_dpi = monitor::xft_dpi(&conn, root)?;
+
+        let keyboard = Keyboard::new().map(|mut keyboard| {
+            // SAFETY:
+            // the raw connection is valid, as long as conn lives.
+            unsafe { keyboard.load_x11(conn.get_raw_xcb_connection()) };
+            keyboard
+        });
+
+        let xconn = Self {
+            conn: Arc::new(conn),
+            screen_num,
+            atoms,
+            windows: RefCell::new(HashMap::new()),
+            user_events: Arc::default(),
+            keyboard: RefCell::new(keyboard),
+            modifiers: Cell::default(),
+            scrolling: RefCell::new(scrolling),
+            raw_motion: RefCell::default(),
+            grab: Cell::new(None),
+            blank_cursor: Cell::new(None),
+            cursor_theme: RefCell::new(None),
+            randr,
+            xft_dpi,
+            monitors: RefCell::default(),
+            scale_factors: RefCell::default(),
+            reported: RefCell::default(),
+            redraws: RefCell::default(),
+        };
+
+        xconn.reload_monitors();
+
+        Ok(xconn)
+    }
+
+    pub fn screen(&self) -> &Screen {
+        &self.conn.setup().roots[self.screen_num]
+    }
+
+    pub const fn has_randr(&self) -> bool {
+        self.randr
+    }
+
+    /// Returns the DPI set in the resources of the screen, which overrides that of the monitors.
+    pub const fn xft_dpi(&self) -> Option<f64> {
+        self.xft_dpi
+    }
+
+    /// Returns all monitors, or none if they can't be queried.
+    pub fn available_monitors(&self) -> Vec<MonitorHandle> {
+        self.monitors
+            .borrow()
+            .iter()
+            .map(|monitor| monitor.handle.clone())
+            .collect()
+    }
+
+    pub fn primary_monitor(&self) -> Option<MonitorHandle> {
+        self.monitors
+            .borrow()
+            .iter()
+            .find_map(|monitor| monitor.primary.then(|| monitor.handle.clone()))
+    }
+
+    /// Returns the Xinerama index of a monitor, which window managers refer to monitors by.
+    pub fn xinerama_index(&self, id: u32) -> Option<u32> {
+        self.monitors
+            .borrow()
+            .iter()
+            .find_map(|monitor| (monitor.handle.id == id).then_some(monitor.xinerama_index))
+    }
+
+    fn reload_monitors(&self) {
+        let monitors = if self.randr {
+            monitor::query_monitors(self).unwrap_or_else(|err| {
+                log::warn!("failed to query monitors: {err}");
+                Vec::new()
+            })
+        } else {
+            Vec::new()
+        };
+
+        self.monitors.replace(monitors);
+    }
+
+    /// Returns the scale factor of a window, or that of the primary monitor,
+    /// before the window was placed.
+    pub fn scale_factor(&self, window: u32) -> f64 {
+        self.scale_factors
+            .borrow()
+            .get(&window)
+            .copied()
+            .unwrap_or_else(|| self.default_scale_factor())
+    }
+
+    fn default_scale_factor(&self) -> f64 {
+        self.primary_monitor().map_or_else(
+            || self.xft_dpi.map_or(1.0, |dpi| dpi / 96.0),
+            |monitor| monitor.scale_factor(),
+        )
+    }
+
+    /// Takes the scale factor of the monitor at the center of a window,
+    /// and reports it, if it changed.
+    fn update_scale_factor(
+        &self,
+        eventsys: &dyn EventSystem,
+        window: u32,
+        (x, y): (i32, i32),
+        size: Rect,
+    ) {
+        let Some(monitor) = monitor::monitor_at(
+            self.available_monitors(),
+            x + i32::from(size.x / 2),
+            y + i32::from(size.y / 2),
+        ) else {
+            return;
+        };
+
+        let scale_factor = monitor.scale_factor();
+        let previous = self
+            .scale_factors
+            .borrow_mut()
+            .insert(window, scale_factor)
+            .unwrap_or_else(|| self.default_scale_factor());
+
+        if (previous - scale_factor).abs() > f64::EPSILON {
+            eventsys.invoke_window_event(
+                WindowId::from_raw(u64::from(window)),
+                WindowEvent::ScaleFactorChanged { scale_factor },
+            );
+        }
+    }
+
+    /// Sends a request to the window manager, as described by EWMH.
+    pub fn send_wm_message(&self, window: u32, kind: u32, data: [u32; 5]) -> Result<(), Error> {
+        let message = ClientMessageEvent::new(32, window, kind, data);
+
+        self.conn.send_event(
+            false,
+            self.screen().root,
+            EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY,
+            message,
+        )?;
+
+        Ok(())
+    }
+
+    pub fn visual_type(&self, visual: u32) -> Option<Visualtype> {
+        self.screen()
+            .allowed_depths
+            .iter()
+            .flat_map(|depth| &depth.visuals)
+            .find(|visual_type| visual_type.visual_id == visual)
+            .copied()
+    }
+
+    pub fn register(&self, window: u32, eventsys: Arc<dyn EventSystem>) {
+        self.windows.borrow_mut().insert(window, eventsys);
+    }
+
+    /// Replaces the core pointer events of a window with those of `XInput` 2, if it is available.
+    ///
+    /// Only `XInput` 2 reports smooth scrolling.
+    pub fn select_xinput_events(&self, window: u32) -> Result<(), Error> {
+        if self.scrolling.borrow().is_none() {
+            return Ok(());
+        }
+
+        let mask = XIEventMask::BUTTON_PRESS
+            | XIEventMask::BUTTON_RELEASE
+            | XIEventMask::MOTION
+            | XIEventMask::ENTER
+            | XIEventMask::LEAVE
+            | XIEventMask::DEVICE_CHANGED;
+
+        self.conn.xinput_xi_select_events(
+            window,
+            &[xinput::EventMask {
+                deviceid: Device::ALL_MASTER.into(),
+                mask: vec![mask],
+            }],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn unregister(&self, window: u32) {
+        self.windows.borrow_mut().remove(&window);
+        self.scale_factors.borrow_mut().remove(&window);
+        self.reported.borrow_mut().remove(&window);
+        self.redraws.borrow_mut().remove(&window);
+
+        if self.raw_motion.borrow_mut().remove(&window) {
+            _ = self.select_raw_motion();
+        }
+
+        if self.grab.get().is_some_and(|grab| grab.window == window) {
+            self.grab.set(None);
+        }
+    }
+
+    /// Returns an invisible cursor, that is created on first use.
+    pub fn blank_cursor(&self) -> Result<u32, Error> {
+        if let Some(cursor) = self.blank_cursor.get() {
+            return Ok(cursor);
+        }
+
+        let cursor = create_blank_cursor(&self.conn, self.screen().root)?;
+        self.blank_cursor.set(Some(cursor));
+
+        Ok(cursor)
+    }
+
+    /// Returns a cursor of the cursor theme, that is loaded on first use.
+    pub fn load_cursor(&self, icon: CursorIcon) -> Result<u32, Error> {
+        let mut theme = self.cursor_theme.borrow_mut();
+
+        let theme = match &mut *theme {
+            Some(theme) => theme,
+            None => theme.insert(CursorTheme::new(&self.conn, self.screen_num)?),
+        };
+
+        theme.load(&self.conn, icon)
+    }
+
+    /// Grabs the pointer for a window, replacing the grab of any other window.
+    pub fn set_cursor_grab(
+        &self,
+        window: u32,
+        mode: CursorGrabMode,
+        size: Rect,
+    ) -> Result<(), Error> {
+        if mode == CursorGrabMode::None {
+            if self.grab.get().is_some_and(|grab| grab.window == window) {
+                self.grab.set(None);
+                self.conn.ungrab_pointer(CURRENT_TIME)?;
+                self.conn.flush()?;
+            }
+
+            return Ok(());
+        }
+
+        let grab = Grab {
+            window,
+            mode,
+            center: (
+                i16::try_from(size.x / 2).unwrap_or(i16::MAX),
+                i16::try_from(size.y / 2).unwrap_or(i16::MAX),
+            ),
+            active: true,
+        };
+
+        self.grab_pointer(grab)?;
+        self.grab.set(Some(grab));
+
+        Ok(())
+    }
+
+    fn grab_pointer(&self, grab: Grab) -> Result<(), Error> {
+        // with owner events, the window keeps getting the events it selected
+        let status = self
+            .conn
+            .grab_pointer(
+                true,
+                grab.window,
+                EventMask::BUTTON_PRESS | EventMask::BUTTON_RELEASE | EventMask::POINTER_MOTION,
+                GrabMode::ASYNC,
+                GrabMode::ASYNC,
+                grab.window,
+                x11rb::NONE,
+                CURRENT_TIME,
+            )?
+            .reply()?
+            .status;
+
+        if status != GrabStatus::SUCCESS {
+            return Err(Error::Grab(status));
+        }
+
+        if grab.mode == CursorGrabMode::Locked {
+            let (x, y) = grab.center;
+            self.conn
+                .warp_pointer(x11rb::NONE, grab.window, 0, 0, 0, 0, x, y)?;
+        }
+
+        self.conn.flush()?;
+
+        Ok(())
+    }
+
+    /// Releases the grab of a window, that lost focus, and takes it again once it is focused.
+    fn update_grab(&self, window: u32, focused: bool) {
+        let Some(mut grab) = self.grab.get().filter(|grab| grab.window == window) else {
+            return;
+        };
+
+        if grab.active == focused {
+            return;
+        }
+
+        if focused {
+            if let Err(err) = self.grab_pointer(grab) {
+                log::warn!("{err}");
+                return;
+            }
+        } else {
+            _ = self.conn.ungrab_pointer(CURRENT_TIME);
+            _ = self.conn.flush();
+        }
+
+        grab.active = focused;
+        self.grab.set(Some(grab));
+    }
+
+    /// Keeps a locked pointer at the center of its window.
+    ///
+    /// Returns `true`, if the motion is not reported, because a locked pointer doesn't move.
+    fn warp_locked_pointer(&self, window: u32, x: i16, y: i16) -> bool {
+        let Some(grab) = self.grab.get().filter(|grab| {
+            grab.window == window && grab.mode == CursorGrabMode::Locked && grab.active
+        }) else {
+            return false;
+        };
+
+        if (x, y) != grab.center {
+            let (center_x, center_y) = grab.center;
+
+            _ = self
+                .conn
+                .warp_pointer(x11rb::NONE, window, 0, 0, 0, 0, center_x, center_y);
+            _ = self.conn.flush();
+        }
+
+        true
+    }
+
+    /// Adds or removes a window from those, that get raw motion events.
+    pub fn set_raw_mouse_motion(&self, window: u32, enabled: bool) -> Result<(), Error> {
+        if self.scrolling.borrow().is_none() {
+            return Err(Error::Unsupported("raw mouse motion without XInput 2"));
+        }
+
+        if enabled {
+            self.raw_motion.borrow_mut().insert(window);
+        } else {
+            self.raw_motion.borrow_mut().remove(&window);
+        }
+
+        self.select_raw_motion()?;
+        self.conn.flush()?;
+
+        Ok(())
+    }
+
+    /// Raw events are only reported on the root window,
+    /// so they are selected there while any window wants them.
+    fn select_raw_motion(&self) -> Result<(), Error> {
+        let mask = if self.raw_motion.borrow().is_empty() {
+            XIEventMask::from(0u32)
+        } else {
+            XIEventMask::RAW_MOTION
+        };
+
+        self.conn.xinput_xi_select_events(
+            self.screen().root,
+            &[xinput::EventMask {
+                deviceid: Device::ALL_MASTER.into(),
+                mask: vec![mask],
+            }],
+        )?;
+
+        Ok(())
+    }
+
+    /// Processes all pending events of every window on this connection.
+    pub fn pump_messages(&self) -> Result<(), Error> {
+        while let Some(event) = self.conn.poll_for_event()? {
+            self.dispatch(&event);
+        }
+
+        self.dispatch_redraws();
+
+        Ok(())
+    }
+
+    /// Blocks until at least one event arrives or the timeout expires, then processes all pending events.
+    pub fn wait_events(&self, timeout: Option<Duration>) -> Result<(), Error> {
+        self.conn.flush()?;
+
+        // a requested redraw is due right away
+        let timeout = if self.redraws.borrow().is_empty() {
+            timeout
+        } else {
+            Some(Duration::ZERO)
+        };
+
+        // xcb may already have read events from the socket
+        match self.conn.poll_for_event()? {
+            Some(event) => self.dispatch(&event),
+            None => {
+                poll::wait_readable([self.conn.as_fd()], timeout)?;
+            }
+        }
+
+        self.pump_messages()
+    }
+
+    pub fn create_proxy(&self, window: u32) -> EventLoopProxy {
+        EventLoopProxy {
+            conn: Arc::clone(&self.conn),
+            window,
+            wake_up: self.atoms._PANE_WAKE_UP,
+            user_events: Arc::clone(&self.user_events),
+        }
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn dispatch(&self, event: &Event) {
+        // the keyboard layout changed
+        if let Event::MappingNotify(e) = event
+            && e.request == Mapping::KEYBOARD
+        {
+            self.reload_keymap();
+        }
+
+        if let Event::RandrScreenChangeNotify(_) = event {
+            self.reload_monitors();
+            self.dispatch_monitors_changed();
+            return;
+        }
+
+        if let Event::XinputRawMotion(e) = event {
+            self.dispatch_raw_motion(e);
+            return;
+        }
+
+        // a pointer device changed its valuators, or another device took over the master pointer
+        if let Event::XinputDeviceChanged(e) = event
+            && let Some(scrolling) = self.scrolling.borrow_mut().as_mut()
+        {
+            scrolling.update_device(e.sourceid, &e.classes);
+        }
+
+        let Some(window) = event_window(event) else {
+            return;
+        };
+
+        // clone the event system out of the map, so callbacks are free to create or destroy windows
+        let Some(eventsys) = self.windows.borrow().get(&window).cloned() else {
+            return;
+        };
+
+        let id = WindowId::from_raw(u64::from(window));
+
+        match event {
+            // key events
+            Event::KeyPress(k) | Event::KeyRelease(k) => {
+                let is_pressed = matches!(event, Event::KeyPress(_));
+                let raw_key = k.detail;
+
+                let key = crate::linux::inputs::x_translate_key(raw_key);
+                let modifiers = self.update_modifiers(&*eventsys, id, k.state.into());
+
+                eventsys.invoke_key_event(
+                    id,
+                    KeyEvent {
+                        key,
+                        down: is_pressed,
+                        repeat: 0,
+                        modifiers,
+                    },
+                );
+
+                if is_pressed && let Some(text) = self.key_text(k) {
+                    eventsys.invoke_text_event(id, TextEvent { text });
+                }
+
+                // the state of an event is the one before it, so ask the server for the new one
+                if key.modifier().is_some()
+                    && let Some(pointer) = self
+                        .conn
+                        .query_pointer(window)
+                        .ok()
+                        .and_then(|cookie| cookie.reply().ok())
+                {
+                    self.update_modifiers(&*eventsys, id, pointer.mask.into());
+                }
+            }
+
+            // mouse button events
+            Event::ButtonPress(e) | Event::ButtonRelease(e) => {
+                let is_pressed = matches!(event, Event::ButtonPress(_));
+                let modifiers = self.update_modifiers(&*eventsys, id, e.state.into());
+
+                button_event(&*eventsys, id, e.detail, is_pressed, modifiers);
+            }
+
+            // once selected, XInput 2 pointer events replace the core ones
+            Event::XinputButtonPress(e) | Event::XinputButtonRelease(e) => {
+                // emulated from the scroll valuators, which are handled with the motion events
+                if e.flags.contains(PointerEventFlags::POINTER_EMULATED) {
+                    return;
+                }
+
+                let Ok(button) = u8::try_from(e.detail) else {
+                    return;
+                };
+
+                let is_pressed = matches!(event, Event::XinputButtonPress(_));
+                let modifiers = self.update_modifiers(&*eventsys, id, e.mods.effective);
+
+                button_event(&*eventsys, id, button, is_pressed, modifiers);
+            }
+
+            Event::XinputMotion(e) => {
+                let modifiers = self.update_modifiers(&*eventsys, id, e.mods.effective);
+
+                let delta = self
+                    .scrolling
+                    .borrow_mut()
+                    .as_mut()
+                    .and_then(|scrolling| scrolling.scroll(e));
+
+                if let Some(delta) = delta
+                    && let Some(direction) = delta.direction()
+                {
+                    eventsys.invoke_mouse_wheel_event(
+                        id,
+                        MouseWheelEvent {
+                            direction,
+                            delta,
+                            modifiers,
+                        },
+                    );
+                }
+
+                // scrolling alone doesn't move the pointer, which has the first two valuators
+                let moved =
+                    delta.is_none() || e.valuator_mask.first().is_some_and(|mask| mask & 0b11 != 0);
+                let locked = moved
+                    && self.warp_locked_pointer(
+                        e.event,
+                        i16::try_from(e.event_x >> 16).unwrap_or(i16::MAX),
+                        i16::try_from(e.event_y >> 16).unwrap_or(i16::MAX),
+                    );
+
+                if moved && !locked {
+                    // the position is a 16.16 fixed point number
+                    eventsys.invoke_mouse_move_event(
+                        id,
+                        MouseMoveEvent {
+                            x_pos: u32::try_from(e.event_x >> 16).unwrap_or(0),
+                            y_pos: u32::try_from(e.event_y >> 16).unwrap_or(0),
+                            modifiers,
+                        },
+                    );
+                }
+            }
+
+            Event::XinputEnter(e) => {
+                // scrolling outside of our windows went unnoticed
+                self.reload_scrolling();
+
+                if e.mode == xinput::NotifyMode::NORMAL {
+                    eventsys.invoke_window_event(id, WindowEvent::CursorEntered);
+                }
+            }
+
+            // pointer grabs move the pointer into the window and back, without the cursor moving
+            Event::XinputLeave(e) if e.mode == xinput::NotifyMode::NORMAL => {
+                eventsys.invoke_window_event(id, WindowEvent::CursorLeft);
+            }
+
+            // without XInput 2, the core crossing events are selected
+            Event::EnterNotify(e) if e.mode == NotifyMode::NORMAL => {
+                eventsys.invoke_window_event(id, WindowEvent::CursorEntered);
+            }
+            Event::LeaveNotify(e) if e.mode == NotifyMode::NORMAL => {
+                eventsys.invoke_window_event(id, WindowEvent::CursorLeft);
+            }
+
+            // mouse move
+            Event::MotionNotify(m) => {
+                let x = m.event_x;
+                let y = m.event_y;
+                let modifiers = self.update_modifiers(&*eventsys, id, m.state.into());
+
+                if self.warp_locked_pointer(m.event, x, y) {
+                    return;
+                }
+
+                // the pointer is outside of the window, while a button is held down
+                eventsys.invoke_mouse_move_event(
+                    id,
+                    MouseMoveEvent {
+                        x_pos: u32::try_from(x).unwrap_or(0),
+                        y_pos: u32::try_from(y).unwrap_or(0),
+                        modifiers,
+                    },
+                );
+            }
+
+            Event::FocusIn(f) | Event::FocusOut(f) => {
+                let focused = matches!(event, Event::FocusIn(_));
+
+                // grabs would keep the pointer from other windows
+                self.update_grab(f.event, focused);
+
+                // keyboard grabs, and the focus of the window under the pointer, don't move the focus
+                if f.mode != NotifyMode::GRAB
+                    && f.mode != NotifyMode::UNGRAB
+                    && f.detail != NotifyDetail::POINTER
+                {
+                    eventsys.invoke_window_event(id, WindowEvent::Focused(focused));
+                }
+            }
+
+            // the server sends the exposed areas one by one, the rest follow in this batch
+            Event::Expose(e) => {
+                let damage = DamageRect::new(e.x, e.y, e.width, e.height);
+                self.add_damage(e.window, Some(damage));
+            }
+
+            Event::VisibilityNotify(v) => {
+                let occluded = v.state == Visibility::FULLY_OBSCURED;
+
+                if self.report(v.window, |reported| &mut reported.occluded, occluded) {
+                    eventsys.invoke_window_event(id, WindowEvent::Occluded(occluded));
+                }
+            }
+
+            // the window manager adds the hidden state to minimized windows
+            Event::PropertyNotify(p) if p.atom == self.atoms._NET_WM_STATE => {
+                self.update_minimized(&*eventsys, id, p.window);
+            }
+
+            // resize
+            Event::ConfigureNotify(r) => {
+                let height = r.height;
+                let width = r.width;
+
+                if let Some(mut grab) = self.grab.get().filter(|grab| grab.window == r.window) {
+                    grab.center = (
+                        i16::try_from(width / 2).unwrap_or(i16::MAX),
+                        i16::try_from(height / 2).unwrap_or(i16::MAX),
+                    );
+                    self.grab.set(Some(grab));
+                }
+
+                // restacking the window sends one too, without moving or resizing it
+                let configure = Some((r.x, r.y, width, height));
+                let changed = self.report(r.window, |reported| &mut reported.configure, configure);
+
+                let origin = if changed {
+                    self.origin(r.window)
+                        .inspect_err(|err| log::warn!("{err}"))
+                        .ok()
+                } else {
+                    None
+                };
+
+                if let Some(origin) = origin {
+                    let size = Rect::new(width, height);
+                    self.update_scale_factor(&*eventsys, r.window, origin, size);
+                }
+
+                eventsys.invoke_window_resize_event(
+                    id,
+                    WindowResizeEvent {
+                        width: u32::from(width),
+                        height: u32::from(height),
+                    },
+                );
+
+                if let Some(origin) = origin {
+                    self.update_position(&*eventsys, id, r.window, origin);
+                }
+            }
+
+            // woken up by an `EventLoopProxy`
+            Event::ClientMessage(m) if m.type_ == self.atoms._PANE_WAKE_UP => {
+                self.dispatch_user_events();
+            }
+
+            // close window
+            Event::ClientMessage(m) => {
+                // We have received a message from the server
+                let atom = m.data.as_data32()[0];
+
+                // closing is up to the application, the other windows keep receiving events
+                if atom == self.atoms.WM_DELETE_WINDOW {
+                    eventsys.invoke_window_event(id, WindowEvent::CloseWindow);
+                }
+            }
+
+            _ => {}
+        }
+    }
+
+    /// Replaces a reported state of a window, and returns `true` if it changed.
+    fn report<T: PartialEq>(
+        &self,
+        window: u32,
+        field: impl FnOnce(&mut Reported) -> &mut T,
+        value: T,
+    ) -> bool {
+        let mut reported = self.reported.borrow_mut();
+        let field = field(reported.entry(window).or_default());
+
+        *field != value && {
+            *field = value;
+            true
+        }
+    }
+
+    /// Returns the position of the client area on the root window.
+    fn origin(&self, window: u32) -> Result<(i32, i32), Error> {
+        let origin = self
+            .conn
+            .translate_coordinates(window, self.screen().root, 0, 0)?
+            .reply()?;
+
+        Ok((i32::from(origin.dst_x), i32::from(origin.dst_y)))
+    }
+
+    /// Reports the position of the client area on the root window, if it changed.
+    ///
+    /// The coordinates of a `ConfigureNotify` are relative to the frame, once a window manager
+    /// reparented the window.
+    fn update_position(
+        &self,
+        eventsys: &dyn EventSystem,
+        id: WindowId,
+        window: u32,
+        (x, y): (i32, i32),
+    ) {
+        if self.report(window, |reported| &mut reported.position, Some((x, y))) {
+            eventsys.invoke_window_event(id, WindowEvent::Moved { x, y });
+        }
+    }
+
+    /// Reports a window as minimized, while `_NET_WM_STATE` has the hidden state.
+    fn update_minimized(&self, eventsys: &dyn EventSystem, id: WindowId, window: u32) {
+        let states = self
+            .conn
+            .get_property(
+                false,
+                window,
+                self.atoms._NET_WM_STATE,
+                AtomEnum::ATOM,
+                0,
+                u32::MAX,
+            )
+            .map_err(Error::from)
+            .and_then(|cookie| Ok(cookie.reply()?));
+
+        let minimized = match states {
+            Ok(states) => states.value32().is_some_and(|mut states| {
+                states.any(|state| state == self.atoms._NET_WM_STATE_HIDDEN)
+            }),
+            Err(err) => {
+                log::warn!("{err}");
+                return;
+            }
+        };
+
+        if !self.report(window, |reported| &mut reported.minimized, minimized) {
+            return;
+        }
+
+        if minimized {
+            eventsys.invoke_window_event(id, WindowEvent::Minimized);
+
+            // an unmapped window gets no VisibilityNotify, until it is mapped again
+            if self.report(window, |reported| &mut reported.occluded, true) {
+                eventsys.invoke_window_event(id, WindowEvent::Occluded(true));
+            }
+        } else {
+            eventsys.invoke_window_event(id, WindowEvent::Restored);
+        }
+    }
+
+    /// Remembers the modifiers of an input event and reports them, if they changed since the last one.
+    ///
+    /// Changes made while none of our windows had focus are noticed with the next event.
+    fn update_modifiers(&self, eventsys: &dyn EventSystem, id: WindowId, state: u32) -> Modifiers {
+        let modifiers = translate_modifiers(state);
+
+        if self.modifiers.replace(modifiers) != modifiers {
+            eventsys.invoke_modifiers_changed_event(id, ModifiersChangedEvent { modifiers });
+        }
+
+        modifiers
+    }
+
+    /// Translates a key press with the modifiers and layout group active at the time of the event.
+    fn key_text(&self, e: &KeyPressEvent) -> Option<String> {
+        let keyboard = self.keyboard.borrow();
+        let keyboard = keyboard.as_ref()?;
+
+        keyboard.update_x11_state(u16::from(e.state));
+        keyboard.key_text(u32::from(e.detail))
+    }
+
+    fn reload_scrolling(&self) {
+        if let Some(scrolling) = self.scrolling.borrow_mut().as_mut()
+            && let Err(err) = scrolling.reload(&self.conn)
+        {
+            log::warn!("failed to reload scroll valuators: {err}");
+        }
+    }
+
+    fn reload_keymap(&self) {
+        if let Some(keyboard) = self.keyboard.borrow_mut().as_mut() {
+            // SAFETY:
+            // the raw connection is valid, as long as self.conn lives.
+            unsafe { keyboard.load_x11(self.conn.get_raw_xcb_connection()) };
+        }
+    }
+
+    /// Passes raw motion to every window, that enabled it.
+    fn dispatch_raw_motion(&self, e: &RawMotionEvent) {
+        let Some((dx, dy)) = raw_motion(e) else {
+            return;
+        };
+
+        let windows: Vec<u32> = self.raw_motion.borrow().iter().copied().collect();
+
+        for window in windows {
+            let Some(eventsys) = self.windows.borrow().get(&window).cloned() else {
+                continue;
+            };
+
+            eventsys.invoke_raw_mouse_motion_event(
+                WindowId::from_raw(u64::from(window)),
+                RawMouseMotionEvent { dx, dy },
+            );
+        }
+    }
+
+    /// Schedules a `RedrawRequested` for the whole window.
+    pub fn request_redraw(&self, window: u32) {
+        self.add_damage(window, None);
+    }
+
+    /// Merges damage into the pending redraw of a window.
+    fn add_damage(&self, window: u32, damage: Option<DamageRect>) {
+        self.redraws
+            .borrow_mut()
+            .entry(window)
+            .and_modify(|pending| *pending = pending.zip(damage).map(|(a, b)| union(a, b)))
+            .or_insert(damage);
+    }
+
+    /// Reports the damage collected by `Expose` events and `request_redraw`, once per window.
+    fn dispatch_redraws(&self) {
+        let redraws = std::mem::take(&mut *self.redraws.borrow_mut());
+
+        for (window, damage) in redraws {
+            let Some(eventsys) = self.windows.borrow().get(&window).cloned() else {
+                continue;
+            };
+
+            eventsys.invoke_window_event(
+                WindowId::from_raw(u64::from(window)),
+                WindowEvent::RedrawRequested { damage },
+            );
+        }
+    }
+
+    /// Tells every window, that monitors were added, removed or reconfigured.
+    fn dispatch_monitors_changed(&self) {
+        let windows: Vec<_> = self
+            .windows
+            .borrow()
+            .iter()
+            .map(|(window, eventsys)| (*window, Arc::clone(eventsys)))
+            .collect();
+
+        for (window, eventsys) in windows {
+            eventsys.invoke_window_event(
+                WindowId::from_raw(u64::from(window)),
+                WindowEvent::MonitorsChanged,
+            );
+
+            // the monitor of the window may have a new DPI, or be gone
+            let geometry = self
+                .conn
+                .get_geometry(window)
+                .ok()
+                .and_then(|cookie| cookie.reply().ok());
+
+            if let (Some(geometry), Ok(origin)) = (geometry, self.origin(window)) {
+                let size = Rect::new(geometry.width, geometry.height);
+                self.update_scale_factor(&*eventsys, window, origin, size);
+            }
+        }
+    }
+
+    fn dispatch_user_events(&self) {
+        let user_events = std::mem::take(
+            &mut *self
+                .user_events
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner),
+        );
+
+        for (window, e) in user_events {
+            let Some(eventsys) = self.windows.borrow().get(&window).cloned() else {
+                continue;
+            };
+
+            eventsys.invoke_user_event(WindowId::from_raw(u64::from(window)), e);
+        }
+    }
+}
+
+/// Wakes up the event loop of a window from another thread.
+#[derive(Debug, Clone)]
+pub struct EventLoopProxy {
+    conn: Arc<XCBConnection>,
+    window: u32,
+    wake_up: u32,
+    user_events: UserEvents,
+}
+
+impl crate::EventLoopProxyImpl for EventLoopProxy {
+    fn wake_up(&self, event: Option<UserEvent>) -> Result<(), Error> {
+        if let Some(event) = event {
+            self.user_events
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push_back((self.window, event));
+        }
+
+        let message = ClientMessageEvent::new(32, self.window, self.wake_up, [0; 5]);
+
+        self.conn
+            .send_event(false, self.window, EventMask::NO_EVENT, message)?;
+        self.conn.flush()?;
+
+        Ok(())
+    }
+}
+
+/// Reports a press or release of a button, where buttons 4 to 7 are the mouse wheel.
+fn button_event(
+    eventsys: &dyn EventSystem,
+    id: WindowId,
+    button: u8,
+    down: bool,
+    modifiers: Modifiers,
+) {
+    // scrolling is reported as a press and release of a button, only the press is kept
+    if let Some(direction) = crate::linux::inputs::x_wheel_direction(button) {
+        if down {
+            eventsys.invoke_mouse_wheel_event(
+                id,
+                MouseWheelEvent {
+                    direction,
+                    delta: direction.into(),
+                    modifiers,
+                },
+            );
+        }
+
+        return;
+    }
+
+    eventsys.invoke_mouse_button_event(
+        id,
+        MouseButtonEvent {
+            down,
+            button: crate::linux::inputs::x_translate_button(button),
+            modifiers,
+        },
+    );
+}
+
+/// Returns the smallest area, that covers both.
+fn union(a: DamageRect, b: DamageRect) -> DamageRect {
+    let x = a.x.min(b.x);
+    let y = a.y.min(b.y);
+    let right = a.x.saturating_add(a.width).max(b.x.saturating_add(b.width));
+    let bottom =
+        a.y.saturating_add(a.height)
+            .max(b.y.saturating_add(b.height));
+
+    DamageRect::new(x, y, right - x, bottom - y)
+}
+
+/// Returns the window an event is addressed to.
+const fn event_window(event: &Event) -> Option<u32> {
+    match event {
+        Event::KeyPress(e) | Event::KeyRelease(e) => Some(e.event),
+        Event::ButtonPress(e) | Event::ButtonRelease(e) => Some(e.event),
+        Event::MotionNotify(e) => Some(e.event),
+        Event::XinputButtonPress(e) | Event::XinputButtonRelease(e) | Event::XinputMotion(e) => {
+            Some(e.event)
+        }
+        Event::XinputEnter(e) | Event::XinputLeave(e) => Some(e.event),
+        Event::EnterNotify(e) | Event::LeaveNotify(e) => Some(e.event),
+        Event::FocusIn(e) | Event::FocusOut(e) => Some(e.event),
+        Event::VisibilityNotify(e) => Some(e.window),
+        Event::PropertyNotify(e) => Some(e.window),
+        Event::ConfigureNotify(e) => Some(e.window),
+        Event::ClientMessage(e) => Some(e.window),
+        Event::Expose(e) => Some(e.window),
+        _ => None,
+    }
+}
+
+/// Owns a single X11 connection that any number of windows can be created on.
+#[derive(Debug)]
+pub struct EventLoop {
+    xconn: Rc<XConnection>,
+}
+
+impl crate::EventLoopImpl for EventLoop {
+    fn new() -> Result<Self, Error> {
+        let xconn = XConnection::connect()?;
+
+        Ok(Self {
+            xconn: Rc::new(xconn),
+        })
+    }
+
+    fn pump_messages(&mut self) -> Result<(), Error> {
+        self.xconn.pump_messages()
+    }
+
+    fn wait_events(&mut self, timeout: Option<Duration>) -> Result<(), Error> {
+        self.xconn.wait_events(timeout)
+    }
+
+    fn available_monitors(&self) -> Vec<MonitorHandle> {
+        self.xconn.available_monitors()
+    }
+
+    fn primary_monitor(&self) -> Option<MonitorHandle> {
+        self.xconn.primary_monitor()
+    }
+}
+
+impl EventLoop {
+    pub fn connection(&self) -> Rc<XConnection> {
+        Rc::clone(&self.xconn)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn union_of_disjoint_rects() {
+        assert_eq!(
+            union(DamageRect::new(0, 0, 4, 4), DamageRect::new(10, 20, 5, 5)),
+            DamageRect::new(0, 0, 15, 25)
+        );
+        assert_eq!(
+            union(DamageRect::new(10, 20, 5, 5), DamageRect::new(0, 0, 4, 4)),
+            DamageRect::new(0, 0, 15, 25)
+        );
+    }
+
+    #[test]
+    fn union_of_nested_rects() {
+        let outer = DamageRect::new(2, 2, 20, 20);
+        let inner = DamageRect::new(5, 5, 3, 3);
+
+        assert_eq!(union(outer, inner), outer);
+        assert_eq!(union(inner, outer), outer);
+    }
+
+    #[test]
+    fn union_past_u16_max_saturates() {
+        assert_eq!(
+            union(
+                DamageRect::new(65_000, 0, 1_000, 1),
+                DamageRect::new(0, 0, 1, 1)
+            ),
+            DamageRect::new(0, 0, u16::MAX, 1)
+        );
+    }
+}
@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+
+use x11rb::{
+    connection::{Connection as _, RequestConnection as _},
+    cursor::Handle,
+    image::{BitsPerPixel, Image, ScanlinePad},
+    protocol::{
+        render::{self, ConnectionExt as _, CreatePictureAux, PictType},
+        xproto::{ConnectionExt as _, CreateGCAux, Rectangle},
+    },
+    resource_manager,
+    xcb_ffi::XCBConnection,
+};
+
+use crate::{Error, cursor::CursorIcon};
+
+use super::present::native_order;
+
+/// Loads cursors from the cursor theme of the user, falling back to the core cursor font.
+///
+/// Cursors are shared by all windows and kept until the connection is closed.
+#[derive(Debug)]
+pub struct CursorTheme {
+    handle: Handle,
+    cursors: HashMap<CursorIcon, u32>,
+}
+
+impl CursorTheme {
+    pub fn new(conn: &XCBConnection, screen_num: usize) -> Result<Self, Error> {
+        // the theme and size are configured in the resource database
+        let database = resource_manager::new_from_default(conn)?;
+        let handle = Handle::new(conn, screen_num, &database)?.reply()?;
+
+        Ok(Self {
+            handle,
+            cursors: HashMap::new(),
+        })
+    }
+
+    /// Returns `x11rb::NONE` for `CursorIcon::Default`, which uses the cursor of the root window.
+    pub fn load(&mut self, conn: &XCBConnection, icon: CursorIcon) -> Result<u32, Error> {
+        if icon == CursorIcon::Default {
+            return Ok(x11rb::NONE);
+        }
+
+        if let Some(cursor) = self.cursors.get(&icon) {
+            return Ok(*cursor);
+        }
+
+        let mut cursor = x11rb::NONE;
+        for name in cursor_names(icon) {
+            cursor = self.handle.load_cursor(conn, name)?;
+
+            if cursor != x11rb::NONE {
+                break;
+            }
+        }
+
+        if cursor == x11rb::NONE {
+            log::warn!("no cursor found for {icon:?}");
+        }
+
+        self.cursors.insert(icon, cursor);
+
+        Ok(cursor)
+    }
+}
+
+/// Names of a cursor shape, as used by cursor themes.
+///
+/// The last name is one of the core cursor font, that is used without a theme.
+const fn cursor_names(icon: CursorIcon) -> &'static [&'static str] {
+    match icon {
+        CursorIcon::Default => &["default", "left_ptr"],
+        CursorIcon::Text => &["text", "xterm"],
+        CursorIcon::Hand => &["pointer", "hand2"],
+        CursorIcon::Crosshair => &["crosshair"],
+        CursorIcon::Move => &["move", "fleur"],
+        CursorIcon::EwResize => &["ew-resize", "sb_h_double_arrow"],
+        CursorIcon::NsResize => &["ns-resize", "sb_v_double_arrow"],
+        CursorIcon::NeswResize => &["nesw-resize", "size_bdiag", "bottom_left_corner"],
+        CursorIcon::NwseResize => &["nwse-resize", "size_fdiag", "bottom_right_corner"],
+        CursorIcon::Wait => &["wait", "watch"],
+        CursorIcon::Progress => &["progress", "left_ptr_watch", "watch"],
+        CursorIcon::NotAllowed => &["not-allowed", "crossed_circle", "X_cursor"],
+    }
+}
+
+/// Creates a cursor from RGBA pixels, that aren't premultiplied.
+pub fn create_custom_cursor(
+    conn: &XCBConnection,
+    root: u32,
+    rgba: &[u8],
+    width: u16,
+    height: u16,
+    (hotspot_x, hotspot_y): (u16, u16),
+) -> Result<u32, Error> {
+    crate::icon::check_rgba_size(rgba, width, height)?;
+
+    if conn
+        .extension_information(render::X11_EXTENSION_NAME)?
+        .is_none()
+    {
+        return Err(Error::Unsupported("custom cursors without RENDER"));
+    }
+
+    let format = conn
+        .render_query_pict_formats()?
+        .reply()?
+        .formats
+        .into_iter()
+        .find(|format| {
+            let direct = format.direct;
+
+            format.type_ == PictType::DIRECT
+                && format.depth == 32
+                && (direct.alpha_shift, direct.alpha_mask) == (24, 0xff)
+                && (direct.red_shift, direct.red_mask) == (16, 0xff)
+                && (direct.green_shift, direct.green_mask) == (8, 0xff)
+                && (direct.blue_shift, direct.blue_mask) == (0, 0xff)
+        })
+        .ok_or(Error::Unsupported(
+            "custom cursors without an ARGB32 picture format",
+        ))?;
+
+    // RENDER expects premultiplied ARGB pixels
+    let data = rgba
+        .chunks_exact(4)
+        .flat_map(|pixel| {
+            let [red, green, blue, alpha] = [pixel[0], pixel[1], pixel[2], pixel[3]];
+            let premultiply = |color: u8| {
+                u8::try_from(u16::from(color) * u16::from(alpha) / 255).unwrap_or(u8::MAX)
+            };
+
+            u32::from_be_bytes([
+                alpha,
+                premultiply(red),
+                premultiply(green),
+                premultiply(blue),
+            ])
+            .to_ne_bytes()
+        })
+        .collect::<Vec<u8>>();
+
+    let image = Image::new(
+        width,
+        height,
+        ScanlinePad::Pad32,
+        32,
+        BitsPerPixel::B32,
+        native_order(),
+        data.into(),
+    )?;
+
+    let pixmap = conn.generate_id()?;
+    conn.create_pixmap(32, pixmap, root, width, height)?;
+
+    let gc = conn.generate_id()?;
+    conn.create_gc(gc, pixmap, &CreateGCAux::new())?;
+    image.native(conn.setup())?.put(conn, pixmap, gc, 0, 0)?;
+    conn.free_gc(gc)?;
+
+    let picture = conn.generate_id()?;
+    conn.render_create_picture(picture, pixmap, format.id, &CreatePictureAux::new())?;
+
+    let cursor = conn.generate_id()?;
+    conn.render_create_cursor(
+        cursor,
+        picture,
+        hotspot_x.min(width.saturating_sub(1)),
+        hotspot_y.min(height.saturating_sub(1)),
+    )?;
+
+    conn.render_free_picture(picture)?;
+    conn.free_pixmap(pixmap)?;
+
+    Ok(cursor)
+}
+
+/// Creates an invisible cursor, from an empty 1x1 bitmap.
+pub fn create_blank_cursor(conn: &XCBConnection, root: u32) -> Result<u32, Error> {
+    let pixmap = conn.generate_id()?;
+    conn.create_pixmap(1, pixmap, root, 1, 1)?;
+
+    // the contents of a new pixmap are undefined
+    let gc = conn.generate_id()?;
+    conn.create_gc(gc, pixmap, &CreateGCAux::new().foreground(0))?;
+    conn.poly_fill_rectangle(
+        pixmap,
+        gc,
+        &[Rectangle {
+            x: 0,
+            y: 0,
+            width: 1,
+            height: 1,
+        }],
+    )?;
+    conn.free_gc(gc)?;
+
+    let cursor = conn.generate_id()?;
+    conn.create_cursor(cursor, pixmap, pixmap, 0, 0, 0, 0, 0, 0, 0, 0)?;
+    conn.free_pixmap(pixmap)?;
+
+    Ok(cursor)
+}
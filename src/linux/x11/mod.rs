@@ -0,0 +1,956 @@
+mod capture;
+mod connection;
+mod cursor;
+mod monitor;
+mod present;
+mod xinput;
+mod xtest;
+
+pub use connection::{EventLoop, EventLoopProxy};
+pub use xtest::InputInjector;
+
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+    sync::Arc,
+    time::Duration,
+};
+
+use x11rb::{
+    connection::Connection as _,
+    properties::{AspectRatio, WmHints, WmSizeHints, WmSizeHintsSpecification},
+    protocol::xproto::{
+        AtomEnum, ChangeWindowAttributesAux, ColormapAlloc, ConfigureWindowAux, ConnectionExt,
+        CreateWindowAux, EventMask, MapState, PropMode, UNMAP_NOTIFY_EVENT, UnmapNotifyEvent,
+        VisualClass, WindowClass,
+    },
+    wrapper::ConnectionExt as _,
+};
+
+use crate::{
+    DamageRect, Rect, UserAttentionType, WindowId, WindowLevel,
+    builder::WindowAttributes,
+    cursor::{CursorGrabMode, CursorIcon},
+    dpi::PhysicalPosition,
+    icon::Icon,
+    monitor::{Fullscreen, MonitorHandle},
+};
+
+use self::{connection::XConnection, monitor::SavedMode, present::Presenter};
+
+#[derive(Debug)]
+pub struct Window {
+    xconn: Rc<XConnection>,
+    window: u32,
+    depth: u8,
+    visual: u32,
+    /// The colormap of a transparent window, which has another visual than the root window.
+    colormap: Option<u32>,
+    presenter: Option<Presenter>,
+    /// The cursor shown while visible, `x11rb::NONE` for the default one.
+    cursor: Cell<u32>,
+    /// A cursor created by `set_custom_cursor`, that is freed once it's replaced.
+    custom_cursor: Cell<Option<u32>>,
+    cursor_visible: Cell<bool>,
+    title: RefCell<String>,
+    /// `WM_NORMAL_HINTS`, which the window manager reads whenever they change.
+    size_hints: RefCell<WmSizeHints>,
+    resizable: bool,
+    fullscreen: RefCell<Option<Fullscreen>>,
+    /// The mode of the monitor before `Fullscreen::Exclusive`, restored once it ends.
+    saved_mode: RefCell<Option<SavedMode>>,
+}
+
+impl Window {
+    #[allow(clippy::too_many_lines)]
+    pub fn create(
+        event_loop: &EventLoop,
+        attributes: &WindowAttributes,
+        eventsys: Arc<dyn crate::events::EventSystem>,
+    ) -> Result<Self, super::Error> {
+        let xconn = event_loop.connection();
+        let conn = &xconn.conn;
+
+        let screen = xconn.screen();
+        let window = conn.generate_id()?;
+
+        let parent = match attributes.parent {
+            None => screen.root,
+            Some(crate::RawWindowHandle::Xcb { window, .. }) => window,
+            Some(_) => {
+                return Err(super::Error::Unsupported(
+                    "parent windows of another windowing system",
+                ));
+            }
+        };
+
+        // the default visual has no alpha channel
+        let (depth, visual) = if attributes.transparent {
+            argb_visual(&xconn).ok_or(super::Error::Unsupported(
+                "transparency without ARGB visual",
+            ))?
+        } else {
+            (screen.root_depth, screen.root_visual)
+        };
+
+        let mut aux = CreateWindowAux::new().event_mask(
+            EventMask::EXPOSURE
+                | EventMask::KEY_PRESS
+                | EventMask::KEY_RELEASE
+                | EventMask::BUTTON_PRESS
+                | EventMask::BUTTON_RELEASE
+                | EventMask::POINTER_MOTION
+                | EventMask::FOCUS_CHANGE
+                | EventMask::ENTER_WINDOW
+                | EventMask::LEAVE_WINDOW
+                | EventMask::VISIBILITY_CHANGE
+                | EventMask::PROPERTY_CHANGE
+                | EventMask::STRUCTURE_NOTIFY,
+        );
+
+        // windows of another depth than their parent need their own colormap and border
+        let colormap = if depth == screen.root_depth {
+            aux = aux.background_pixel(screen.black_pixel);
+            None
+        } else {
+            let colormap = conn.generate_id()?;
+            conn.create_colormap(ColormapAlloc::NONE, colormap, screen.root, visual)?;
+
+            aux = aux.background_pixel(0).border_pixel(0).colormap(colormap);
+            Some(colormap)
+        };
+
+        let (x, y) = attributes.position.unwrap_or_default();
+
+        conn.create_window(
+            depth,
+            window,
+            parent,
+            x,
+            y,
+            attributes.size.x,
+            attributes.size.y,
+            0,
+            WindowClass::INPUT_OUTPUT,
+            visual,
+            &aux,
+        )?;
+
+        conn.change_property32(
+            PropMode::REPLACE,
+            window,
+            xconn.atoms.WM_PROTOCOLS,
+            AtomEnum::ATOM,
+            &[xconn.atoms.WM_DELETE_WINDOW],
+        )?;
+
+        write_title(&xconn, window, &attributes.title)?;
+
+        let size_hints = size_hints(attributes);
+        size_hints.set_normal_hints(&**conn, window)?;
+
+        if let Some(app_id) = &attributes.app_id {
+            // the instance and class name, each terminated by a NUL byte
+            let class = format!("{app_id}\0{app_id}\0");
+
+            conn.change_property8(
+                PropMode::REPLACE,
+                window,
+                AtomEnum::WM_CLASS,
+                AtomEnum::STRING,
+                class.as_bytes(),
+            )?;
+        }
+
+        if !attributes.decorations || !attributes.resizable {
+            // flags, functions, decorations, input mode and status of the Motif hints
+            let mut hints = [0; 5];
+
+            if !attributes.resizable {
+                // with MWM_FUNC_ALL, the listed functions are the ones removed
+                hints[0] |= MWM_HINTS_FUNCTIONS;
+                hints[1] = MWM_FUNC_ALL | MWM_FUNC_RESIZE | MWM_FUNC_MAXIMIZE;
+            }
+            if !attributes.decorations {
+                hints[0] |= MWM_HINTS_DECORATIONS;
+            }
+
+            conn.change_property32(
+                PropMode::REPLACE,
+                window,
+                xconn.atoms._MOTIF_WM_HINTS,
+                xconn.atoms._MOTIF_WM_HINTS,
+                &hints,
+            )?;
+        }
+
+        // the window manager reads the initial state, once the window is mapped
+        let mut states = Vec::new();
+        if attributes.maximized {
+            states.push(xconn.atoms._NET_WM_STATE_MAXIMIZED_VERT);
+            states.push(xconn.atoms._NET_WM_STATE_MAXIMIZED_HORZ);
+        }
+        if attributes.always_on_top {
+            states.push(xconn.atoms._NET_WM_STATE_ABOVE);
+        }
+        if !states.is_empty() {
+            conn.change_property32(
+                PropMode::REPLACE,
+                window,
+                xconn.atoms._NET_WM_STATE,
+                AtomEnum::ATOM,
+                &states,
+            )?;
+        }
+
+        xconn.select_xinput_events(window)?;
+        xconn.register(window, eventsys);
+
+        let window = Self {
+            xconn,
+            window,
+            depth,
+            visual,
+            colormap,
+            presenter: None,
+            cursor: Cell::new(x11rb::NONE),
+            custom_cursor: Cell::new(None),
+            cursor_visible: Cell::new(true),
+            title: RefCell::new(attributes.title.clone()),
+            size_hints: RefCell::new(size_hints),
+            resizable: attributes.resizable,
+            fullscreen: RefCell::new(None),
+            saved_mode: RefCell::new(None),
+        };
+
+        if attributes.fullscreen.is_some() {
+            window.set_fullscreen(attributes.fullscreen.clone())?;
+        }
+
+        if attributes.visible {
+            window.show()?;
+        }
+
+        Ok(window)
+    }
+
+    pub fn show(&self) -> Result<(), super::Error> {
+        self.xconn.conn.map_window(self.window)?;
+        self.xconn.conn.flush()?;
+        Ok(())
+    }
+
+    pub fn set_visible(&self, visible: bool) -> Result<(), super::Error> {
+        if visible {
+            return self.show();
+        }
+
+        let conn = &self.xconn.conn;
+        let root = self.xconn.screen().root;
+
+        conn.unmap_window(self.window)?;
+
+        // ICCCM asks for a synthetic UnmapNotify, which also withdraws minimized windows
+        let event = UnmapNotifyEvent {
+            response_type: UNMAP_NOTIFY_EVENT,
+            sequence: 0,
+            event: root,
+            window: self.window,
+            from_configure: false,
+        };
+
+        conn.send_event(
+            false,
+            root,
+            EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY,
+            event,
+        )?;
+        conn.flush()?;
+
+        Ok(())
+    }
+
+    /// Mapping a minimized window restores it.
+    pub fn set_minimized(&self, minimized: bool) -> Result<(), super::Error> {
+        if !minimized {
+            return self.show();
+        }
+
+        self.xconn.send_wm_message(
+            self.window,
+            self.xconn.atoms.WM_CHANGE_STATE,
+            [ICONIC_STATE, 0, 0, 0, 0],
+        )?;
+        self.xconn.conn.flush()?;
+
+        Ok(())
+    }
+
+    pub fn set_maximized(&self, maximized: bool) -> Result<(), super::Error> {
+        let atoms = &self.xconn.atoms;
+
+        self.change_state(
+            &[
+                atoms._NET_WM_STATE_MAXIMIZED_VERT,
+                atoms._NET_WM_STATE_MAXIMIZED_HORZ,
+            ],
+            maximized,
+        )?;
+        self.xconn.conn.flush()?;
+
+        Ok(())
+    }
+
+    pub fn is_maximized(&self) -> bool {
+        let atoms = &self.xconn.atoms;
+
+        let states = self.states().unwrap_or_else(|err| {
+            log::warn!("{err}");
+            Vec::new()
+        });
+
+        states.contains(&atoms._NET_WM_STATE_MAXIMIZED_VERT)
+            && states.contains(&atoms._NET_WM_STATE_MAXIMIZED_HORZ)
+    }
+
+    pub fn focus_window(&self) -> Result<(), super::Error> {
+        // from a normal application, without a timestamp or the active window
+        self.xconn.send_wm_message(
+            self.window,
+            self.xconn.atoms._NET_ACTIVE_WINDOW,
+            [1, x11rb::CURRENT_TIME, 0, 0, 0],
+        )?;
+        self.xconn.conn.flush()?;
+
+        Ok(())
+    }
+
+    pub fn set_window_level(&self, level: WindowLevel) -> Result<(), super::Error> {
+        let atoms = &self.xconn.atoms;
+
+        self.change_state(
+            &[atoms._NET_WM_STATE_ABOVE],
+            level == WindowLevel::AlwaysOnTop,
+        )?;
+        self.change_state(
+            &[atoms._NET_WM_STATE_BELOW],
+            level == WindowLevel::AlwaysOnBottom,
+        )?;
+        self.xconn.conn.flush()?;
+
+        Ok(())
+    }
+
+    /// Critical attention also sets the urgency hint, which most window managers flash.
+    pub fn request_user_attention(
+        &self,
+        attention: Option<UserAttentionType>,
+    ) -> Result<(), super::Error> {
+        let conn = &self.xconn.conn;
+
+        let mut hints = WmHints::get(&**conn, self.window)?
+            .reply()?
+            .unwrap_or_default();
+        hints.urgent = attention == Some(UserAttentionType::Critical);
+        hints.set(&**conn, self.window)?;
+
+        self.change_state(
+            &[self.xconn.atoms._NET_WM_STATE_DEMANDS_ATTENTION],
+            attention.is_some(),
+        )?;
+        conn.flush()?;
+
+        Ok(())
+    }
+
+    pub fn set_title(&self, title: &str) -> Result<(), super::Error> {
+        write_title(&self.xconn, self.window, title)?;
+        title.clone_into(&mut self.title.borrow_mut());
+
+        self.xconn.conn.flush()?;
+
+        Ok(())
+    }
+
+    pub fn title(&self) -> String {
+        self.title.borrow().clone()
+    }
+
+    pub fn pump_messages(&self) -> Result<(), super::Error> {
+        self.xconn.pump_messages()
+    }
+
+    pub fn wait_events(&self, timeout: Option<Duration>) -> Result<(), super::Error> {
+        self.xconn.wait_events(timeout)
+    }
+
+    pub fn create_proxy(&self) -> EventLoopProxy {
+        self.xconn.create_proxy(self.window)
+    }
+
+    pub fn inner_size(&self) -> crate::Rect {
+        self.geometry().unwrap_or_else(|err| {
+            log::warn!("{err}");
+            Rect::new(0, 0)
+        })
+    }
+
+    /// Returns the size of the window, without the frame.
+    fn geometry(&self) -> Result<Rect, super::Error> {
+        let geometry = self.xconn.conn.get_geometry(self.window)?.reply()?;
+
+        Ok(Rect::new(geometry.width, geometry.height))
+    }
+
+    pub fn set_inner_size(&self, size: Rect) -> Result<(), super::Error> {
+        // the window manager keeps a window, that is not resizable, at its minimum size
+        if !self.resizable {
+            self.update_size_hints(|hints| {
+                let size = (i32::from(size.x), i32::from(size.y));
+
+                hints.min_size = Some(size);
+                hints.max_size = Some(size);
+            })?;
+        }
+
+        self.xconn.conn.configure_window(
+            self.window,
+            &ConfigureWindowAux::new()
+                .width(u32::from(size.x))
+                .height(u32::from(size.y)),
+        )?;
+        self.xconn.conn.flush()?;
+
+        Ok(())
+    }
+
+    pub fn outer_position(&self) -> Result<PhysicalPosition, super::Error> {
+        let origin = self
+            .xconn
+            .conn
+            .translate_coordinates(self.window, self.xconn.screen().root, 0, 0)?
+            .reply()?;
+
+        let [left, _, top, _] = self.frame_extents()?;
+
+        Ok(PhysicalPosition::new(
+            i32::from(origin.dst_x) - left,
+            i32::from(origin.dst_y) - top,
+        ))
+    }
+
+    /// Moves the frame, as the window has the default `NorthWest` gravity.
+    pub fn set_outer_position(&self, position: PhysicalPosition) -> Result<(), super::Error> {
+        // a window, that is not mapped yet, is placed by the hints
+        self.update_size_hints(|hints| {
+            hints.position = Some((
+                WmSizeHintsSpecification::UserSpecified,
+                position.x,
+                position.y,
+            ));
+        })?;
+
+        self.xconn.conn.configure_window(
+            self.window,
+            &ConfigureWindowAux::new().x(position.x).y(position.y),
+        )?;
+        self.xconn.conn.flush()?;
+
+        Ok(())
+    }
+
+    pub fn outer_size(&self) -> Rect {
+        let size = self.geometry().unwrap_or_else(|err| {
+            log::warn!("{err}");
+            Rect::new(0, 0)
+        });
+
+        let [left, right, top, bottom] = self.frame_extents().unwrap_or_else(|err| {
+            log::warn!("{err}");
+            [0; 4]
+        });
+
+        let grow =
+            |size: u16, a: i32, b: i32| u16::try_from(i32::from(size) + a + b).unwrap_or(u16::MAX);
+
+        Rect::new(grow(size.x, left, right), grow(size.y, top, bottom))
+    }
+
+    /// Returns the left, right, top and bottom border of the frame.
+    ///
+    /// Window managers, that don't support `_NET_FRAME_EXTENTS`, have no borders.
+    fn frame_extents(&self) -> Result<[i32; 4], super::Error> {
+        let reply = self
+            .xconn
+            .conn
+            .get_property(
+                false,
+                self.window,
+                self.xconn.atoms._NET_FRAME_EXTENTS,
+                AtomEnum::CARDINAL,
+                0,
+                4,
+            )?
+            .reply()?;
+
+        let mut extents = [0; 4];
+
+        if let Some(values) = reply.value32() {
+            for (extent, value) in extents.iter_mut().zip(values) {
+                *extent = i32::try_from(value).unwrap_or(0);
+            }
+        }
+
+        Ok(extents)
+    }
+
+    pub fn set_min_inner_size(&self, size: Option<Rect>) -> Result<(), super::Error> {
+        self.update_size_hints(|hints| {
+            hints.min_size = size.map(|size| (i32::from(size.x), i32::from(size.y)));
+        })
+    }
+
+    pub fn set_max_inner_size(&self, size: Option<Rect>) -> Result<(), super::Error> {
+        self.update_size_hints(|hints| {
+            hints.max_size = size.map(|size| (i32::from(size.x), i32::from(size.y)));
+        })
+    }
+
+    pub fn set_resize_increments(&self, increments: Option<Rect>) -> Result<(), super::Error> {
+        self.update_size_hints(|hints| {
+            hints.size_increment =
+                increments.map(|increments| (i32::from(increments.x), i32::from(increments.y)));
+        })
+    }
+
+    pub fn set_aspect_ratio(&self, ratio: Option<(u32, u32)>) -> Result<(), super::Error> {
+        self.update_size_hints(|hints| {
+            // the same minimum and maximum ratio
+            hints.aspect = ratio.map(|(width, height)| {
+                let ratio = AspectRatio::new(
+                    i32::try_from(width).unwrap_or(i32::MAX),
+                    i32::try_from(height).unwrap_or(i32::MAX),
+                );
+
+                (ratio, ratio)
+            });
+        })
+    }
+
+    fn update_size_hints(&self, update: impl FnOnce(&mut WmSizeHints)) -> Result<(), super::Error> {
+        let mut hints = self.size_hints.borrow_mut();
+        update(&mut hints);
+
+        hints.set_normal_hints(&*self.xconn.conn, self.window)?;
+        self.xconn.conn.flush()?;
+
+        Ok(())
+    }
+
+    pub fn scale_factor(&self) -> f64 {
+        self.xconn.scale_factor(self.window)
+    }
+
+    pub fn request_redraw(&self) {
+        self.xconn.request_redraw(self.window);
+    }
+
+    pub fn present_buffer(
+        &mut self,
+        buffer: &[u32],
+        width: u16,
+        height: u16,
+        damage: &[DamageRect],
+    ) -> Result<(), super::Error> {
+        let presenter = match &mut self.presenter {
+            Some(presenter) => presenter,
+            None => self.presenter.insert(Presenter::new(
+                &self.xconn,
+                self.window,
+                self.depth,
+                self.visual,
+            )?),
+        };
+
+        presenter.present(self.window, buffer, width, height, damage)
+    }
+
+    pub fn capture(&self) -> Result<crate::Image, super::Error> {
+        capture::capture(&self.xconn, self.window)
+    }
+
+    pub fn create_input_injector(&self) -> Result<InputInjector, super::Error> {
+        InputInjector::new(Rc::clone(&self.xconn), self.window)
+    }
+
+    pub fn set_raw_mouse_motion(&self, enabled: bool) -> Result<(), super::Error> {
+        self.xconn.set_raw_mouse_motion(self.window, enabled)
+    }
+
+    pub fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), super::Error> {
+        self.xconn
+            .set_cursor_grab(self.window, mode, self.geometry()?)
+    }
+
+    pub fn set_cursor_visible(&self, visible: bool) -> Result<(), super::Error> {
+        self.cursor_visible.set(visible);
+        self.apply_cursor()
+    }
+
+    pub fn set_cursor(&self, icon: CursorIcon) -> Result<(), super::Error> {
+        let cursor = self.xconn.load_cursor(icon)?;
+        self.replace_cursor(cursor, None)
+    }
+
+    pub fn set_custom_cursor(
+        &self,
+        rgba: &[u8],
+        width: u16,
+        height: u16,
+        hotspot: (u16, u16),
+    ) -> Result<(), super::Error> {
+        let cursor = cursor::create_custom_cursor(
+            &self.xconn.conn,
+            self.xconn.screen().root,
+            rgba,
+            width,
+            height,
+            hotspot,
+        )?;
+
+        self.replace_cursor(cursor, Some(cursor))
+    }
+
+    pub fn set_icon(&self, icon: Option<&Icon>) -> Result<(), super::Error> {
+        let conn = &self.xconn.conn;
+
+        let Some(icon) = icon else {
+            conn.delete_property(self.window, self.xconn.atoms._NET_WM_ICON)?;
+            conn.flush()?;
+
+            return Ok(());
+        };
+
+        // every size is its width and height, followed by ARGB pixels
+        let mut data = Vec::new();
+        for image in icon.images() {
+            data.extend([u32::from(image.width), u32::from(image.height)]);
+            data.extend(
+                image
+                    .data
+                    .chunks_exact(4)
+                    .map(|pixel| u32::from_be_bytes([pixel[3], pixel[0], pixel[1], pixel[2]])),
+            );
+        }
+
+        conn.change_property32(
+            PropMode::REPLACE,
+            self.window,
+            self.xconn.atoms._NET_WM_ICON,
+            AtomEnum::CARDINAL,
+            &data,
+        )?;
+        conn.flush()?;
+
+        Ok(())
+    }
+
+    pub fn set_fullscreen(&self, fullscreen: Option<Fullscreen>) -> Result<(), super::Error> {
+        if let Some(saved) = self.saved_mode.take() {
+            monitor::restore_video_mode(&self.xconn, &saved)?;
+        }
+
+        let monitor = match &fullscreen {
+            None => None,
+            Some(Fullscreen::Borderless(Some(monitor))) => Some(monitor.id),
+            Some(Fullscreen::Borderless(None)) => self.current_monitor().map(|monitor| monitor.id),
+            Some(Fullscreen::Exclusive(video_mode)) => {
+                let saved = monitor::set_video_mode(&self.xconn, video_mode)?;
+                self.saved_mode.replace(Some(saved));
+
+                Some(video_mode.monitor)
+            }
+        };
+
+        let index = monitor.and_then(|id| self.xconn.xinerama_index(id));
+
+        self.set_wm_fullscreen(fullscreen.is_some(), index)?;
+        self.fullscreen.replace(fullscreen);
+
+        Ok(())
+    }
+
+    pub fn fullscreen(&self) -> Option<Fullscreen> {
+        self.fullscreen.borrow().clone()
+    }
+
+    fn set_wm_fullscreen(&self, enabled: bool, monitor: Option<u32>) -> Result<(), super::Error> {
+        let conn = &self.xconn.conn;
+        let atoms = &self.xconn.atoms;
+
+        if let Some(index) = monitor {
+            // the top, bottom, left and right edge all on the same monitor
+            self.xconn.send_wm_message(
+                self.window,
+                atoms._NET_WM_FULLSCREEN_MONITORS,
+                [index, index, index, index, 1],
+            )?;
+        }
+
+        self.change_state(&[atoms._NET_WM_STATE_FULLSCREEN], enabled)?;
+        conn.flush()?;
+
+        Ok(())
+    }
+
+    /// Asks the window manager to add or remove one or two states, like both maximized states.
+    fn change_state(&self, states: &[u32], enabled: bool) -> Result<(), super::Error> {
+        let first = states.first().copied().unwrap_or(x11rb::NONE);
+        let second = states.get(1).copied().unwrap_or(x11rb::NONE);
+
+        // _NET_WM_STATE_REMOVE and _NET_WM_STATE_ADD, from a normal application
+        self.xconn.send_wm_message(
+            self.window,
+            self.xconn.atoms._NET_WM_STATE,
+            [u32::from(enabled), first, second, 1, 0],
+        )?;
+
+        // the window manager only reads the property when the window is mapped
+        let mapped = self
+            .xconn
+            .conn
+            .get_window_attributes(self.window)?
+            .reply()?
+            .map_state
+            != MapState::UNMAPPED;
+
+        if !mapped {
+            self.change_initial_state(states, enabled)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the atoms of `_NET_WM_STATE`, as set by the window manager.
+    fn states(&self) -> Result<Vec<u32>, super::Error> {
+        let reply = self
+            .xconn
+            .conn
+            .get_property(
+                false,
+                self.window,
+                self.xconn.atoms._NET_WM_STATE,
+                AtomEnum::ATOM,
+                0,
+                u32::MAX,
+            )?
+            .reply()?;
+
+        Ok(reply.value32().map(Iterator::collect).unwrap_or_default())
+    }
+
+    /// Adds or removes states from `_NET_WM_STATE`, while keeping all others.
+    ///
+    /// Only has an effect, while the window is not mapped.
+    fn change_initial_state(&self, changed: &[u32], enabled: bool) -> Result<(), super::Error> {
+        let conn = &self.xconn.conn;
+        let atoms = &self.xconn.atoms;
+
+        let mut states = self.states()?;
+        states.retain(|state| !changed.contains(state));
+
+        if enabled {
+            states.extend_from_slice(changed);
+        }
+
+        conn.change_property32(
+            PropMode::REPLACE,
+            self.window,
+            atoms._NET_WM_STATE,
+            AtomEnum::ATOM,
+            &states,
+        )?;
+
+        Ok(())
+    }
+
+    /// Returns the monitor, that contains the center of the window.
+    pub fn current_monitor(&self) -> Option<MonitorHandle> {
+        let center = || -> Result<_, super::Error> {
+            let size = self.geometry()?;
+
+            Ok(self
+                .xconn
+                .conn
+                .translate_coordinates(
+                    self.window,
+                    self.xconn.screen().root,
+                    i16::try_from(size.x / 2).unwrap_or(i16::MAX),
+                    i16::try_from(size.y / 2).unwrap_or(i16::MAX),
+                )?
+                .reply()?)
+        };
+
+        match center() {
+            Ok(reply) => monitor::monitor_at(
+                self.xconn.available_monitors(),
+                i32::from(reply.dst_x),
+                i32::from(reply.dst_y),
+            ),
+            Err(err) => {
+                log::warn!("{err}");
+                None
+            }
+        }
+    }
+
+    pub fn available_monitors(&self) -> Vec<MonitorHandle> {
+        self.xconn.available_monitors()
+    }
+
+    pub fn primary_monitor(&self) -> Option<MonitorHandle> {
+        self.xconn.primary_monitor()
+    }
+
+    fn replace_cursor(&self, cursor: u32, custom: Option<u32>) -> Result<(), super::Error> {
+        self.cursor.set(cursor);
+        let res = self.apply_cursor();
+
+        // the window keeps its own reference to the cursor
+        if let Some(previous) = self.custom_cursor.replace(custom) {
+            self.xconn.conn.free_cursor(previous)?;
+        }
+
+        res
+    }
+
+    fn apply_cursor(&self) -> Result<(), super::Error> {
+        let cursor = if self.cursor_visible.get() {
+            self.cursor.get()
+        } else {
+            self.xconn.blank_cursor()?
+        };
+
+        self.xconn.conn.change_window_attributes(
+            self.window,
+            &ChangeWindowAttributesAux::new().cursor(cursor),
+        )?;
+        self.xconn.conn.flush()?;
+
+        Ok(())
+    }
+
+    pub fn id(&self) -> WindowId {
+        WindowId::from_raw(u64::from(self.window))
+    }
+
+    pub fn raw_handle(&self) -> crate::RawWindowHandle {
+        let raw_conn = self.xconn.conn.get_raw_xcb_connection();
+
+        crate::RawWindowHandle::Xcb {
+            connection: raw_conn,
+            window: self.window,
+        }
+    }
+}
+
+/// The `WM_CHANGE_STATE` of a minimized window.
+const ICONIC_STATE: u32 = 3;
+
+// the flags and functions of `_MOTIF_WM_HINTS`
+const MWM_HINTS_FUNCTIONS: u32 = 1 << 0;
+const MWM_HINTS_DECORATIONS: u32 = 1 << 1;
+const MWM_FUNC_ALL: u32 = 1 << 0;
+const MWM_FUNC_RESIZE: u32 = 1 << 1;
+const MWM_FUNC_MAXIMIZE: u32 = 1 << 4;
+
+/// Returns the depth and id of a visual with an alpha channel.
+fn argb_visual(xconn: &XConnection) -> Option<(u8, u32)> {
+    xconn
+        .screen()
+        .allowed_depths
+        .iter()
+        .filter(|depth| depth.depth == 32)
+        .flat_map(|depth| &depth.visuals)
+        .find(|visual| visual.class == VisualClass::TRUE_COLOR)
+        .map(|visual| (32, visual.visual_id))
+}
+
+/// The size hints of a new window, for the window manager.
+fn size_hints(attributes: &WindowAttributes) -> WmSizeHints {
+    let mut hints = WmSizeHints::new();
+
+    let size = (i32::from(attributes.size.x), i32::from(attributes.size.y));
+
+    hints.position = attributes.position.map(|(x, y)| {
+        (
+            WmSizeHintsSpecification::UserSpecified,
+            i32::from(x),
+            i32::from(y),
+        )
+    });
+    hints.size = Some((WmSizeHintsSpecification::UserSpecified, size.0, size.1));
+    hints.min_size = attributes
+        .min_size
+        .map(|size| (i32::from(size.x), i32::from(size.y)));
+    hints.max_size = attributes
+        .max_size
+        .map(|size| (i32::from(size.x), i32::from(size.y)));
+
+    hints
+}
+
+/// Writes the title as UTF-8 for EWMH window managers, and as Latin-1 for all others.
+fn write_title(xconn: &XConnection, window: u32, title: &str) -> Result<(), super::Error> {
+    let conn = &xconn.conn;
+
+    for property in [xconn.atoms._NET_WM_NAME, xconn.atoms._NET_WM_ICON_NAME] {
+        conn.change_property8(
+            PropMode::REPLACE,
+            window,
+            property,
+            xconn.atoms.UTF8_STRING,
+            title.as_bytes(),
+        )?;
+    }
+
+    // characters outside of Latin-1 can't be represented
+    let latin1: Vec<u8> = title
+        .chars()
+        .map(|c| u8::try_from(c).unwrap_or(b'?'))
+        .collect();
+
+    for property in [AtomEnum::WM_NAME, AtomEnum::WM_ICON_NAME] {
+        conn.change_property8(
+            PropMode::REPLACE,
+            window,
+            property,
+            AtomEnum::STRING,
+            &latin1,
+        )?;
+    }
+
+    Ok(())
+}
+
+impl Drop for Window {
+    fn drop(&mut self) {
+        // release graphic resources before the window is gone
+        self.presenter = None;
+
+        self.xconn.unregister(self.window);
+        _ = self.xconn.conn.destroy_window(self.window);
+
+        if let Some(saved) = self.saved_mode.take() {
+            _ = monitor::restore_video_mode(&self.xconn, &saved);
+        }
+
+        if let Some(cursor) = self.custom_cursor.take() {
+            _ = self.xconn.conn.free_cursor(cursor);
+        }
+        if let Some(colormap) = self.colormap {
+            _ = self.xconn.conn.free_colormap(colormap);
+        }
+        _ = self.xconn.conn.flush();
+    }
+}
@@ -0,0 +1,316 @@
+use x11rb::{
+    CURRENT_TIME,
+    connection::RequestConnection as _,
+    protocol::{
+        randr::{self, ConnectionExt as _, ModeFlag, ModeInfo, Rotation, SetConfig},
+        xproto::{AtomEnum, ConnectionExt as _},
+    },
+    xcb_ffi::XCBConnection,
+};
+
+use crate::{
+    Error, Rect,
+    monitor::{MonitorHandle, VideoMode},
+};
+
+use super::connection::XConnection;
+
+/// Returns `true`, if the server supports `RandR` 1.5, which added monitors.
+pub fn has_randr(conn: &XCBConnection) -> Result<bool, Error> {
+    if conn
+        .extension_information(randr::X11_EXTENSION_NAME)?
+        .is_none()
+    {
+        return Ok(false);
+    }
+
+    let version = conn.randr_query_version(1, 5)?.reply()?;
+
+    Ok((version.major_version, version.minor_version) >= (1, 5))
+}
+
+/// An active monitor, as reported by `RandR`.
+#[derive(Debug)]
+pub struct Monitor {
+    pub handle: MonitorHandle,
+    pub primary: bool,
+    /// The index, that window managers refer to the monitor by.
+    pub xinerama_index: u32,
+}
+
+/// Queries all active monitors, that have an output.
+///
+/// Monitors without outputs are skipped, so the position in the list is not
+/// necessarily the Xinerama index.
+pub fn query_monitors(xconn: &XConnection) -> Result<Vec<Monitor>, Error> {
+    if !xconn.has_randr() {
+        return Err(Error::Unsupported("monitors without RandR 1.5"));
+    }
+
+    let conn = &*xconn.conn;
+    let screen = xconn.screen();
+
+    let resources = conn
+        .randr_get_screen_resources_current(screen.root)?
+        .reply()?;
+    let reply = conn.randr_get_monitors(screen.root, true)?.reply()?;
+
+    let mut monitors = Vec::with_capacity(reply.monitors.len());
+
+    for (xinerama_index, monitor) in (0..).zip(reply.monitors) {
+        // monitors without outputs are set up by the user and can't change their mode
+        let Some(&output) = monitor.outputs.first() else {
+            continue;
+        };
+
+        let name = conn.get_atom_name(monitor.name)?.reply()?.name;
+        let info = conn
+            .randr_get_output_info(output, resources.config_timestamp)?
+            .reply()?;
+
+        let video_modes = info
+            .modes
+            .iter()
+            .filter_map(|id| resources.modes.iter().find(|mode| mode.id == *id))
+            .map(|mode| VideoMode {
+                monitor: output,
+                native: mode.id,
+                size: Rect::new(mode.width, mode.height),
+                bit_depth: u16::from(screen.root_depth),
+                refresh_rate_millihertz: refresh_rate(mode),
+            })
+            .collect();
+
+        let refresh_rate_millihertz = if info.crtc == x11rb::NONE {
+            None
+        } else {
+            let crtc = conn
+                .randr_get_crtc_info(info.crtc, resources.config_timestamp)?
+                .reply()?;
+
+            resources
+                .modes
+                .iter()
+                .find(|mode| mode.id == crtc.mode)
+                .map(refresh_rate)
+        };
+
+        let handle = MonitorHandle {
+            id: output,
+            name: String::from_utf8_lossy(&name).into_owned(),
+            position: (i32::from(monitor.x), i32::from(monitor.y)),
+            size: Rect::new(monitor.width, monitor.height),
+            physical_size_mm: (monitor.width_in_millimeters, monitor.height_in_millimeters),
+            scale_factor: xconn.xft_dpi().map_or_else(
+                || scale_factor(monitor.width, monitor.width_in_millimeters),
+                |dpi| dpi / 96.0,
+            ),
+            refresh_rate_millihertz,
+            video_modes,
+        };
+
+        monitors.push(Monitor {
+            handle,
+            primary: monitor.primary,
+            xinerama_index,
+        });
+    }
+
+    Ok(monitors)
+}
+
+/// Derives a scale factor from the pixel density of a monitor, in steps of a quarter.
+///
+/// Monitors below 96 DPI, or without a physical size, are not scaled.
+fn scale_factor(pixels: u16, millimeters: u32) -> f64 {
+    if millimeters == 0 {
+        return 1.0;
+    }
+
+    let dpi = f64::from(pixels) * 25.4 / f64::from(millimeters);
+
+    ((dpi / 96.0) * 4.0).round().max(4.0) / 4.0
+}
+
+/// Returns the monitor, that contains the point, or the first one.
+pub fn monitor_at(mut monitors: Vec<MonitorHandle>, x: i32, y: i32) -> Option<MonitorHandle> {
+    let index = monitors
+        .iter()
+        .position(|monitor| {
+            let (left, top) = monitor.position;
+
+            (left..left + i32::from(monitor.size.x)).contains(&x)
+                && (top..top + i32::from(monitor.size.y)).contains(&y)
+        })
+        .unwrap_or(0);
+
+    (index < monitors.len()).then(|| monitors.swap_remove(index))
+}
+
+/// Reads `Xft.dpi` from the resources of the screen, which desktop environments set
+/// to scale every application.
+pub fn xft_dpi(conn: &XCBConnection, root: u32) -> Result<Option<f64>, Error> {
+    let reply = conn
+        .get_property(
+            false,
+            root,
+            AtomEnum::RESOURCE_MANAGER,
+            AtomEnum::STRING,
+            0,
+            u32::MAX,
+        )?
+        .reply()?;
+
+    let resources = String::from_utf8_lossy(&reply.value);
+
+    Ok(resources
+        .lines()
+        .find_map(|line| line.strip_prefix("Xft.dpi:"))
+        .and_then(|dpi| dpi.trim().parse::<f64>().ok())
+        .filter(|dpi| *dpi > 0.0))
+}
+
+fn refresh_rate(mode: &ModeInfo) -> u32 {
+    let mut lines = u64::from(mode.vtotal);
+
+    if mode.mode_flags.contains(ModeFlag::DOUBLE_SCAN) {
+        lines *= 2;
+    }
+    if mode.mode_flags.contains(ModeFlag::INTERLACE) {
+        lines /= 2;
+    }
+
+    let pixels = u64::from(mode.htotal) * lines;
+    if pixels == 0 {
+        return 0;
+    }
+
+    u32::try_from(u64::from(mode.dot_clock) * 1000 / pixels).unwrap_or(u32::MAX)
+}
+
+/// The configuration of a CRTC, before its mode was switched.
+#[derive(Debug)]
+pub struct SavedMode {
+    crtc: u32,
+    mode: u32,
+    position: (i16, i16),
+    rotation: Rotation,
+    outputs: Vec<u32>,
+}
+
+/// Switches the CRTC of the monitor, that the mode belongs to.
+pub fn set_video_mode(xconn: &XConnection, video_mode: &VideoMode) -> Result<SavedMode, Error> {
+    let conn = &*xconn.conn;
+
+    let resources = conn
+        .randr_get_screen_resources_current(xconn.screen().root)?
+        .reply()?;
+    let output = conn
+        .randr_get_output_info(video_mode.monitor, resources.config_timestamp)?
+        .reply()?;
+
+    if output.crtc == x11rb::NONE {
+        return Err(Error::Unsupported("video modes of disabled monitors"));
+    }
+
+    let crtc = conn
+        .randr_get_crtc_info(output.crtc, resources.config_timestamp)?
+        .reply()?;
+
+    let saved = SavedMode {
+        crtc: output.crtc,
+        mode: crtc.mode,
+        position: (crtc.x, crtc.y),
+        rotation: crtc.rotation,
+        outputs: crtc.outputs,
+    };
+
+    set_crtc_config(
+        conn,
+        saved.crtc,
+        resources.config_timestamp,
+        saved.position,
+        video_mode.native,
+        saved.rotation,
+        &saved.outputs,
+    )?;
+
+    Ok(saved)
+}
+
+/// Switches a CRTC back to the mode it had before `set_video_mode`.
+pub fn restore_video_mode(xconn: &XConnection, saved: &SavedMode) -> Result<(), Error> {
+    let conn = &*xconn.conn;
+
+    let resources = conn
+        .randr_get_screen_resources_current(xconn.screen().root)?
+        .reply()?;
+
+    set_crtc_config(
+        conn,
+        saved.crtc,
+        resources.config_timestamp,
+        saved.position,
+        saved.mode,
+        saved.rotation,
+        &saved.outputs,
+    )
+}
+
+fn set_crtc_config(
+    conn: &XCBConnection,
+    crtc: u32,
+    config_timestamp: u32,
+    (x, y): (i16, i16),
+    mode: u32,
+    rotation: Rotation,
+    outputs: &[u32],
+) -> Result<(), Error> {
+    let status = conn
+        .randr_set_crtc_config(
+            crtc,
+            CURRENT_TIME,
+            config_timestamp,
+            x,
+            y,
+            mode,
+            rotation,
+            outputs,
+        )?
+        .reply()?
+        .status;
+
+    if status != SetConfig::SUCCESS {
+        return Err(Error::VideoMode(status));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_scale_factor(pixels: u16, millimeters: u32, expected: f64) {
+        let actual = scale_factor(pixels, millimeters);
+        assert!(
+            (actual - expected).abs() < f64::EPSILON,
+            "{actual} != {expected}"
+        );
+    }
+
+    #[test]
+    fn scale_factor_in_quarter_steps() {
+        // a 20 inch wide monitor, at 96, 128, 144 and 192 DPI
+        assert_scale_factor(1920, 508, 1.0);
+        assert_scale_factor(2560, 508, 1.25);
+        assert_scale_factor(2880, 508, 1.5);
+        assert_scale_factor(3840, 508, 2.0);
+    }
+
+    #[test]
+    fn scale_factor_of_low_density_or_unknown_size() {
+        assert_scale_factor(1024, 508, 1.0);
+        assert_scale_factor(1920, 0, 1.0);
+    }
+}
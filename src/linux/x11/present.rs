@@ -0,0 +1,351 @@
+use std::{borrow::Cow, ptr::NonNull, sync::Arc};
+
+use x11rb::{
+    connection::{Connection as _, RequestConnection as _},
+    image::{BitsPerPixel, ColorComponent, Image, ImageOrder, PixelLayout, ScanlinePad},
+    protocol::{
+        shm::{self, ConnectionExt as _},
+        xproto::{ConnectionExt as _, CreateGCAux, ImageFormat},
+    },
+    wrapper::ConnectionExt as _,
+    xcb_ffi::XCBConnection,
+};
+
+use crate::{DamageRect, Error};
+
+use super::connection::XConnection;
+
+/// Uploads XRGB8888 buffers to a window.
+///
+/// Uses a MIT-SHM segment when the server supports it, `PutImage` requests otherwise.
+#[derive(Debug)]
+pub struct Presenter {
+    conn: Arc<XCBConnection>,
+    gc: u32,
+    depth: u8,
+    layout: PixelLayout,
+    shm: Option<ShmSegment>,
+    use_shm: bool,
+}
+
+impl Presenter {
+    pub fn new(xconn: &XConnection, window: u32, depth: u8, visual: u32) -> Result<Self, Error> {
+        let conn = Arc::clone(&xconn.conn);
+
+        let layout = xconn
+            .visual_type(visual)
+            .ok_or(Error::Unsupported("window visual"))
+            .and_then(|visual| Ok(PixelLayout::from_visual_type(visual)?))?;
+
+        let gc = conn.generate_id()?;
+        conn.create_gc(gc, window, &CreateGCAux::new().graphics_exposures(0))?;
+
+        // shared memory can only be used, if the buffer is already in the servers native format
+        let use_shm = conn
+            .extension_information(shm::X11_EXTENSION_NAME)?
+            .is_some()
+            && layout == xrgb_layout()
+            && is_native_xrgb(&conn, depth);
+
+        Ok(Self {
+            conn,
+            gc,
+            depth,
+            layout,
+            shm: None,
+            use_shm,
+        })
+    }
+
+    pub fn present(
+        &mut self,
+        window: u32,
+        buffer: &[u32],
+        width: u16,
+        height: u16,
+        damage: &[DamageRect],
+    ) -> Result<(), Error> {
+        let expected = usize::from(width) * usize::from(height);
+        if buffer.len() != expected {
+            return Err(Error::BufferSize {
+                expected,
+                actual: buffer.len(),
+            });
+        }
+
+        // SAFETY:
+        // u8 has no alignment requirements and the byte length is exactly the size of the u32 slice.
+        let bytes = unsafe {
+            std::slice::from_raw_parts(buffer.as_ptr().cast::<u8>(), std::mem::size_of_val(buffer))
+        };
+
+        let damage = damage
+            .iter()
+            .filter_map(|rect| rect.clamp(width, height))
+            .collect::<Vec<_>>();
+
+        if self.use_shm {
+            match self.present_shm(window, bytes, width, height, &damage) {
+                Ok(true) => return Ok(()),
+                Ok(false) => {
+                    log::warn!("MIT-SHM is not usable, falling back to PutImage");
+                    self.use_shm = false;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        self.present_put_image(window, bytes, width, &damage)
+    }
+
+    /// Returns `false` if no shared memory segment could be attached.
+    fn present_shm(
+        &mut self,
+        window: u32,
+        bytes: &[u8],
+        width: u16,
+        height: u16,
+        damage: &[DamageRect],
+    ) -> Result<bool, Error> {
+        if self.shm.as_ref().is_none_or(|shm| shm.size < bytes.len()) {
+            // detach the old segment first
+            if let Some(shm) = self.shm.take() {
+                shm.destroy(&self.conn);
+            }
+
+            let Some(shm) = ShmSegment::new(&self.conn, bytes.len())? else {
+                return Ok(false);
+            };
+
+            self.shm = Some(shm);
+        }
+
+        let Some(shm) = &self.shm else {
+            return Ok(false);
+        };
+
+        // SAFETY:
+        // The segment is at least bytes.len() long and the server finished reading from it,
+        // because every upload waits for the server to process it.
+        unsafe {
+            std::ptr::copy_nonoverlapping(bytes.as_ptr(), shm.addr.as_ptr(), bytes.len());
+        };
+
+        for rect in damage {
+            #[allow(clippy::cast_possible_wrap)]
+            self.conn.shm_put_image(
+                window,
+                self.gc,
+                width,
+                height,
+                rect.x,
+                rect.y,
+                rect.width,
+                rect.height,
+                rect.x as i16,
+                rect.y as i16,
+                self.depth,
+                ImageFormat::Z_PIXMAP.into(),
+                false,
+                shm.seg,
+                0,
+            )?;
+        }
+
+        // the server reads the segment asynchronously, wait until it is done before it gets overwritten
+        self.conn.sync()?;
+
+        Ok(true)
+    }
+
+    fn present_put_image(
+        &self,
+        window: u32,
+        bytes: &[u8],
+        width: u16,
+        damage: &[DamageRect],
+    ) -> Result<(), Error> {
+        let stride = usize::from(width) * 4;
+
+        for rect in damage {
+            let data = if rect.width == width {
+                let start = usize::from(rect.y) * stride;
+                let end = start + usize::from(rect.height) * stride;
+
+                Cow::Borrowed(&bytes[start..end])
+            } else {
+                let mut data =
+                    Vec::with_capacity(usize::from(rect.width) * usize::from(rect.height) * 4);
+
+                for row in rect.y..rect.y + rect.height {
+                    let start = usize::from(row) * stride + usize::from(rect.x) * 4;
+                    let end = start + usize::from(rect.width) * 4;
+
+                    data.extend_from_slice(&bytes[start..end]);
+                }
+
+                Cow::Owned(data)
+            };
+
+            let image = Image::new(
+                rect.width,
+                rect.height,
+                ScanlinePad::Pad32,
+                self.depth,
+                BitsPerPixel::B32,
+                native_order(),
+                data,
+            )?;
+
+            // converts into the servers pixel format, if necessary
+            let image = if self.layout == xrgb_layout() {
+                image.native(self.conn.setup())?
+            } else {
+                Cow::Owned(self.convert(&image)?)
+            };
+
+            #[allow(clippy::cast_possible_wrap)]
+            image.put(&*self.conn, window, self.gc, rect.x as i16, rect.y as i16)?;
+        }
+
+        self.conn.flush()?;
+
+        Ok(())
+    }
+
+    /// Converts XRGB pixels into the layout of the visual.
+    ///
+    /// Unlike `Image::reencode`, this keeps the depth of the window, which is 32 instead of
+    /// the 24 bits of the color channels for transparent windows.
+    fn convert(&self, image: &Image<'_>) -> Result<Image<'static>, Error> {
+        let mut converted =
+            Image::allocate_native(image.width(), image.height(), self.depth, self.conn.setup())?;
+
+        // the alpha channel takes the bits, that no color channel uses
+        let colors = self.layout.encode((u16::MAX, u16::MAX, u16::MAX));
+        let keep_alpha = self.depth == 32 && colors & 0xff00_0000 == 0;
+
+        for y in 0..image.height() {
+            for x in 0..image.width() {
+                let pixel = image.get_pixel(x, y);
+                let mut converted_pixel = self.layout.encode(xrgb_layout().decode(pixel));
+
+                if keep_alpha {
+                    converted_pixel |= pixel & 0xff00_0000;
+                }
+
+                converted.put_pixel(x, y, converted_pixel);
+            }
+        }
+
+        Ok(converted)
+    }
+}
+
+impl Drop for Presenter {
+    fn drop(&mut self) {
+        if let Some(shm) = self.shm.take() {
+            shm.destroy(&self.conn);
+        }
+
+        _ = self.conn.free_gc(self.gc);
+    }
+}
+
+/// A shared memory segment attached to both this process and the X server.
+#[derive(Debug)]
+struct ShmSegment {
+    seg: u32,
+    addr: NonNull<u8>,
+    size: usize,
+}
+
+impl ShmSegment {
+    /// Returns `None` if the segment could not be created or attached, e.g. for remote connections.
+    fn new(conn: &XCBConnection, size: usize) -> Result<Option<Self>, Error> {
+        // SAFETY:
+        // shmget has no memory safety requirements.
+        let shmid = unsafe { libc::shmget(libc::IPC_PRIVATE, size, libc::IPC_CREAT | 0o600) };
+
+        if shmid < 0 {
+            log::warn!("shmget failed: {}", std::io::Error::last_os_error());
+            return Ok(None);
+        }
+
+        // SAFETY:
+        // shmid is a valid segment id.
+        let addr = unsafe { libc::shmat(shmid, std::ptr::null(), 0) };
+
+        let seg = conn.generate_id()?;
+
+        #[allow(clippy::cast_sign_loss)]
+        let attached = if addr as isize == -1 {
+            log::warn!("shmat failed: {}", std::io::Error::last_os_error());
+            false
+        } else {
+            conn.shm_attach(seg, shmid as u32, false)?
+                .check()
+                .inspect_err(|err| log::warn!("failed to attach shared memory: {err}"))
+                .is_ok()
+        };
+
+        // SAFETY:
+        // shmid is a valid segment id.
+        // The segment is destroyed once both this process and the server detached from it.
+        unsafe { libc::shmctl(shmid, libc::IPC_RMID, std::ptr::null_mut()) };
+
+        match NonNull::new(addr.cast::<u8>()) {
+            Some(addr) if attached => Ok(Some(Self { seg, addr, size })),
+            _ => {
+                if addr as isize != -1 {
+                    // SAFETY:
+                    // addr was returned by shmat.
+                    unsafe { libc::shmdt(addr) };
+                }
+
+                Ok(None)
+            }
+        }
+    }
+
+    fn destroy(self, conn: &XCBConnection) {
+        _ = conn.shm_detach(self.seg);
+        _ = conn.sync();
+
+        // SAFETY:
+        // addr was returned by shmat and the server no longer uses the segment.
+        unsafe { libc::shmdt(self.addr.as_ptr().cast()) };
+    }
+}
+
+/// The pixel layout of the buffers passed to `present_buffer`.
+fn xrgb_layout() -> PixelLayout {
+    PixelLayout::new(
+        ColorComponent::new(8, 16).unwrap(),
+        ColorComponent::new(8, 8).unwrap(),
+        ColorComponent::new(8, 0).unwrap(),
+    )
+}
+
+pub const fn native_order() -> ImageOrder {
+    if cfg!(target_endian = "little") {
+        ImageOrder::LsbFirst
+    } else {
+        ImageOrder::MsbFirst
+    }
+}
+
+/// Checks if the server stores pixels of `depth` exactly like a native `u32` XRGB buffer.
+fn is_native_xrgb(conn: &XCBConnection, depth: u8) -> bool {
+    let setup = conn.setup();
+
+    let Ok(order) = ImageOrder::try_from(setup.image_byte_order) else {
+        return false;
+    };
+
+    order == native_order()
+        && setup
+            .pixmap_formats
+            .iter()
+            .any(|format| format.depth == depth && format.bits_per_pixel == 32)
+}
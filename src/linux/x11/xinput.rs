@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+
+use x11rb::{
+    connection::RequestConnection as _,
+    protocol::xinput::{
+        self, ConnectionExt as _, Device, DeviceClass, DeviceClassData, DeviceId, Fp3232,
+        MotionEvent, RawMotionEvent, ScrollType,
+    },
+    xcb_ffi::XCBConnection,
+};
+
+use crate::{Error, inputs::ScrollDelta};
+
+/// Turns the scroll valuators of `XInput` 2.1 into smooth scroll deltas.
+///
+/// Valuators only report absolute values, so the last value of each one is remembered.
+#[derive(Debug, Default)]
+pub struct Scrolling {
+    devices: HashMap<DeviceId, Vec<ScrollValuator>>,
+}
+
+#[derive(Debug)]
+struct ScrollValuator {
+    number: u16,
+    horizontal: bool,
+    /// The distance of a single notch.
+    increment: f64,
+    value: f64,
+}
+
+impl Scrolling {
+    /// Returns `None`, if the server doesn't support `XInput` 2.1.
+    pub fn new(conn: &XCBConnection) -> Result<Option<Self>, Error> {
+        if conn
+            .extension_information(xinput::X11_EXTENSION_NAME)?
+            .is_none()
+        {
+            return Ok(None);
+        }
+
+        let version = conn.xinput_xi_query_version(2, 1)?.reply()?;
+        if (version.major_version, version.minor_version) < (2, 1) {
+            return Ok(None);
+        }
+
+        let mut scrolling = Self::default();
+        scrolling.reload(conn)?;
+
+        Ok(Some(scrolling))
+    }
+
+    /// Reads the scroll valuators of all devices and their current values.
+    ///
+    /// Needed after the pointer was outside of our windows, where scrolling went unnoticed.
+    pub fn reload(&mut self, conn: &XCBConnection) -> Result<(), Error> {
+        let reply = conn.xinput_xi_query_device(Device::ALL)?.reply()?;
+
+        self.devices = reply
+            .infos
+            .into_iter()
+            .map(|info| (info.deviceid, scroll_valuators(&info.classes)))
+            .collect();
+
+        Ok(())
+    }
+
+    /// Replaces the scroll valuators of a device, that changed its classes.
+    pub fn update_device(&mut self, device: DeviceId, classes: &[DeviceClass]) {
+        self.devices.insert(device, scroll_valuators(classes));
+    }
+
+    /// Returns the distance scrolled since the last event of the same device.
+    pub fn scroll(&mut self, e: &MotionEvent) -> Option<ScrollDelta> {
+        let valuators = self.devices.get_mut(&e.sourceid)?;
+
+        let (mut x, mut y) = (0.0, 0.0);
+
+        for (number, value) in valuator_values(&e.valuator_mask, &e.axisvalues) {
+            let Some(valuator) = valuators
+                .iter_mut()
+                .find(|valuator| usize::from(valuator.number) == number)
+            else {
+                continue;
+            };
+
+            let lines = (value - valuator.value) / valuator.increment;
+            valuator.value = value;
+
+            if valuator.horizontal {
+                x += lines;
+            } else {
+                y += lines;
+            }
+        }
+
+        // valuators grow when scrolling down
+        let delta = ScrollDelta::Lines { x, y: -y };
+
+        delta.direction().map(|_| delta)
+    }
+}
+
+/// Returns the relative motion of a raw event, before pointer acceleration.
+pub fn raw_motion(e: &RawMotionEvent) -> Option<(f64, f64)> {
+    let (mut dx, mut dy) = (None, None);
+
+    // the pointer moves along the first two valuators
+    for (number, value) in valuator_values(&e.valuator_mask, &e.axisvalues_raw) {
+        match number {
+            0 => dx = Some(value),
+            1 => dy = Some(value),
+            _ => {}
+        }
+    }
+
+    (dx.is_some() || dy.is_some()).then(|| (dx.unwrap_or(0.0), dy.unwrap_or(0.0)))
+}
+
+/// Pairs the values of an event with the numbers of their valuators.
+///
+/// Values are only sent for the set bits of the mask.
+fn valuator_values<'a>(
+    mask: &'a [u32],
+    values: &'a [Fp3232],
+) -> impl Iterator<Item = (usize, f64)> + 'a {
+    let numbers = mask.iter().enumerate().flat_map(|(index, mask)| {
+        (0..32)
+            .filter(move |bit| mask & (1 << bit) != 0)
+            .map(move |bit| index * 32 + bit)
+    });
+
+    numbers.zip(values.iter().copied().map(fp3232))
+}
+
+/// Collects the scroll classes of a device, with the current values of their valuators.
+fn scroll_valuators(classes: &[DeviceClass]) -> Vec<ScrollValuator> {
+    let value = |number| {
+        classes.iter().find_map(|class| match &class.data {
+            DeviceClassData::Valuator(valuator) if valuator.number == number => {
+                Some(fp3232(valuator.value))
+            }
+            _ => None,
+        })
+    };
+
+    classes
+        .iter()
+        .filter_map(|class| match &class.data {
+            DeviceClassData::Scroll(scroll) => Some(ScrollValuator {
+                number: scroll.number,
+                horizontal: scroll.scroll_type == ScrollType::HORIZONTAL,
+                increment: fp3232(scroll.increment),
+                value: value(scroll.number)?,
+            }),
+            _ => None,
+        })
+        .filter(|valuator| valuator.increment != 0.0)
+        .collect()
+}
+
+fn fp3232(value: Fp3232) -> f64 {
+    f64::from(value.integral) + f64::from(value.frac) / 4_294_967_296.0
+}
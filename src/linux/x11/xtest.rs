@@ -0,0 +1,107 @@
+use std::rc::Rc;
+
+use x11rb::{
+    CURRENT_TIME,
+    connection::RequestConnection as _,
+    protocol::{
+        xproto::{
+            BUTTON_PRESS_EVENT, BUTTON_RELEASE_EVENT, ConnectionExt as _, KEY_PRESS_EVENT,
+            KEY_RELEASE_EVENT, MOTION_NOTIFY_EVENT,
+        },
+        xtest::{self, ConnectionExt as _},
+    },
+    wrapper::ConnectionExt as _,
+};
+
+use crate::{
+    Error, InputInjectorImpl,
+    inputs::{Key, MouseButton, MouseWheelDirection},
+    linux::inputs::{x_button, x_keycode},
+};
+
+use super::connection::XConnection;
+
+/// Generates input events for a window through the XTEST extension.
+#[derive(Debug)]
+pub struct InputInjector {
+    xconn: Rc<XConnection>,
+    window: u32,
+}
+
+impl InputInjector {
+    pub fn new(xconn: Rc<XConnection>, window: u32) -> Result<Self, Error> {
+        if xconn
+            .conn
+            .extension_information(xtest::X11_EXTENSION_NAME)?
+            .is_none()
+        {
+            return Err(Error::Unsupported("XTEST extension"));
+        }
+
+        Ok(Self { xconn, window })
+    }
+
+    fn fake_input(&self, type_: u8, detail: u8, root_x: i16, root_y: i16) -> Result<(), Error> {
+        let root = self.xconn.screen().root;
+
+        // device 0 is the core keyboard/pointer
+        self.xconn
+            .conn
+            .xtest_fake_input(type_, detail, CURRENT_TIME, root, root_x, root_y, 0)?;
+
+        // once the server answered, the generated events are queued on the connection
+        self.xconn.conn.sync()?;
+
+        Ok(())
+    }
+}
+
+impl InputInjectorImpl for InputInjector {
+    fn key(&self, key: Key, down: bool) -> Result<(), Error> {
+        let keycode = x_keycode(key).ok_or(Error::Unsupported("key"))?;
+        let type_ = if down {
+            KEY_PRESS_EVENT
+        } else {
+            KEY_RELEASE_EVENT
+        };
+
+        self.fake_input(type_, keycode, 0, 0)
+    }
+
+    fn mouse_button(&self, button: MouseButton, down: bool) -> Result<(), Error> {
+        let type_ = if down {
+            BUTTON_PRESS_EVENT
+        } else {
+            BUTTON_RELEASE_EVENT
+        };
+
+        self.fake_input(type_, x_button(button), 0, 0)
+    }
+
+    fn move_pointer(&self, x: i32, y: i32) -> Result<(), Error> {
+        let clamp =
+            |pos: i32| i16::try_from(pos).unwrap_or(if pos < 0 { i16::MIN } else { i16::MAX });
+
+        let root = self.xconn.screen().root;
+        let translated = self
+            .xconn
+            .conn
+            .translate_coordinates(self.window, root, clamp(x), clamp(y))?
+            .reply()?;
+
+        self.fake_input(MOTION_NOTIFY_EVENT, 0, translated.dst_x, translated.dst_y)
+    }
+
+    fn scroll(&self, direction: MouseWheelDirection) -> Result<(), Error> {
+        // the core protocol reports scrolling as presses of buttons 4 to 7
+        let button = match direction {
+            MouseWheelDirection::Up => 4,
+            MouseWheelDirection::Down => 5,
+            MouseWheelDirection::Left => 6,
+            MouseWheelDirection::Right => 7,
+        };
+
+        self.fake_input(BUTTON_PRESS_EVENT, button, 0, 0)?;
+        self.fake_input(BUTTON_RELEASE_EVENT, button, 0, 0)
+    }
+}
@@ -0,0 +1,448 @@
+use std::{
+    ffi::{CStr, c_char, c_int, c_void},
+    ptr::NonNull,
+};
+
+use xkbcommon_dl::{
+    XkbCommon, XkbCommonCompose, x11::xkb_x11_setup_xkb_extension_flags, xkb_compose_compile_flags,
+    xkb_compose_feed_result, xkb_compose_state, xkb_compose_state_flags, xkb_compose_status,
+    xkb_compose_table, xkb_context, xkb_context_flags, xkb_keymap, xkb_keymap_compile_flags,
+    xkb_keymap_format, xkb_state,
+};
+
+/// Translates keycodes into text, following the keyboard layout, dead keys and compose sequences.
+///
+/// libxkbcommon is loaded at runtime, without it no text is produced.
+pub struct Keyboard {
+    xkb: &'static XkbCommon,
+    context: NonNull<xkb_context>,
+    keymap: Option<Keymap>,
+    compose: Option<Compose>,
+}
+
+impl Keyboard {
+    pub fn new() -> Option<Self> {
+        let Some(xkb) = xkbcommon_dl::xkbcommon_option() else {
+            log::warn!("libxkbcommon could not be loaded, text input is not available");
+            return None;
+        };
+
+        // SAFETY:
+        // xkb_context_new has no safety requirements.
+        let context = NonNull::new(unsafe {
+            (xkb.xkb_context_new)(xkb_context_flags::XKB_CONTEXT_NO_FLAGS)
+        })?;
+
+        let compose = Compose::new(context);
+
+        Some(Self {
+            xkb,
+            context,
+            keymap: None,
+            compose,
+        })
+    }
+
+    /// Loads the keymap of the core keyboard of an X11 connection.
+    ///
+    /// # Safety
+    /// `conn` has to be a valid `xcb_connection_t`.
+    pub unsafe fn load_x11(&mut self, conn: *mut c_void) -> bool {
+        let Some(x11) = xkbcommon_dl::x11::xkbcommon_x11_option() else {
+            log::warn!("libxkbcommon-x11 could not be loaded, text input is not available");
+            return false;
+        };
+
+        // SAFETY:
+        // conn is a valid connection and the out parameters are optional.
+        unsafe {
+            let setup = (x11.xkb_x11_setup_xkb_extension)(
+                conn,
+                1,
+                0,
+                xkb_x11_setup_xkb_extension_flags::XKB_X11_SETUP_XKB_EXTENSION_NO_FLAGS,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+            );
+
+            if setup == 0 {
+                log::warn!("the X server does not support XKB");
+                return false;
+            }
+
+            let device = (x11.xkb_x11_get_core_keyboard_device_id)(conn);
+            if device == -1 {
+                return false;
+            }
+
+            let keymap = (x11.xkb_x11_keymap_new_from_device)(
+                self.context.as_ptr(),
+                conn,
+                device,
+                xkb_keymap_compile_flags::XKB_KEYMAP_COMPILE_NO_FLAGS,
+            );
+
+            let Some(keymap) = NonNull::new(keymap) else {
+                return false;
+            };
+
+            let state = (x11.xkb_x11_state_new_from_device)(keymap.as_ptr(), conn, device);
+
+            self.set_keymap(keymap, state)
+        }
+    }
+
+    /// Loads a keymap in the XKB text format, as sent by Wayland compositors.
+    pub fn load_keymap(&mut self, keymap: &CStr) -> bool {
+        // SAFETY:
+        // keymap is a valid C string.
+        unsafe {
+            let keymap = (self.xkb.xkb_keymap_new_from_string)(
+                self.context.as_ptr(),
+                keymap.as_ptr(),
+                xkb_keymap_format::XKB_KEYMAP_FORMAT_TEXT_V1,
+                xkb_keymap_compile_flags::XKB_KEYMAP_COMPILE_NO_FLAGS,
+            );
+
+            let Some(keymap) = NonNull::new(keymap) else {
+                log::warn!("failed to compile keymap");
+                return false;
+            };
+
+            let state = (self.xkb.xkb_state_new)(keymap.as_ptr());
+
+            self.set_keymap(keymap, state)
+        }
+    }
+
+    /// Takes ownership of both the keymap and the state.
+    unsafe fn set_keymap(&mut self, keymap: NonNull<xkb_keymap>, state: *mut xkb_state) -> bool {
+        let Some(state) = NonNull::new(state) else {
+            // SAFETY:
+            // keymap is a valid keymap, that is owned by nobody else.
+            unsafe { (self.xkb.xkb_keymap_unref)(keymap.as_ptr()) };
+            return false;
+        };
+
+        self.keymap = Some(Keymap {
+            xkb: self.xkb,
+            keymap,
+            state,
+        });
+
+        true
+    }
+
+    /// Sets the modifiers and the layout group, that keycodes are translated with.
+    pub fn update_mask(&self, depressed: u32, latched: u32, locked: u32, group: u32) {
+        let Some(keymap) = &self.keymap else {
+            return;
+        };
+
+        // SAFETY:
+        // state is a valid state.
+        unsafe {
+            (self.xkb.xkb_state_update_mask)(
+                keymap.state.as_ptr(),
+                depressed,
+                latched,
+                locked,
+                0,
+                0,
+                group,
+            );
+        }
+    }
+
+    /// Sets the modifiers and the layout group from the `state` of a core X11 event.
+    ///
+    /// Caps Lock (`Lock`) and Num Lock (`Mod2`) are locked, all other modifiers are held down.
+    pub fn update_x11_state(&self, state: u16) {
+        const LOCKED: u32 = (1 << 1) | (1 << 4);
+
+        // the modifiers are in the lower 8 bits, the group in bits 13 and 14
+        let modifiers = u32::from(state & 0xff);
+        let group = u32::from((state >> 13) & 0b11);
+
+        self.update_mask(modifiers & !LOCKED, 0, modifiers & LOCKED, group);
+    }
+
+    /// Returns the text typed by a key press.
+    ///
+    /// Dead keys and keys in the middle of a compose sequence don't produce text,
+    /// the sequence is returned once it is complete.
+    /// Control characters, like those of Enter, Backspace or Ctrl+letter, are not considered text.
+    pub fn key_text(&self, keycode: u32) -> Option<String> {
+        let keymap = self.keymap.as_ref()?;
+
+        // SAFETY:
+        // state is a valid state.
+        let keysym =
+            unsafe { (self.xkb.xkb_state_key_get_one_sym)(keymap.state.as_ptr(), keycode) };
+
+        let text = match self.compose.as_ref().map(|compose| compose.feed(keysym)) {
+            Some(xkb_compose_status::XKB_COMPOSE_COMPOSING) => return None,
+            Some(xkb_compose_status::XKB_COMPOSE_COMPOSED) => self.compose.as_ref()?.take(),
+            Some(xkb_compose_status::XKB_COMPOSE_CANCELLED) => {
+                self.compose.as_ref()?.reset();
+                return None;
+            }
+            _ => {
+                // SAFETY:
+                // state is a valid state and the buffer is valid for len bytes.
+                read_utf8(|buffer, len| unsafe {
+                    (self.xkb.xkb_state_key_get_utf8)(keymap.state.as_ptr(), keycode, buffer, len)
+                })
+            }
+        }?;
+
+        (!text.chars().all(char::is_control)).then_some(text)
+    }
+}
+
+impl Drop for Keyboard {
+    fn drop(&mut self) {
+        // the keymap and compose table hold references to the context
+        self.keymap = None;
+        self.compose = None;
+
+        // SAFETY:
+        // context is a valid context.
+        unsafe { (self.xkb.xkb_context_unref)(self.context.as_ptr()) };
+    }
+}
+
+impl std::fmt::Debug for Keyboard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Keyboard")
+            .field("keymap", &self.keymap.is_some())
+            .field("compose", &self.compose.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+struct Keymap {
+    xkb: &'static XkbCommon,
+    keymap: NonNull<xkb_keymap>,
+    state: NonNull<xkb_state>,
+}
+
+impl Drop for Keymap {
+    fn drop(&mut self) {
+        // SAFETY:
+        // both are valid and owned by self.
+        unsafe {
+            (self.xkb.xkb_state_unref)(self.state.as_ptr());
+            (self.xkb.xkb_keymap_unref)(self.keymap.as_ptr());
+        }
+    }
+}
+
+/// The compose table of the current locale, which also contains the dead key sequences.
+struct Compose {
+    xkb: &'static XkbCommonCompose,
+    table: NonNull<xkb_compose_table>,
+    state: NonNull<xkb_compose_state>,
+}
+
+impl Compose {
+    fn new(context: NonNull<xkb_context>) -> Option<Self> {
+        let xkb = xkbcommon_dl::xkbcommon_compose_option()?;
+
+        let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .into_iter()
+            .filter_map(std::env::var_os)
+            .find(|locale| !locale.is_empty())
+            .and_then(|locale| std::ffi::CString::new(locale.into_encoded_bytes()).ok())
+            .unwrap_or_else(|| c"C".to_owned());
+
+        // SAFETY:
+        // context is a valid context and locale a valid C string.
+        unsafe {
+            let table = (xkb.xkb_compose_table_new_from_locale)(
+                context.as_ptr(),
+                locale.as_ptr(),
+                xkb_compose_compile_flags::XKB_COMPOSE_COMPILE_NO_FLAGS,
+            );
+
+            let Some(table) = NonNull::new(table) else {
+                log::warn!("no compose table for locale {locale:?}");
+                return None;
+            };
+
+            let state = (xkb.xkb_compose_state_new)(
+                table.as_ptr(),
+                xkb_compose_state_flags::XKB_COMPOSE_STATE_NO_FLAGS,
+            );
+
+            let Some(state) = NonNull::new(state) else {
+                (xkb.xkb_compose_table_unref)(table.as_ptr());
+                return None;
+            };
+
+            Some(Self { xkb, table, state })
+        }
+    }
+
+    fn feed(&self, keysym: u32) -> xkb_compose_status {
+        // SAFETY:
+        // state is a valid compose state.
+        unsafe {
+            // modifier keys are ignored and don't interrupt a sequence
+            if (self.xkb.xkb_compose_state_feed)(self.state.as_ptr(), keysym)
+                == xkb_compose_feed_result::XKB_COMPOSE_FEED_IGNORED
+            {
+                return xkb_compose_status::XKB_COMPOSE_NOTHING;
+            }
+
+            (self.xkb.xkb_compose_state_get_status)(self.state.as_ptr())
+        }
+    }
+
+    /// Returns the text of a completed sequence and starts a new one.
+    fn take(&self) -> Option<String> {
+        // SAFETY:
+        // state is a valid compose state and the buffer is valid for len bytes.
+        let text = read_utf8(|buffer, len| unsafe {
+            (self.xkb.xkb_compose_state_get_utf8)(self.state.as_ptr(), buffer, len)
+        });
+
+        self.reset();
+
+        text
+    }
+
+    fn reset(&self) {
+        // SAFETY:
+        // state is a valid compose state.
+        unsafe { (self.xkb.xkb_compose_state_reset)(self.state.as_ptr()) };
+    }
+}
+
+impl Drop for Compose {
+    fn drop(&mut self) {
+        // SAFETY:
+        // both are valid and owned by self.
+        unsafe {
+            (self.xkb.xkb_compose_state_unref)(self.state.as_ptr());
+            (self.xkb.xkb_compose_table_unref)(self.table.as_ptr());
+        }
+    }
+}
+
+/// Calls a libxkbcommon function, that writes a NUL terminated UTF-8 string into a buffer.
+fn read_utf8(get: impl Fn(*mut c_char, usize) -> c_int) -> Option<String> {
+    // the first call only returns the required size
+    let len = usize::try_from(get(std::ptr::null_mut(), 0)).ok()?;
+    if len == 0 {
+        return None;
+    }
+
+    let mut buffer = vec![0u8; len + 1];
+    get(buffer.as_mut_ptr().cast(), buffer.len());
+    buffer.truncate(len);
+
+    String::from_utf8(buffer).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use xkbcommon_dl::xkb_rule_names;
+
+    use super::*;
+
+    // core X11 modifier masks and keycodes, which are evdev codes + 8
+    const SHIFT: u16 = 1 << 0;
+    const LOCK: u16 = 1 << 1;
+    const CONTROL: u16 = 1 << 2;
+    const MOD2: u16 = 1 << 4;
+
+    const KEY_A: u32 = 30 + 8;
+    const KEY_ENTER: u32 = 28 + 8;
+    const KEY_KP1: u32 = 79 + 8;
+
+    /// A keyboard with the US layout, or `None` if libxkbcommon is not installed.
+    fn us_keyboard() -> Option<Keyboard> {
+        let mut keyboard = Keyboard::new()?;
+
+        let names = xkb_rule_names {
+            rules: c"evdev".as_ptr(),
+            model: c"pc105".as_ptr(),
+            layout: c"us".as_ptr(),
+            variant: std::ptr::null(),
+            options: std::ptr::null(),
+        };
+
+        // SAFETY:
+        // the context is valid and the names are NUL terminated strings.
+        unsafe {
+            let keymap = NonNull::new((keyboard.xkb.xkb_keymap_new_from_names)(
+                keyboard.context.as_ptr(),
+                &raw const names,
+                xkb_keymap_compile_flags::XKB_KEYMAP_COMPILE_NO_FLAGS,
+            ))?;
+            let state = (keyboard.xkb.xkb_state_new)(keymap.as_ptr());
+
+            keyboard.set_keymap(keymap, state).then_some(keyboard)
+        }
+    }
+
+    fn text(keyboard: &Keyboard, state: u16, keycode: u32) -> Option<String> {
+        keyboard.update_x11_state(state);
+        keyboard.key_text(keycode)
+    }
+
+    #[test]
+    fn read_utf8_reads_the_whole_string() {
+        let source = "äb";
+
+        let text = read_utf8(|buffer, len| {
+            if !buffer.is_null() {
+                let len = len.min(source.len());
+
+                // SAFETY:
+                // buffer is valid for len bytes.
+                unsafe { std::ptr::copy_nonoverlapping(source.as_ptr(), buffer.cast(), len) };
+            }
+
+            c_int::try_from(source.len()).unwrap()
+        });
+
+        assert_eq!(text.as_deref(), Some(source));
+    }
+
+    #[test]
+    fn read_utf8_without_text() {
+        assert_eq!(read_utf8(|_, _| 0), None);
+        assert_eq!(read_utf8(|_, _| -1), None);
+    }
+
+    #[test]
+    fn control_characters_are_not_text() {
+        let Some(keyboard) = us_keyboard() else {
+            return;
+        };
+
+        assert_eq!(text(&keyboard, 0, KEY_A).as_deref(), Some("a"));
+        assert_eq!(text(&keyboard, SHIFT, KEY_A).as_deref(), Some("A"));
+
+        // Ctrl+A is U+0001, Enter is a carriage return
+        assert_eq!(text(&keyboard, CONTROL, KEY_A), None);
+        assert_eq!(text(&keyboard, 0, KEY_ENTER), None);
+    }
+
+    #[test]
+    fn lock_modifiers_are_locked() {
+        let Some(keyboard) = us_keyboard() else {
+            return;
+        };
+
+        assert_eq!(text(&keyboard, LOCK, KEY_A).as_deref(), Some("A"));
+
+        // without Num Lock, the keypad moves the cursor
+        assert_eq!(text(&keyboard, MOD2, KEY_KP1).as_deref(), Some("1"));
+        assert_eq!(text(&keyboard, 0, KEY_KP1), None);
+    }
+}
@@ -0,0 +1,103 @@
+use crate::{Rect, TargetMonitorId};
+
+/// A display, as it was when it was queried.
+///
+/// Handles are snapshots, they don't change when the display is reconfigured.
+/// Query them again after a `WindowEvent::MonitorsChanged`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitorHandle {
+    pub(crate) id: TargetMonitorId,
+    pub(crate) name: String,
+    pub(crate) position: (i32, i32),
+    pub(crate) size: Rect,
+    pub(crate) physical_size_mm: (u32, u32),
+    pub(crate) scale_factor: f64,
+    pub(crate) refresh_rate_millihertz: Option<u32>,
+    pub(crate) video_modes: Vec<VideoMode>,
+}
+
+impl MonitorHandle {
+    /// Returns the name of the connector or display device, like `DP-1`.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the position of the top left corner on the desktop, in pixels.
+    #[must_use]
+    pub const fn position(&self) -> (i32, i32) {
+        self.position
+    }
+
+    /// Returns the size in pixels.
+    #[must_use]
+    pub const fn size(&self) -> Rect {
+        self.size
+    }
+
+    /// Returns the size of the visible area in millimeters, or `(0, 0)` if the display
+    /// doesn't report it, like most projectors.
+    #[must_use]
+    pub const fn physical_size_mm(&self) -> (u32, u32) {
+        self.physical_size_mm
+    }
+
+    /// Returns the factor, that logical sizes are multiplied by to get pixels on this monitor.
+    ///
+    /// A monitor at 96 DPI has a scale factor of `1.0`.
+    #[must_use]
+    pub const fn scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
+
+    /// Returns the refresh rate of the current mode in mHz, so 60 Hz is `60_000`.
+    #[must_use]
+    pub const fn refresh_rate_millihertz(&self) -> Option<u32> {
+        self.refresh_rate_millihertz
+    }
+
+    /// Returns all modes, that the monitor can be switched to with `Fullscreen::Exclusive`.
+    #[must_use]
+    pub fn video_modes(&self) -> &[VideoMode] {
+        &self.video_modes
+    }
+}
+
+/// A resolution and refresh rate of a monitor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VideoMode {
+    pub(crate) monitor: TargetMonitorId,
+    /// The `RandR` mode on X11.
+    pub(crate) native: u32,
+    pub(crate) size: Rect,
+    pub(crate) bit_depth: u16,
+    pub(crate) refresh_rate_millihertz: u32,
+}
+
+impl VideoMode {
+    #[must_use]
+    pub const fn size(&self) -> Rect {
+        self.size
+    }
+
+    #[must_use]
+    pub const fn bit_depth(&self) -> u16 {
+        self.bit_depth
+    }
+
+    /// Returns the refresh rate in mHz, so 60 Hz is `60_000`.
+    #[must_use]
+    pub const fn refresh_rate_millihertz(&self) -> u32 {
+        self.refresh_rate_millihertz
+    }
+}
+
+/// How a window covers a monitor, set with `Window::set_fullscreen`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fullscreen {
+    /// Switches the monitor of the mode to it, and restores the previous mode afterwards.
+    Exclusive(VideoMode),
+
+    /// Covers a monitor without changing its mode, `None` uses the monitor of the window.
+    Borderless(Option<MonitorHandle>),
+}
@@ -0,0 +1,116 @@
+use std::ptr::{null, null_mut};
+
+use windows_sys::Win32::{
+    Foundation::{HWND, POINT, RECT},
+    Graphics::Gdi::ClientToScreen,
+    UI::WindowsAndMessaging::{
+        ClipCursor, GetClientRect, HCURSOR, IDC_APPSTARTING, IDC_ARROW, IDC_CROSS, IDC_HAND,
+        IDC_IBEAM, IDC_NO, IDC_SIZEALL, IDC_SIZENESW, IDC_SIZENS, IDC_SIZENWSE, IDC_SIZEWE,
+        IDC_WAIT, LoadCursorW,
+    },
+};
+
+use crate::cursor::{CursorGrabMode, CursorIcon};
+
+use super::{Error, icon::create_icon};
+
+/// Confines the cursor to the client area of a window, or to its center, when locked.
+///
+/// Windows releases the clip, when another window is activated, so this has to be
+/// repeated whenever the window gains focus, moves or is resized.
+pub fn clip_cursor(hwnd: HWND, mode: CursorGrabMode) -> Result<(), Error> {
+    let res = match mode {
+        CursorGrabMode::None => unsafe { ClipCursor(null()) },
+        CursorGrabMode::Confined | CursorGrabMode::Locked => {
+            let mut rect = client_rect(hwnd)?;
+
+            if mode == CursorGrabMode::Locked {
+                let x = rect.left + (rect.right - rect.left) / 2;
+                let y = rect.top + (rect.bottom - rect.top) / 2;
+
+                rect = RECT {
+                    left: x,
+                    top: y,
+                    right: x + 1,
+                    bottom: y + 1,
+                };
+            }
+
+            unsafe { ClipCursor(&raw const rect) }
+        }
+    };
+
+    if res == 0 {
+        return Err(Error::CursorGrab(std::io::Error::last_os_error()));
+    }
+
+    Ok(())
+}
+
+/// Returns the client area of a window in screen coordinates.
+fn client_rect(hwnd: HWND) -> Result<RECT, Error> {
+    let mut rect: RECT = unsafe { std::mem::zeroed() };
+
+    if unsafe { GetClientRect(hwnd, &raw mut rect) } == 0 {
+        return Err(Error::CursorGrab(std::io::Error::last_os_error()));
+    }
+
+    let mut top_left = POINT {
+        x: rect.left,
+        y: rect.top,
+    };
+    let mut bottom_right = POINT {
+        x: rect.right,
+        y: rect.bottom,
+    };
+
+    unsafe {
+        ClientToScreen(hwnd, &raw mut top_left);
+        ClientToScreen(hwnd, &raw mut bottom_right);
+    }
+
+    Ok(RECT {
+        left: top_left.x,
+        top: top_left.y,
+        right: bottom_right.x,
+        bottom: bottom_right.y,
+    })
+}
+
+/// Returns a shared system cursor.
+pub fn load_cursor(icon: CursorIcon) -> Result<HCURSOR, Error> {
+    let name = match icon {
+        CursorIcon::Default => IDC_ARROW,
+        CursorIcon::Text => IDC_IBEAM,
+        CursorIcon::Hand => IDC_HAND,
+        CursorIcon::Crosshair => IDC_CROSS,
+        CursorIcon::Move => IDC_SIZEALL,
+        CursorIcon::EwResize => IDC_SIZEWE,
+        CursorIcon::NsResize => IDC_SIZENS,
+        CursorIcon::NeswResize => IDC_SIZENESW,
+        CursorIcon::NwseResize => IDC_SIZENWSE,
+        CursorIcon::Wait => IDC_WAIT,
+        CursorIcon::Progress => IDC_APPSTARTING,
+        CursorIcon::NotAllowed => IDC_NO,
+    };
+
+    let cursor = unsafe { LoadCursorW(null_mut(), name) };
+
+    if cursor.is_null() {
+        return Err(Error::Cursor(std::io::Error::last_os_error()));
+    }
+
+    Ok(cursor)
+}
+
+/// Creates a cursor from RGBA pixels, that has to be destroyed with `DestroyCursor`.
+pub fn create_custom_cursor(
+    rgba: &[u8],
+    width: u16,
+    height: u16,
+    hotspot: (u16, u16),
+) -> Result<HCURSOR, Error> {
+    crate::icon::check_rgba_size(rgba, width, height)?;
+
+    create_icon(rgba, width, height, Some(hotspot)).map_err(Error::Cursor)
+}
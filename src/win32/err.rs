@@ -4,6 +4,21 @@ pub enum Error {
     CreateWindow(std::io::Error),
     ShowWindow(std::io::Error),
     SetTitle(std::io::Error),
+    InvalidTitle,
+    Wait(std::io::Error),
+    WakeUp(std::io::Error),
+    Present(std::io::Error),
+    Capture(std::io::Error),
+    SendInput(std::io::Error),
+    RawInput(std::io::Error),
+    CursorGrab(std::io::Error),
+    Cursor(std::io::Error),
+    Icon(std::io::Error),
+    SetWindowPos(std::io::Error),
+    VideoMode(i32),
+    Unsupported(&'static str),
+    BufferSize { expected: usize, actual: usize },
+    RgbaSize { expected: usize, actual: usize },
 }
 
 impl Error {
@@ -29,6 +44,27 @@ impl std::fmt::Display for Error {
             Self::CreateWindow(err) => write!(f, "Failed to create window: {err}"),
             Self::ShowWindow(err) => write!(f, "Failed to show window:  {err}"),
             Self::SetTitle(err) => write!(f, "Failed to set title: {err}"),
+            Self::InvalidTitle => write!(f, "Invalid title: contains a NUL byte"),
+            Self::Wait(err) => write!(f, "Failed to wait for messages: {err}"),
+            Self::WakeUp(err) => write!(f, "Failed to wake up message loop: {err}"),
+            Self::Present(err) => write!(f, "Failed to present buffer: {err}"),
+            Self::Capture(err) => write!(f, "Failed to capture window: {err}"),
+            Self::SendInput(err) => write!(f, "Failed to send input: {err}"),
+            Self::RawInput(err) => write!(f, "Failed to register for raw input: {err}"),
+            Self::CursorGrab(err) => write!(f, "Failed to grab the cursor: {err}"),
+            Self::Cursor(err) => write!(f, "Failed to create cursor: {err}"),
+            Self::Icon(err) => write!(f, "Failed to create icon: {err}"),
+            Self::SetWindowPos(err) => write!(f, "Failed to move or resize the window: {err}"),
+            Self::VideoMode(code) => write!(f, "Failed to switch the video mode: {code}"),
+            Self::Unsupported(what) => write!(f, "Unsupported {what}"),
+            Self::BufferSize { expected, actual } => write!(
+                f,
+                "Invalid buffer size: expected {expected} pixels, got {actual}"
+            ),
+            Self::RgbaSize { expected, actual } => write!(
+                f,
+                "Invalid RGBA buffer size: expected {expected} bytes, got {actual}"
+            ),
         }
     }
 }
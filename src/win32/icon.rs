@@ -0,0 +1,107 @@
+use windows_sys::Win32::{
+    Foundation::{HWND, LPARAM},
+    Graphics::Gdi::{CreateBitmap, DeleteObject},
+    UI::WindowsAndMessaging::{
+        CreateIconIndirect, DestroyIcon, GetSystemMetrics, HICON, ICON_BIG, ICON_SMALL, ICONINFO,
+        SM_CXICON, SM_CXSMICON, SendMessageW, WM_SETICON,
+    },
+};
+
+use crate::{Image, icon::Icon};
+
+use super::Error;
+
+/// Sets the big icon of the title bar and task switcher and the small one of the taskbar.
+///
+/// Returns the created icons, that have to be destroyed once they are replaced.
+pub fn set_icon(hwnd: HWND, icon: Option<&Icon>) -> Result<[HICON; 2], Error> {
+    let mut icons = [std::ptr::null_mut(); 2];
+
+    if let Some(icon) = icon {
+        for (handle, metric) in icons.iter_mut().zip([SM_CXICON, SM_CXSMICON]) {
+            let size = unsafe { GetSystemMetrics(metric) };
+            let image = closest_image(icon, size);
+
+            match create_icon(&image.data, image.width, image.height, None) {
+                Ok(created) => *handle = created,
+                Err(err) => {
+                    destroy_icons(icons);
+                    return Err(Error::Icon(err));
+                }
+            }
+        }
+    }
+
+    // null icons fall back to the icon of the window class
+    for (handle, kind) in icons.into_iter().zip([ICON_BIG, ICON_SMALL]) {
+        unsafe { SendMessageW(hwnd, WM_SETICON, kind as usize, handle as LPARAM) };
+    }
+
+    Ok(icons)
+}
+
+pub fn destroy_icons(icons: [HICON; 2]) {
+    for icon in icons.into_iter().filter(|icon| !icon.is_null()) {
+        unsafe { DestroyIcon(icon) };
+    }
+}
+
+/// Returns the smallest image, that is at least `size` wide, or the largest one.
+fn closest_image(icon: &Icon, size: i32) -> &Image {
+    let images = icon.images();
+
+    images
+        .iter()
+        .filter(|image| i32::from(image.width) >= size)
+        .min_by_key(|image| image.width)
+        .or_else(|| images.iter().max_by_key(|image| image.width))
+        .expect("an icon has at least one image")
+}
+
+/// Creates an icon, or a cursor with a hotspot, from RGBA pixels.
+///
+/// The result has to be destroyed with `DestroyIcon` or `DestroyCursor`.
+pub fn create_icon(
+    rgba: &[u8],
+    width: u16,
+    height: u16,
+    hotspot: Option<(u16, u16)>,
+) -> std::io::Result<HICON> {
+    // 32 bit bitmaps are BGRA, with alpha taking precedence over the mask
+    let bgra: Vec<u8> = rgba
+        .chunks_exact(4)
+        .flat_map(|pixel| [pixel[2], pixel[1], pixel[0], pixel[3]])
+        .collect();
+
+    // monochrome rows are padded to 16 bits
+    let mask = vec![0u8; usize::from(width).div_ceil(16) * 2 * usize::from(height)];
+
+    let (hotspot_x, hotspot_y) = hotspot.unwrap_or_default();
+    let (width, height) = (i32::from(width), i32::from(height));
+
+    let icon = unsafe {
+        let color = CreateBitmap(width, height, 1, 32, bgra.as_ptr().cast());
+        let mask = CreateBitmap(width, height, 1, 1, mask.as_ptr().cast());
+
+        let info = ICONINFO {
+            fIcon: i32::from(hotspot.is_none()),
+            xHotspot: u32::from(hotspot_x),
+            yHotspot: u32::from(hotspot_y),
+            hbmMask: mask,
+            hbmColor: color,
+        };
+
+        let icon = CreateIconIndirect(&raw const info);
+
+        DeleteObject(color);
+        DeleteObject(mask);
+
+        icon
+    };
+
+    if icon.is_null() {
+        return Err(std::io::Error::last_os_error());
+    }
+
+    Ok(icon)
+}
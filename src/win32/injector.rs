@@ -0,0 +1,146 @@
+use windows_sys::Win32::{
+    Foundation::{HWND, POINT},
+    Graphics::Gdi::ClientToScreen,
+    UI::{
+        Input::KeyboardAndMouse::{
+            INPUT, INPUT_0, INPUT_KEYBOARD, INPUT_MOUSE, KEYBDINPUT, KEYEVENTF_KEYUP,
+            MOUSE_EVENT_FLAGS, MOUSEEVENTF_ABSOLUTE, MOUSEEVENTF_HWHEEL, MOUSEEVENTF_LEFTDOWN,
+            MOUSEEVENTF_LEFTUP, MOUSEEVENTF_MIDDLEDOWN, MOUSEEVENTF_MIDDLEUP, MOUSEEVENTF_MOVE,
+            MOUSEEVENTF_RIGHTDOWN, MOUSEEVENTF_RIGHTUP, MOUSEEVENTF_VIRTUALDESK, MOUSEEVENTF_WHEEL,
+            MOUSEINPUT, SendInput,
+        },
+        WindowsAndMessaging::{
+            GetSystemMetrics, SM_CXVIRTUALSCREEN, SM_CYVIRTUALSCREEN, SM_XVIRTUALSCREEN,
+            SM_YVIRTUALSCREEN, WHEEL_DELTA,
+        },
+    },
+};
+
+use crate::{
+    InputInjectorImpl,
+    inputs::{Key, MouseButton, MouseWheelDirection},
+};
+
+use super::Error;
+
+/// Generates input events for a window through `SendInput`.
+#[derive(Debug)]
+pub struct InputInjector {
+    handle: HWND,
+}
+
+impl InputInjector {
+    pub const fn new(handle: HWND) -> Self {
+        Self { handle }
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+    fn send(input: INPUT) -> Result<(), Error> {
+        // The function returns the number of events that it successfully inserted into the input stream.
+        let sent = unsafe { SendInput(1, &raw const input, std::mem::size_of::<INPUT>() as i32) };
+
+        if sent != 1 {
+            let err = std::io::Error::last_os_error();
+            return Err(Error::SendInput(err));
+        }
+
+        Ok(())
+    }
+
+    fn send_mouse(
+        dx: i32,
+        dy: i32,
+        mouse_data: u32,
+        flags: MOUSE_EVENT_FLAGS,
+    ) -> Result<(), Error> {
+        Self::send(INPUT {
+            r#type: INPUT_MOUSE,
+            Anonymous: INPUT_0 {
+                mi: MOUSEINPUT {
+                    dx,
+                    dy,
+                    mouseData: mouse_data,
+                    dwFlags: flags,
+                    time: 0,
+                    dwExtraInfo: 0,
+                },
+            },
+        })
+    }
+}
+
+impl InputInjectorImpl for InputInjector {
+    fn key(&self, key: Key, down: bool) -> Result<(), Error> {
+        if key == Key::Unidentified {
+            return Err(Error::Unsupported("key"));
+        }
+
+        Self::send(INPUT {
+            r#type: INPUT_KEYBOARD,
+            Anonymous: INPUT_0 {
+                ki: KEYBDINPUT {
+                    // keys are numbered by their virtual key code
+                    wVk: key as u16,
+                    wScan: 0,
+                    dwFlags: if down { 0 } else { KEYEVENTF_KEYUP },
+                    time: 0,
+                    dwExtraInfo: 0,
+                },
+            },
+        })
+    }
+
+    fn mouse_button(&self, button: MouseButton, down: bool) -> Result<(), Error> {
+        let flags = match (button, down) {
+            (MouseButton::Left, true) => MOUSEEVENTF_LEFTDOWN,
+            (MouseButton::Left, false) => MOUSEEVENTF_LEFTUP,
+            (MouseButton::Right, true) => MOUSEEVENTF_RIGHTDOWN,
+            (MouseButton::Right, false) => MOUSEEVENTF_RIGHTUP,
+            (MouseButton::Middle, true) => MOUSEEVENTF_MIDDLEDOWN,
+            (MouseButton::Middle, false) => MOUSEEVENTF_MIDDLEUP,
+            (MouseButton::Custom(_), _) => return Err(Error::Unsupported("mouse button")),
+        };
+
+        Self::send_mouse(0, 0, 0, flags)
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn move_pointer(&self, x: i32, y: i32) -> Result<(), Error> {
+        let mut point = POINT { x, y };
+        unsafe { ClientToScreen(self.handle, &raw mut point) };
+
+        // absolute coordinates are normalized to 0..=65535 across the virtual desktop
+        let (left, top, width, height) = unsafe {
+            (
+                GetSystemMetrics(SM_XVIRTUALSCREEN),
+                GetSystemMetrics(SM_YVIRTUALSCREEN),
+                GetSystemMetrics(SM_CXVIRTUALSCREEN),
+                GetSystemMetrics(SM_CYVIRTUALSCREEN),
+            )
+        };
+
+        let normalize = |pos: i32, origin: i32, size: i32| {
+            (i64::from(pos - origin) * 65535 / i64::from((size - 1).max(1))) as i32
+        };
+
+        Self::send_mouse(
+            normalize(point.x, left, width),
+            normalize(point.y, top, height),
+            0,
+            MOUSEEVENTF_MOVE | MOUSEEVENTF_ABSOLUTE | MOUSEEVENTF_VIRTUALDESK,
+        )
+    }
+
+    #[allow(clippy::cast_sign_loss, clippy::cast_possible_wrap)]
+    fn scroll(&self, direction: MouseWheelDirection) -> Result<(), Error> {
+        // mouseData holds a signed delta, positive is up or right
+        let (delta, flags) = match direction {
+            MouseWheelDirection::Up => (1, MOUSEEVENTF_WHEEL),
+            MouseWheelDirection::Down => (-1, MOUSEEVENTF_WHEEL),
+            MouseWheelDirection::Left => (-1, MOUSEEVENTF_HWHEEL),
+            MouseWheelDirection::Right => (1, MOUSEEVENTF_HWHEEL),
+        };
+
+        Self::send_mouse(0, 0, (delta * WHEEL_DELTA as i32) as u32, flags)
+    }
+}
@@ -1,67 +1,141 @@
+mod cursor;
 mod err;
+mod icon;
+mod injector;
 mod inputs;
+mod monitor;
 mod userdata;
 
 pub use err::Error;
+pub use injector::InputInjector;
+pub use monitor::MonitorId;
 
 use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
     os::windows::ffi::OsStrExt,
     ptr::{null, null_mut},
-    sync::Arc,
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
 };
 
 use windows_sys::{
     Win32::{
-        Foundation::{HMODULE, HWND, LPARAM, LRESULT, RECT, SetLastError, WPARAM},
-        System::LibraryLoader::GetModuleHandleW,
+        Devices::HumanInterfaceDevice::{HID_USAGE_GENERIC_MOUSE, HID_USAGE_PAGE_GENERIC},
+        Foundation::{
+            ERROR_CLASS_ALREADY_EXISTS, HMODULE, HWND, LPARAM, LRESULT, POINT, RECT, SetLastError,
+            WAIT_FAILED, WPARAM,
+        },
+        Graphics::Gdi::{
+            BI_RGB, BITMAPINFO, BITMAPINFOHEADER, BeginPaint, BitBlt, CreateCompatibleBitmap,
+            CreateCompatibleDC, DIB_RGB_COLORS, DeleteDC, DeleteObject, EndPaint, GetDC, GetDIBits,
+            InvalidateRect, PAINTSTRUCT, ReleaseDC, SRCCOPY, SelectObject, StretchDIBits,
+        },
+        System::{LibraryLoader::GetModuleHandleW, Threading::INFINITE},
+        UI::Controls::WM_MOUSELEAVE,
+        UI::HiDpi::{
+            DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2, GetDpiForWindow,
+            SetProcessDpiAwarenessContext,
+        },
+        UI::Input::KeyboardAndMouse::{
+            GetKeyState, TME_LEAVE, TRACKMOUSEEVENT, TrackMouseEvent, VK_CAPITAL, VK_CONTROL,
+            VK_LWIN, VK_MENU, VK_NUMLOCK, VK_RWIN, VK_SHIFT,
+        },
+        UI::Input::{
+            GetRawInputData, HRAWINPUT, MOUSE_MOVE_ABSOLUTE, RAWINPUT, RAWINPUTDEVICE,
+            RAWINPUTHEADER, RID_INPUT, RIDEV_REMOVE, RIM_TYPEMOUSE, RegisterRawInputDevices,
+        },
         UI::WindowsAndMessaging::{
-            AdjustWindowRectEx, CS_DBLCLKS, CreateWindowExW, DefWindowProcW, DestroyWindow,
-            DispatchMessageW, GWLP_USERDATA, GetClientRect, GetWindowLongPtrW, IDC_ARROW,
-            IDI_APPLICATION, LoadCursorW, LoadIconW, MSG, PM_REMOVE, PeekMessageW, PostQuitMessage,
-            RegisterClassExW, SW_SHOW, SW_SHOWNOACTIVATE, SetWindowLongPtrW, SetWindowTextW,
-            ShowWindow, TranslateMessage, WM_CLOSE, WM_DESTROY, WM_ERASEBKGND, WM_KEYDOWN,
-            WM_KEYUP, WM_LBUTTONDOWN, WM_LBUTTONUP, WM_MBUTTONDOWN, WM_MBUTTONUP, WM_MOUSEMOVE,
-            WM_MOUSEWHEEL, WM_RBUTTONDOWN, WM_RBUTTONUP, WM_SIZE, WM_SYSKEYDOWN, WM_SYSKEYUP,
-            WNDCLASSEXW, WS_CAPTION, WS_EX_APPWINDOW, WS_MAXIMIZEBOX, WS_MINIMIZEBOX,
-            WS_OVERLAPPED, WS_OVERLAPPEDWINDOW, WS_SYSMENU, WS_THICKFRAME,
+            AdjustWindowRectEx, CS_DBLCLKS, CW_USEDEFAULT, CreateWindowExW, DefWindowProcW,
+            DestroyCursor, DestroyWindow, DispatchMessageW, FLASHW_ALL, FLASHW_STOP,
+            FLASHW_TIMERNOFG, FLASHW_TRAY, FLASHWINFO, FlashWindowEx, GWL_EXSTYLE, GWL_STYLE,
+            GWLP_USERDATA, GetClientRect, GetForegroundWindow, GetWindowLongPtrW, GetWindowRect,
+            GetWindowTextLengthW, GetWindowTextW, HTCLIENT, HWND_BOTTOM, HWND_NOTOPMOST, HWND_TOP,
+            HWND_TOPMOST, IDC_ARROW, IDI_APPLICATION, IsIconic, IsZoomed, LoadCursorW, LoadIconW,
+            MINMAXINFO, MSG, MWMO_INPUTAVAILABLE, MsgWaitForMultipleObjectsEx, PM_REMOVE,
+            PeekMessageW, PostMessageW, PostQuitMessage, QS_ALLINPUT, RegisterClassExW,
+            SIZE_MINIMIZED, SW_HIDE, SW_MAXIMIZE, SW_MINIMIZE, SW_RESTORE, SW_SHOW,
+            SW_SHOWNOACTIVATE, SWP_FRAMECHANGED, SWP_NOACTIVATE, SWP_NOMOVE, SWP_NOOWNERZORDER,
+            SWP_NOSIZE, SWP_NOZORDER, SetCursor, SetForegroundWindow, SetWindowLongPtrW,
+            SetWindowPos, SetWindowTextW, ShowWindow, TranslateMessage, WHEEL_DELTA, WINDOWPOS,
+            WM_APP, WM_CHAR, WM_CLOSE, WM_DESTROY, WM_DISPLAYCHANGE, WM_DPICHANGED, WM_ERASEBKGND,
+            WM_GETMINMAXINFO, WM_INPUT, WM_KEYDOWN, WM_KEYUP, WM_KILLFOCUS, WM_LBUTTONDOWN,
+            WM_LBUTTONUP, WM_MBUTTONDOWN, WM_MBUTTONUP, WM_MOUSEHWHEEL, WM_MOUSEMOVE,
+            WM_MOUSEWHEEL, WM_MOVE, WM_PAINT, WM_RBUTTONDOWN, WM_RBUTTONUP, WM_SETCURSOR,
+            WM_SETFOCUS, WM_SIZE, WM_SIZING, WM_SYSKEYDOWN, WM_SYSKEYUP, WM_WINDOWPOSCHANGING,
+            WMSZ_BOTTOM, WMSZ_BOTTOMLEFT, WMSZ_LEFT, WMSZ_TOP, WMSZ_TOPLEFT, WMSZ_TOPRIGHT,
+            WNDCLASSEXW, WS_CAPTION, WS_CHILD, WS_EX_APPWINDOW, WS_EX_TOPMOST, WS_MAXIMIZE,
+            WS_MAXIMIZEBOX, WS_MINIMIZEBOX, WS_OVERLAPPED, WS_OVERLAPPEDWINDOW, WS_POPUP,
+            WS_SYSMENU, WS_THICKFRAME,
         },
     },
     w,
 };
 
 use crate::{
-    RawWindowHandle, Rect, WindowImpl,
+    DamageRect, EventLoopImpl, EventLoopProxyImpl, Image, RawWindowHandle, Rect, UserAttentionType,
+    WindowId, WindowImpl, WindowLevel,
+    builder::WindowAttributes,
+    cursor::{CursorGrabMode, CursorIcon},
+    dpi::PhysicalPosition,
     events::{
-        self, KeyEvent, MouseButtonEvent, MouseMoveEvent, MouseWheelEvent, WindowResizeEvent,
+        self, KeyEvent, MouseButtonEvent, MouseMoveEvent, MouseWheelEvent, RawMouseMotionEvent,
+        TextEvent, UserEvent, WindowResizeEvent,
     },
-    inputs::{Key, MouseButton, MouseWheelDirection},
+    icon::Icon,
+    inputs::{Key, Modifiers, MouseButton, ScrollDelta},
+    monitor::{Fullscreen, MonitorHandle},
 };
 
 use self::userdata::UserData;
 
+const CLASS_NAME: &str = "Windows_Class";
+
+/// Returns the style of a new window.
+const fn window_style(attributes: &WindowAttributes) -> u32 {
+    let mut style = if attributes.parent.is_some() {
+        WS_CHILD
+    } else if attributes.decorations {
+        WS_OVERLAPPED | WS_SYSMENU | WS_CAPTION | WS_MINIMIZEBOX
+    } else {
+        WS_POPUP
+    };
+
+    if attributes.resizable && attributes.decorations {
+        style |= WS_MAXIMIZEBOX | WS_THICKFRAME;
+    }
+
+    // applied once the window is shown
+    if attributes.maximized {
+        style |= WS_MAXIMIZE;
+    }
+
+    style
+}
+
+/// Posted by an `EventLoopProxy` to wake up the message loop.
+const WM_WAKE_UP: u32 = WM_APP;
+
+type UserEvents = Arc<Mutex<VecDeque<UserEvent>>>;
+
+/// All windows of a thread share its message queue, so the event loop only has to
+/// register the window class once.
 #[derive(Debug)]
-pub struct Window {
-    handle: HWND,
+pub struct EventLoop {
     instance: HMODULE,
 }
 
-impl WindowImpl for Window {
-    #[inline]
+impl EventLoopImpl for EventLoop {
     #[allow(clippy::cast_possible_truncation)]
-    fn create(
-        title: &str,
-        eventsys: Arc<dyn events::EventSystem>,
-        window_pos_x: i16,
-        window_pos_y: i16,
-        window_width: u16,
-        window_height: u16,
-    ) -> Result<Self, Error> {
-        let userdata = UserData::new(eventsys);
-
+    fn new() -> Result<Self, Error> {
         let instance: HMODULE = unsafe { GetModuleHandleW(null()) };
 
+        // windows report their real size in pixels, instead of being stretched by the system.
+        // fails if the awareness was already set, by a manifest or an earlier event loop
+        unsafe { SetProcessDpiAwarenessContext(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2) };
+
         // create window class
-        let class_name = win32_string("Windows_Class");
+        let class_name = win32_string(CLASS_NAME);
 
         let icon = unsafe { LoadIconW(instance, IDI_APPLICATION) };
         let cursor = unsafe { LoadCursorW(null_mut(), IDC_ARROW) };
@@ -82,23 +156,137 @@ impl WindowImpl for Window {
         };
 
         if unsafe { RegisterClassExW(&wnd_class) } == 0 {
-            return Err(Error::register_window_class(std::io::Error::last_os_error()))
-                .inspect_err(|err| log::error!("{err}"));
+            let err = std::io::Error::last_os_error();
+
+            // the class is shared by all event loops of this process
+            #[allow(clippy::cast_possible_wrap)]
+            if err.raw_os_error() != Some(ERROR_CLASS_ALREADY_EXISTS as i32) {
+                return Err(Error::register_window_class(err))
+                    .inspect_err(|err| log::error!("{err}"));
+            }
+        }
+
+        Ok(Self { instance })
+    }
+
+    fn pump_messages(&mut self) -> Result<(), Error> {
+        let mut msg: MSG = unsafe { std::mem::zeroed() };
+        let ptr = std::ptr::addr_of_mut!(msg);
+
+        // SAFETY:
+        // PeekMessageW is safe to call, because ptr is a valid pointer,
+        // hwnd := 0 retrieves messages from all Windows on the current thread,
+        // wMsgFilterMin,wMsgFilterMax := 0 filters no messages
+        // wRemoveMsg := 1 removes messages from queue
+        while unsafe { PeekMessageW(ptr, null_mut(), 0, 0, PM_REMOVE) } != 0 {
+            // SAFETY:
+            // This is safe to use, because ptr is a valid pointer to a MSG struct.
+            unsafe {
+                TranslateMessage(ptr);
+                DispatchMessageW(ptr)
+            };
+        }
+
+        Ok(())
+    }
+
+    fn wait_events(&mut self, timeout: Option<Duration>) -> Result<(), Error> {
+        wait_for_messages(timeout)?;
+
+        self.pump_messages()
+    }
+
+    fn available_monitors(&self) -> Vec<MonitorHandle> {
+        monitor::monitors()
+    }
+
+    fn primary_monitor(&self) -> Option<MonitorHandle> {
+        monitor::primary_monitor()
+    }
+}
+
+/// Wakes up the message loop of a window from another thread.
+#[derive(Debug, Clone)]
+pub struct EventLoopProxy {
+    // HWND is not Send, but posting messages to it is thread safe
+    handle: isize,
+    user_events: UserEvents,
+}
+
+impl EventLoopProxyImpl for EventLoopProxy {
+    fn wake_up(&self, event: Option<UserEvent>) -> Result<(), Error> {
+        if let Some(event) = event {
+            self.user_events
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push_back(event);
+        }
+
+        // If the function succeeds, the return value is nonzero.
+        // If the function fails, the return value is zero.
+        if unsafe { PostMessageW(self.handle as HWND, WM_WAKE_UP, 0, 0) } == 0 {
+            let err = std::io::Error::last_os_error();
+            return Err(Error::WakeUp(err));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct Window {
+    handle: HWND,
+    instance: HMODULE,
+    user_events: UserEvents,
+    fullscreen: RefCell<Option<Fullscreen>>,
+    /// The style, position and size of the window, before it became fullscreen.
+    windowed: Cell<Option<(isize, [i32; 4])>>,
+}
+
+impl WindowImpl for Window {
+    #[inline]
+    fn create(
+        event_loop: &EventLoop,
+        attributes: &WindowAttributes,
+        eventsys: Arc<dyn events::EventSystem>,
+    ) -> Result<Self, Error> {
+        if attributes.title.contains('\0') {
+            return Err(Error::InvalidTitle);
         }
 
+        // GDI ignores the alpha of the presented pixels
+        if attributes.transparent {
+            return Err(Error::Unsupported("transparent windows on Windows"));
+        }
+
+        let parent = match attributes.parent {
+            None => null_mut(),
+            Some(RawWindowHandle::Win32 { hwnd, .. }) => hwnd as HWND,
+            Some(_) => {
+                return Err(Error::Unsupported(
+                    "parent windows of another windowing system",
+                ));
+            }
+        };
+
+        let user_events = UserEvents::default();
+        let userdata = UserData::new(eventsys, Arc::clone(&user_events));
+        userdata.set_size_limits(attributes.min_size, attributes.max_size);
+
+        let instance = event_loop.instance;
+
+        let class_name = win32_string(CLASS_NAME);
+
         // calculate window sizes
-        let window_styles = WS_OVERLAPPED
-            | WS_SYSMENU
-            | WS_CAPTION
-            | WS_MAXIMIZEBOX
-            | WS_MINIMIZEBOX
-            | WS_THICKFRAME;
-        let windows_styles_ex = WS_EX_APPWINDOW;
-
-        let mut window_pos_x = i32::from(window_pos_x);
-        let mut window_pos_y = i32::from(window_pos_y);
-        let mut window_width = i32::from(window_width);
-        let mut window_height = i32::from(window_height);
+        let window_styles = window_style(attributes);
+        let windows_styles_ex = if attributes.always_on_top {
+            WS_EX_APPWINDOW | WS_EX_TOPMOST
+        } else {
+            WS_EX_APPWINDOW
+        };
+
+        let mut window_width = i32::from(attributes.size.x);
+        let mut window_height = i32::from(attributes.size.y);
         let mut rect = RECT {
             left: 0,
             top: 0,
@@ -115,24 +303,28 @@ impl WindowImpl for Window {
             )
         };
 
-        window_pos_x += rect.left;
-        window_pos_y += rect.top;
+        // the position of the client area is given, the frame goes around it
+        let (window_pos_x, window_pos_y) = attributes
+            .position
+            .map_or((CW_USEDEFAULT, CW_USEDEFAULT), |(x, y)| {
+                (i32::from(x) + rect.left, i32::from(y) + rect.top)
+            });
         window_width += rect.right - rect.left;
         window_height += rect.bottom - rect.top;
 
-        let title = win32_string(title);
+        let title = win32_string(&attributes.title);
 
         let hwnd = unsafe {
             CreateWindowExW(
-                0,
-                wnd_class.lpszClassName,
+                windows_styles_ex,
+                class_name.as_ptr(),
                 title.as_ptr(),
-                WS_OVERLAPPEDWINDOW,
+                window_styles,
                 window_pos_x,
                 window_pos_y,
                 window_width,
                 window_height,
-                null_mut(),
+                parent,
                 null_mut(),
                 instance,
                 null_mut(),
@@ -151,10 +343,21 @@ impl WindowImpl for Window {
         let window = Self {
             handle: hwnd,
             instance,
+            user_events,
+            fullscreen: RefCell::new(None),
+            windowed: Cell::new(None),
         };
 
         window.set_user_data(Box::new(userdata));
 
+        if attributes.fullscreen.is_some() {
+            window.set_fullscreen(attributes.fullscreen.clone())?;
+        }
+
+        if attributes.visible {
+            window.show()?;
+        }
+
         Ok(window)
     }
 
@@ -173,7 +376,101 @@ impl WindowImpl for Window {
         Ok(())
     }
 
+    fn set_visible(&self, visible: bool) -> Result<(), Error> {
+        if visible {
+            return self.show();
+        }
+
+        let _ = unsafe { ShowWindow(self.handle, SW_HIDE) };
+
+        Ok(())
+    }
+
+    fn set_minimized(&self, minimized: bool) -> Result<(), Error> {
+        let command = if minimized { SW_MINIMIZE } else { SW_RESTORE };
+        let _ = unsafe { ShowWindow(self.handle, command) };
+
+        Ok(())
+    }
+
+    fn set_maximized(&self, maximized: bool) -> Result<(), Error> {
+        let command = if maximized { SW_MAXIMIZE } else { SW_RESTORE };
+        let _ = unsafe { ShowWindow(self.handle, command) };
+
+        Ok(())
+    }
+
+    fn is_maximized(&self) -> bool {
+        unsafe { IsZoomed(self.handle) != 0 }
+    }
+
+    fn focus_window(&self) -> Result<(), Error> {
+        // fails, if another application has the focus, the taskbar entry flashes instead
+        let _ = unsafe { SetForegroundWindow(self.handle) };
+
+        Ok(())
+    }
+
+    fn set_window_level(&self, level: WindowLevel) -> Result<(), Error> {
+        if let Some(userdata) = Self::get_user_data(self.handle) {
+            userdata.set_window_level(level);
+            std::mem::forget(userdata);
+        }
+
+        let insert_after = match level {
+            WindowLevel::Normal => HWND_NOTOPMOST,
+            WindowLevel::AlwaysOnTop => HWND_TOPMOST,
+            WindowLevel::AlwaysOnBottom => HWND_BOTTOM,
+        };
+
+        let res = unsafe {
+            SetWindowPos(
+                self.handle,
+                insert_after,
+                0,
+                0,
+                0,
+                0,
+                SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE,
+            )
+        };
+
+        // If the function fails, the return value is zero.
+        if res == 0 {
+            return Err(Error::SetWindowPos(std::io::Error::last_os_error()));
+        }
+
+        Ok(())
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn request_user_attention(&self, attention: Option<UserAttentionType>) -> Result<(), Error> {
+        // both flash until the window is in the foreground
+        let (flags, count) = match attention {
+            None => (FLASHW_STOP, 0),
+            Some(UserAttentionType::Critical) => (FLASHW_ALL | FLASHW_TIMERNOFG, u32::MAX),
+            Some(UserAttentionType::Informational) => (FLASHW_TRAY | FLASHW_TIMERNOFG, 0),
+        };
+
+        let info = FLASHWINFO {
+            cbSize: size_of::<FLASHWINFO>() as u32,
+            hwnd: self.handle,
+            dwFlags: flags,
+            uCount: count,
+            dwTimeout: 0,
+        };
+
+        // the return value is the previous state of the window, not an error
+        unsafe { FlashWindowEx(&raw const info) };
+
+        Ok(())
+    }
+
     fn set_title(&self, title: &str) -> Result<(), Error> {
+        if title.contains('\0') {
+            return Err(Error::InvalidTitle);
+        }
+
         let title = win32_string(title);
 
         let ret = unsafe { SetWindowTextW(self.handle, title.as_ptr()) };
@@ -188,7 +485,22 @@ impl WindowImpl for Window {
         Ok(())
     }
 
+    #[allow(clippy::cast_sign_loss)]
+    fn title(&self) -> String {
+        let len = unsafe { GetWindowTextLengthW(self.handle) };
+
+        // with room for the terminating NUL
+        let mut title = vec![0u16; len as usize + 1];
+        let copied = unsafe { GetWindowTextW(self.handle, title.as_mut_ptr(), len + 1) };
+
+        String::from_utf16_lossy(&title[..copied as usize])
+    }
+
     fn destroy(self) {
+        if let Some(Fullscreen::Exclusive(mode)) = self.fullscreen.take() {
+            _ = monitor::restore_video_mode(&mode.monitor);
+        }
+
         let userdata = Self::get_user_data(self.handle);
         drop(userdata);
 
@@ -217,6 +529,19 @@ impl WindowImpl for Window {
         Ok(())
     }
 
+    fn wait_events(&mut self, timeout: Option<Duration>) -> Result<(), Error> {
+        wait_for_messages(timeout)?;
+
+        self.pump_messages()
+    }
+
+    fn create_proxy(&self) -> EventLoopProxy {
+        EventLoopProxy {
+            handle: self.handle as isize,
+            user_events: Arc::clone(&self.user_events),
+        }
+    }
+
     #[inline]
     #[allow(clippy::cast_possible_truncation)]
     fn inner_size(&self) -> Rect {
@@ -238,6 +563,348 @@ impl WindowImpl for Window {
         }
     }
 
+    fn set_inner_size(&self, size: Rect) -> Result<(), Error> {
+        let (width, height) = frame_size(self.handle, size);
+
+        self.set_window_pos(0, 0, width, height, SWP_NOMOVE)
+    }
+
+    fn outer_position(&self) -> Result<PhysicalPosition, Error> {
+        let rect = self.window_rect();
+
+        Ok(PhysicalPosition::new(rect.left, rect.top))
+    }
+
+    fn set_outer_position(&self, position: PhysicalPosition) -> Result<(), Error> {
+        self.set_window_pos(position.x, position.y, 0, 0, SWP_NOSIZE)
+    }
+
+    fn outer_size(&self) -> Rect {
+        let rect = self.window_rect();
+
+        Rect::new(
+            u16::try_from(rect.right - rect.left).unwrap_or(0),
+            u16::try_from(rect.bottom - rect.top).unwrap_or(0),
+        )
+    }
+
+    fn set_min_inner_size(&self, size: Option<Rect>) -> Result<(), Error> {
+        if let Some(userdata) = Self::get_user_data(self.handle) {
+            let (_, max_size) = userdata.size_limits();
+            userdata.set_size_limits(size, max_size);
+            std::mem::forget(userdata);
+        }
+
+        Ok(())
+    }
+
+    fn set_max_inner_size(&self, size: Option<Rect>) -> Result<(), Error> {
+        if let Some(userdata) = Self::get_user_data(self.handle) {
+            let (min_size, _) = userdata.size_limits();
+            userdata.set_size_limits(min_size, size);
+            std::mem::forget(userdata);
+        }
+
+        Ok(())
+    }
+
+    fn set_resize_increments(&self, increments: Option<Rect>) -> Result<(), Error> {
+        if let Some(userdata) = Self::get_user_data(self.handle) {
+            userdata.set_resize_increments(increments);
+            std::mem::forget(userdata);
+        }
+
+        Ok(())
+    }
+
+    fn set_aspect_ratio(&self, ratio: Option<(u32, u32)>) -> Result<(), Error> {
+        if let Some(userdata) = Self::get_user_data(self.handle) {
+            userdata.set_aspect_ratio(ratio);
+            std::mem::forget(userdata);
+        }
+
+        Ok(())
+    }
+
+    fn scale_factor(&self) -> f64 {
+        f64::from(unsafe { GetDpiForWindow(self.handle) }) / 96.0
+    }
+
+    // the whole client area is sent with the next `WM_PAINT`
+    fn request_redraw(&self) {
+        unsafe { InvalidateRect(self.handle, null(), 0) };
+    }
+
+    fn present_buffer(
+        &mut self,
+        buffer: &[u32],
+        width: u16,
+        height: u16,
+        damage: &[DamageRect],
+    ) -> Result<(), Error> {
+        let expected = usize::from(width) * usize::from(height);
+        if buffer.len() != expected {
+            return Err(Error::BufferSize {
+                expected,
+                actual: buffer.len(),
+            });
+        }
+
+        let hdc = unsafe { GetDC(self.handle) };
+
+        let mut res = Ok(());
+
+        for rect in damage.iter().filter_map(|rect| rect.clamp(width, height)) {
+            // describe a bitmap, that starts at the first row of rect
+            let info = bitmap_info(i32::from(width), i32::from(rect.height));
+
+            let rows = &buffer[usize::from(rect.y) * usize::from(width)..];
+
+            // If the function fails, the return value is zero.
+            let lines = unsafe {
+                StretchDIBits(
+                    hdc,
+                    i32::from(rect.x),
+                    i32::from(rect.y),
+                    i32::from(rect.width),
+                    i32::from(rect.height),
+                    i32::from(rect.x),
+                    0,
+                    i32::from(rect.width),
+                    i32::from(rect.height),
+                    rows.as_ptr().cast(),
+                    std::ptr::addr_of!(info),
+                    DIB_RGB_COLORS,
+                    SRCCOPY,
+                )
+            };
+
+            if lines == 0 {
+                res = Err(Error::Present(std::io::Error::last_os_error()));
+                break;
+            }
+        }
+
+        unsafe { ReleaseDC(self.handle, hdc) };
+
+        res
+    }
+
+    #[allow(clippy::cast_sign_loss)]
+    fn capture(&self) -> Result<Image, Error> {
+        let size = self.inner_size();
+        let (width, height) = (i32::from(size.x), i32::from(size.y));
+
+        let mut data = vec![0u8; usize::from(size.x) * usize::from(size.y) * 4];
+        let mut info = bitmap_info(width, height);
+
+        let lines = unsafe {
+            let hdc = GetDC(self.handle);
+            let mem_dc = CreateCompatibleDC(hdc);
+            let bitmap = CreateCompatibleBitmap(hdc, width, height);
+
+            // copy the client area into the bitmap
+            let previous = SelectObject(mem_dc, bitmap);
+            BitBlt(mem_dc, 0, 0, width, height, hdc, 0, 0, SRCCOPY);
+            SelectObject(mem_dc, previous);
+
+            // If the function fails, the return value is zero.
+            let lines = GetDIBits(
+                mem_dc,
+                bitmap,
+                0,
+                height as u32,
+                data.as_mut_ptr().cast(),
+                std::ptr::addr_of_mut!(info),
+                DIB_RGB_COLORS,
+            );
+
+            DeleteObject(bitmap);
+            DeleteDC(mem_dc);
+            ReleaseDC(self.handle, hdc);
+
+            lines
+        };
+
+        if lines == 0 && height != 0 {
+            let err = std::io::Error::last_os_error();
+            return Err(Error::Capture(err));
+        }
+
+        // BGRX -> RGBA
+        for pixel in data.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+            pixel[3] = u8::MAX;
+        }
+
+        Ok(Image {
+            width: size.x,
+            height: size.y,
+            data,
+        })
+    }
+
+    fn create_input_injector(&self) -> Result<InputInjector, Error> {
+        Ok(InputInjector::new(self.handle))
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn set_raw_mouse_motion(&self, enabled: bool) -> Result<(), Error> {
+        // raw input goes to a single window per process, removing it doesn't take a window
+        let (flags, target) = if enabled {
+            (0, self.handle)
+        } else {
+            (RIDEV_REMOVE, null_mut())
+        };
+
+        let device = RAWINPUTDEVICE {
+            usUsagePage: HID_USAGE_PAGE_GENERIC,
+            usUsage: HID_USAGE_GENERIC_MOUSE,
+            dwFlags: flags,
+            hwndTarget: target,
+        };
+
+        let registered = unsafe {
+            RegisterRawInputDevices(&raw const device, 1, size_of::<RAWINPUTDEVICE>() as u32)
+        };
+
+        if registered == 0 {
+            return Err(Error::RawInput(std::io::Error::last_os_error()));
+        }
+
+        Ok(())
+    }
+
+    fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), Error> {
+        if let Some(userdata) = Self::get_user_data(self.handle) {
+            userdata.set_cursor_grab(mode);
+            std::mem::forget(userdata);
+        }
+
+        // the clip is applied once the window is activated
+        if unsafe { GetForegroundWindow() } != self.handle {
+            return Ok(());
+        }
+
+        cursor::clip_cursor(self.handle, mode)
+    }
+
+    fn set_cursor_visible(&self, visible: bool) -> Result<(), Error> {
+        if let Some(userdata) = Self::get_user_data(self.handle) {
+            userdata.set_cursor_visible(visible);
+            std::mem::forget(userdata);
+        }
+
+        self.update_cursor();
+
+        Ok(())
+    }
+
+    fn set_cursor(&self, icon: CursorIcon) -> Result<(), Error> {
+        let cursor = cursor::load_cursor(icon)?;
+
+        if let Some(userdata) = Self::get_user_data(self.handle) {
+            userdata.set_cursor(cursor, false);
+            std::mem::forget(userdata);
+        }
+
+        self.update_cursor();
+
+        Ok(())
+    }
+
+    fn set_custom_cursor(
+        &self,
+        rgba: &[u8],
+        width: u16,
+        height: u16,
+        hotspot: (u16, u16),
+    ) -> Result<(), Error> {
+        let cursor = cursor::create_custom_cursor(rgba, width, height, hotspot)?;
+
+        match Self::get_user_data(self.handle) {
+            Some(userdata) => {
+                userdata.set_cursor(cursor, true);
+                std::mem::forget(userdata);
+            }
+            None => unsafe {
+                DestroyCursor(cursor);
+            },
+        }
+
+        self.update_cursor();
+
+        Ok(())
+    }
+
+    fn set_icon(&self, icon: Option<Icon>) -> Result<(), Error> {
+        let icons = icon::set_icon(self.handle, icon.as_ref())?;
+
+        match Self::get_user_data(self.handle) {
+            Some(userdata) => {
+                userdata.set_icons(icons);
+                std::mem::forget(userdata);
+            }
+            None => icon::destroy_icons(icons),
+        }
+
+        Ok(())
+    }
+
+    fn set_fullscreen(&self, fullscreen: Option<Fullscreen>) -> Result<(), Error> {
+        if let Some(Fullscreen::Exclusive(mode)) = &*self.fullscreen.borrow() {
+            monitor::restore_video_mode(&mode.monitor)?;
+        }
+
+        let monitor = match &fullscreen {
+            None => None,
+            Some(Fullscreen::Borderless(Some(monitor))) => Some(monitor.id),
+            Some(Fullscreen::Borderless(None)) => {
+                monitor::current_monitor(self.handle).map(|monitor| monitor.id)
+            }
+            Some(Fullscreen::Exclusive(mode)) => {
+                monitor::set_video_mode(mode)?;
+                Some(mode.monitor)
+            }
+        };
+
+        match monitor {
+            Some(id) => {
+                // the monitor moves, when its size changed
+                let rect = monitor::monitor_rect(&id)
+                    .ok_or(Error::Unsupported("fullscreen on a disconnected monitor"))?;
+
+                self.enter_fullscreen(rect);
+            }
+            None => self.leave_fullscreen(),
+        }
+
+        self.fullscreen.replace(fullscreen);
+
+        Ok(())
+    }
+
+    fn fullscreen(&self) -> Option<Fullscreen> {
+        self.fullscreen.borrow().clone()
+    }
+
+    fn current_monitor(&self) -> Option<MonitorHandle> {
+        monitor::current_monitor(self.handle)
+    }
+
+    fn available_monitors(&self) -> Vec<MonitorHandle> {
+        monitor::monitors()
+    }
+
+    fn primary_monitor(&self) -> Option<MonitorHandle> {
+        monitor::primary_monitor()
+    }
+
+    #[inline]
+    fn id(&self) -> WindowId {
+        WindowId::from_raw(self.handle as u64)
+    }
+
     #[inline]
     fn raw_handle(&self) -> RawWindowHandle {
         RawWindowHandle::Win32 {
@@ -260,6 +927,105 @@ impl Window {
         assert_eq!(res, 0);
     }
 
+    /// Moves or resizes the window, without changing its z-order or activating it.
+    fn set_window_pos(
+        &self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        flags: u32,
+    ) -> Result<(), Error> {
+        let res = unsafe {
+            SetWindowPos(
+                self.handle,
+                null_mut(),
+                x,
+                y,
+                width,
+                height,
+                flags | SWP_NOZORDER | SWP_NOACTIVATE,
+            )
+        };
+
+        // If the function fails, the return value is zero.
+        if res == 0 {
+            return Err(Error::SetWindowPos(std::io::Error::last_os_error()));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the area of the window including its frame, in screen coordinates.
+    fn window_rect(&self) -> RECT {
+        let mut rect: RECT = unsafe { std::mem::zeroed() };
+        unsafe { GetWindowRect(self.handle, &raw mut rect) };
+
+        rect
+    }
+
+    /// Removes the frame and covers the monitor area.
+    #[allow(clippy::cast_possible_wrap)]
+    fn enter_fullscreen(&self, rect: RECT) {
+        let style = unsafe { GetWindowLongPtrW(self.handle, GWL_STYLE) };
+
+        if self.windowed.get().is_none() {
+            let mut rect: RECT = unsafe { std::mem::zeroed() };
+            unsafe { GetWindowRect(self.handle, &raw mut rect) };
+
+            let windowed = [
+                rect.left,
+                rect.top,
+                rect.right - rect.left,
+                rect.bottom - rect.top,
+            ];
+            self.windowed.set(Some((style, windowed)));
+        }
+
+        let style = (style & !(WS_OVERLAPPEDWINDOW as isize)) | WS_POPUP as isize;
+
+        unsafe {
+            SetWindowLongPtrW(self.handle, GWL_STYLE, style);
+            SetWindowPos(
+                self.handle,
+                HWND_TOP,
+                rect.left,
+                rect.top,
+                rect.right - rect.left,
+                rect.bottom - rect.top,
+                SWP_FRAMECHANGED | SWP_NOOWNERZORDER,
+            );
+        }
+    }
+
+    /// Restores the frame and position from before `enter_fullscreen`.
+    fn leave_fullscreen(&self) {
+        let Some((style, [x, y, width, height])) = self.windowed.take() else {
+            return;
+        };
+
+        unsafe {
+            SetWindowLongPtrW(self.handle, GWL_STYLE, style);
+            SetWindowPos(
+                self.handle,
+                null_mut(),
+                x,
+                y,
+                width,
+                height,
+                SWP_FRAMECHANGED | SWP_NOOWNERZORDER | SWP_NOZORDER,
+            );
+        }
+    }
+
+    /// Shows the cursor of this window right away, instead of on its next `WM_SETCURSOR`.
+    fn update_cursor(&self) {
+        if let Some(userdata) = Self::get_user_data(self.handle) {
+            unsafe { SetCursor(userdata.cursor()) };
+            std::mem::forget(userdata);
+        }
+    }
+
     #[inline]
     fn get_user_data(handle: HWND) -> Option<Box<UserData>> {
         let ptr = unsafe { GetWindowLongPtrW(handle, GWLP_USERDATA) } as *mut UserData;
@@ -274,6 +1040,67 @@ impl Window {
     }
 }
 
+/// Describes a top-down XRGB bitmap.
+#[allow(clippy::cast_possible_truncation)]
+const fn bitmap_info(width: i32, height: i32) -> BITMAPINFO {
+    let mut info: BITMAPINFO = unsafe { std::mem::zeroed() };
+
+    info.bmiHeader = BITMAPINFOHEADER {
+        biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+        biWidth: width,
+        // negative height for top-down rows
+        biHeight: -height,
+        biPlanes: 1,
+        biBitCount: 32,
+        biCompression: BI_RGB,
+        ..info.bmiHeader
+    };
+
+    info
+}
+
+/// Blocks until a message arrives in the queue of the current thread or `timeout` expires.
+fn wait_for_messages(timeout: Option<Duration>) -> Result<(), Error> {
+    // round up, so short timeouts don't turn into busy waiting
+    let timeout = timeout.map_or(INFINITE, |timeout| {
+        u32::try_from(timeout.as_micros().div_ceil(1000))
+            .map_or(INFINITE - 1, |ms| ms.min(INFINITE - 1))
+    });
+
+    let res = unsafe {
+        MsgWaitForMultipleObjectsEx(0, null(), timeout, QS_ALLINPUT, MWMO_INPUTAVAILABLE)
+    };
+
+    if res == WAIT_FAILED {
+        let err = std::io::Error::last_os_error();
+        return Err(Error::Wait(err));
+    }
+
+    Ok(())
+}
+
+/// Returns the size of a window with a client area of `size`, including its frame.
+fn frame_size(hwnd: HWND, size: Rect) -> (i32, i32) {
+    let mut rect = RECT {
+        left: 0,
+        top: 0,
+        right: i32::from(size.x),
+        bottom: i32::from(size.y),
+    };
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    unsafe {
+        AdjustWindowRectEx(
+            &raw mut rect,
+            GetWindowLongPtrW(hwnd, GWL_STYLE) as u32,
+            0,
+            GetWindowLongPtrW(hwnd, GWL_EXSTYLE) as u32,
+        );
+    }
+
+    (rect.right - rect.left, rect.bottom - rect.top)
+}
+
 #[inline]
 fn win32_string(str: &str) -> Vec<u16> {
     std::ffi::OsStr::new(str)
@@ -282,7 +1109,11 @@ fn win32_string(str: &str) -> Vec<u16> {
         .collect()
 }
 
-#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::too_many_lines
+)]
 unsafe extern "system" fn process_messages(
     hwnd: HWND,
     msg: u32,
@@ -297,7 +1128,41 @@ unsafe extern "system" fn process_messages(
             return DefWindowProcW(hwnd, msg, wparam, lparam);
         };
 
+        let id = WindowId::from_raw(hwnd as u64);
+
         let callback = || match msg {
+            // windows merges the damage, until the message queue is empty
+            WM_PAINT => {
+                let mut paint: PAINTSTRUCT = std::mem::zeroed();
+                BeginPaint(hwnd, &raw mut paint);
+                EndPaint(hwnd, &raw const paint);
+
+                let to_u16 = |value: i32| u16::try_from(value.max(0)).unwrap_or(u16::MAX);
+                let RECT {
+                    left,
+                    top,
+                    right,
+                    bottom,
+                } = paint.rcPaint;
+
+                if left < right && top < bottom {
+                    let damage = DamageRect::new(
+                        to_u16(left),
+                        to_u16(top),
+                        to_u16(right - left),
+                        to_u16(bottom - top),
+                    );
+
+                    userdata.events().invoke_window_event(
+                        id,
+                        events::WindowEvent::RedrawRequested {
+                            damage: Some(damage),
+                        },
+                    );
+                }
+
+                0
+            }
             WM_ERASEBKGND => {
                 // erasing the screen will be handled by application
                 1
@@ -306,7 +1171,129 @@ unsafe extern "system" fn process_messages(
             WM_CLOSE => {
                 userdata
                     .events()
-                    .invoke_window_event(events::WindowEvent::CloseWindow);
+                    .invoke_window_event(id, events::WindowEvent::CloseWindow);
+
+                0
+            }
+            // woken up by an `EventLoopProxy`
+            WM_WAKE_UP => {
+                for e in userdata.take_user_events() {
+                    userdata.events().invoke_user_event(id, e);
+                }
+
+                0
+            }
+            // moved to a monitor with another DPI, or the DPI of the monitor changed
+            WM_DPICHANGED => {
+                // the system suggests a size, that keeps the logical size of the window
+                let rect = &*(lparam as *const RECT);
+                SetWindowPos(
+                    hwnd,
+                    null_mut(),
+                    rect.left,
+                    rect.top,
+                    rect.right - rect.left,
+                    rect.bottom - rect.top,
+                    SWP_NOZORDER | SWP_NOACTIVATE,
+                );
+
+                let dpi = f64::from(wparam as u16);
+                userdata.events().invoke_window_event(
+                    id,
+                    events::WindowEvent::ScaleFactorChanged {
+                        scale_factor: dpi / 96.0,
+                    },
+                );
+
+                0
+            }
+            // the size limits are for the client area, the system's are for the whole window
+            WM_GETMINMAXINFO => {
+                let info = &mut *(lparam as *mut MINMAXINFO);
+                let (min_size, max_size) = userdata.size_limits();
+
+                if let Some(size) = min_size {
+                    let (width, height) = frame_size(hwnd, size);
+                    info.ptMinTrackSize = POINT {
+                        x: width,
+                        y: height,
+                    };
+                }
+                if let Some(size) = max_size {
+                    let (width, height) = frame_size(hwnd, size);
+                    info.ptMaxTrackSize = POINT {
+                        x: width,
+                        y: height,
+                    };
+                }
+
+                0
+            }
+            // snaps the size, while the user drags the frame
+            WM_SIZING => {
+                let rect = &mut *(lparam as *mut RECT);
+                let edge = wparam as u32;
+
+                let (frame_width, frame_height) = frame_size(hwnd, Rect::new(0, 0));
+                let mut width = rect.right - rect.left - frame_width;
+                let mut height = rect.bottom - rect.top - frame_height;
+
+                if let Some(increments) = userdata.resize_increments() {
+                    let snap = |size: i32, step: u16| {
+                        let step = i32::from(step.max(1));
+                        (size - size % step).max(step)
+                    };
+
+                    width = snap(width, increments.x);
+                    height = snap(height, increments.y);
+                }
+
+                if let Some((ratio_width, ratio_height)) = userdata.aspect_ratio() {
+                    let scale = |size: i32, numerator: u32, denominator: u32| {
+                        let size = i64::from(size) * i64::from(numerator) / i64::from(denominator);
+                        i32::try_from(size).unwrap_or(i32::MAX)
+                    };
+
+                    // the top and bottom edge change the height, all others the width
+                    if matches!(edge, WMSZ_TOP | WMSZ_BOTTOM) {
+                        width = scale(height, ratio_width, ratio_height);
+                    } else {
+                        height = scale(width, ratio_height, ratio_width);
+                    }
+                }
+
+                // the edges, that are not dragged, stay in place
+                if matches!(edge, WMSZ_LEFT | WMSZ_TOPLEFT | WMSZ_BOTTOMLEFT) {
+                    rect.left = rect.right - width - frame_width;
+                } else {
+                    rect.right = rect.left + width + frame_width;
+                }
+                if matches!(edge, WMSZ_TOP | WMSZ_TOPLEFT | WMSZ_TOPRIGHT) {
+                    rect.top = rect.bottom - height - frame_height;
+                } else {
+                    rect.bottom = rect.top + height + frame_height;
+                }
+
+                1
+            }
+            // keeps a window at the bottom, whenever it would be raised
+            WM_WINDOWPOSCHANGING => {
+                let position = &mut *(lparam as *mut WINDOWPOS);
+
+                if userdata.window_level() == WindowLevel::AlwaysOnBottom
+                    && position.flags & SWP_NOZORDER == 0
+                {
+                    position.hwndInsertAfter = HWND_BOTTOM;
+                }
+
+                // sends WM_GETMINMAXINFO
+                DefWindowProcW(hwnd, msg, wparam, lparam)
+            }
+            // sent to every top level window, when a monitor changed
+            WM_DISPLAYCHANGE => {
+                userdata
+                    .events()
+                    .invoke_window_event(id, events::WindowEvent::MonitorsChanged);
 
                 0
             }
@@ -314,8 +1301,58 @@ unsafe extern "system" fn process_messages(
                 PostQuitMessage(0);
                 0
             }
+            // the clip of the cursor is released with the focus
+            WM_SETFOCUS => {
+                if let Err(err) = cursor::clip_cursor(hwnd, userdata.cursor_grab()) {
+                    log::warn!("{err}");
+                }
+
+                userdata
+                    .events()
+                    .invoke_window_event(id, events::WindowEvent::Focused(true));
+
+                0
+            }
+            WM_KILLFOCUS => {
+                if userdata.cursor_grab() != CursorGrabMode::None {
+                    _ = cursor::clip_cursor(hwnd, CursorGrabMode::None);
+                }
+
+                userdata
+                    .events()
+                    .invoke_window_event(id, events::WindowEvent::Focused(false));
+
+                0
+            }
+            WM_MOVE => {
+                if userdata.cursor_grab() != CursorGrabMode::None && GetForegroundWindow() == hwnd {
+                    _ = cursor::clip_cursor(hwnd, userdata.cursor_grab());
+                }
+
+                // minimized windows are moved off the desktop
+                if IsIconic(hwnd) == 0 {
+                    // the signed position of the client area
+                    let x = i32::from(lparam as i16);
+                    let y = i32::from((lparam >> 16) as i16);
+
+                    userdata
+                        .events()
+                        .invoke_window_event(id, events::WindowEvent::Moved { x, y });
+                }
+
+                0
+            }
+            // the cursor of the client area, the frame keeps the default cursors
+            WM_SETCURSOR if u32::from(lparam as u16) == HTCLIENT => {
+                SetCursor(userdata.cursor());
+                1
+            }
             // resize
             WM_SIZE => {
+                if userdata.cursor_grab() != CursorGrabMode::None && GetForegroundWindow() == hwnd {
+                    _ = cursor::clip_cursor(hwnd, userdata.cursor_grab());
+                }
+
                 let mut rect: RECT = std::mem::zeroed();
                 GetClientRect(hwnd, std::ptr::addr_of_mut!(rect));
                 let width: u32 = (rect.right - rect.left).try_into().unwrap();
@@ -323,7 +1360,23 @@ unsafe extern "system" fn process_messages(
 
                 userdata
                     .events()
-                    .invoke_window_resize_event(WindowResizeEvent { width, height });
+                    .invoke_window_resize_event(id, WindowResizeEvent { width, height });
+
+                // a minimized window is hidden, it's restored by any other size
+                let minimized = wparam as u32 == SIZE_MINIMIZED;
+
+                if userdata.set_minimized(minimized) {
+                    let event = if minimized {
+                        events::WindowEvent::Minimized
+                    } else {
+                        events::WindowEvent::Restored
+                    };
+
+                    userdata.events().invoke_window_event(id, event);
+                    userdata
+                        .events()
+                        .invoke_window_event(id, events::WindowEvent::Occluded(minimized));
+                }
 
                 0
             }
@@ -336,37 +1389,120 @@ unsafe extern "system" fn process_messages(
 
                 let repeat = ((lparam & 30) as u32).clamp(0, 1);
 
-                userdata
-                    .events()
-                    .invoke_key_event(KeyEvent { key, down, repeat });
+                let modifiers = userdata.modifiers();
+
+                userdata.events().invoke_key_event(
+                    id,
+                    KeyEvent {
+                        key,
+                        down,
+                        repeat,
+                        modifiers,
+                    },
+                );
+
+                // the key state already includes this key
+                userdata.update_modifiers(id, current_modifiers());
+
+                0
+            }
+            // raw mouse motion, after `set_raw_mouse_motion`
+            WM_INPUT => {
+                if let Some((dx, dy)) = read_raw_motion(lparam as HRAWINPUT) {
+                    userdata.events().invoke_raw_mouse_motion_event(
+                        id,
+                        RawMouseMotionEvent {
+                            dx: f64::from(dx),
+                            dy: f64::from(dy),
+                        },
+                    );
+                }
+
+                // lets the system clean up the input
+                DefWindowProcW(hwnd, msg, wparam, lparam)
+            }
+            // text input, translated from key presses by `TranslateMessage`
+            WM_CHAR => {
+                if let Some(c) = userdata.decode_char(wparam as u16)
+                    && !c.is_control()
+                {
+                    userdata.events().invoke_text_event(
+                        id,
+                        TextEvent {
+                            text: c.to_string(),
+                        },
+                    );
+                }
 
                 0
             }
             // mouse move
             WM_MOUSEMOVE => {
+                // windows only reports the cursor leaving, after it was asked to
+                if userdata.set_cursor_inside(true) {
+                    let mut track = TRACKMOUSEEVENT {
+                        cbSize: size_of::<TRACKMOUSEEVENT>() as u32,
+                        dwFlags: TME_LEAVE,
+                        hwndTrack: hwnd,
+                        dwHoverTime: 0,
+                    };
+                    TrackMouseEvent(&raw mut track);
+
+                    userdata
+                        .events()
+                        .invoke_window_event(id, events::WindowEvent::CursorEntered);
+                }
+
                 let x_pos = get_x_lparam(lparam);
                 let y_pos = get_y_lparam(lparam);
+                let modifiers = userdata.update_modifiers(id, current_modifiers());
+
+                userdata.events().invoke_mouse_move_event(
+                    id,
+                    MouseMoveEvent {
+                        x_pos,
+                        y_pos,
+                        modifiers,
+                    },
+                );
+
+                0
+            }
+            WM_MOUSELEAVE => {
+                userdata.set_cursor_inside(false);
 
                 userdata
                     .events()
-                    .invoke_mouse_move_event(MouseMoveEvent { x_pos, y_pos });
+                    .invoke_window_event(id, events::WindowEvent::CursorLeft);
 
                 0
             }
             // mouse wheel
-            WM_MOUSEWHEEL => {
-                let delta = get_wheel_delta_wparam(wparam);
-
-                // flatten delta to (-1, 1)
-                let direction = match delta {
-                    z_delta if z_delta < 0 => MouseWheelDirection::Down,
-                    z_delta if z_delta > 0 => MouseWheelDirection::Up,
-                    _ => return 0,
+            WM_MOUSEWHEEL | WM_MOUSEHWHEEL => {
+                // a notch is WHEEL_DELTA, high-resolution wheels send fractions of it
+                let lines = f64::from(get_wheel_delta_wparam(wparam)) / f64::from(WHEEL_DELTA);
+
+                // a positive horizontal delta scrolls right
+                let delta = if msg == WM_MOUSEWHEEL {
+                    ScrollDelta::Lines { x: 0.0, y: lines }
+                } else {
+                    ScrollDelta::Lines { x: lines, y: 0.0 }
                 };
 
-                userdata
-                    .events()
-                    .invoke_mouse_wheel_event(MouseWheelEvent { direction });
+                let Some(direction) = delta.direction() else {
+                    return 0;
+                };
+
+                let modifiers = userdata.update_modifiers(id, current_modifiers());
+
+                userdata.events().invoke_mouse_wheel_event(
+                    id,
+                    MouseWheelEvent {
+                        direction,
+                        delta,
+                        modifiers,
+                    },
+                );
 
                 0
             }
@@ -382,9 +1518,16 @@ unsafe extern "system" fn process_messages(
                     _ => MouseButton::Middle,
                 };
 
-                userdata
-                    .events()
-                    .invoke_mouse_button_event(MouseButtonEvent { down, button });
+                let modifiers = userdata.update_modifiers(id, current_modifiers());
+
+                userdata.events().invoke_mouse_button_event(
+                    id,
+                    MouseButtonEvent {
+                        down,
+                        button,
+                        modifiers,
+                    },
+                );
 
                 0
             }
@@ -400,6 +1543,62 @@ unsafe extern "system" fn process_messages(
     }
 }
 
+/// Returns the relative motion of a `WM_INPUT` message.
+#[allow(clippy::cast_possible_truncation)]
+fn read_raw_motion(input: HRAWINPUT) -> Option<(i32, i32)> {
+    let mut raw: RAWINPUT = unsafe { std::mem::zeroed() };
+    let mut size = size_of::<RAWINPUT>() as u32;
+
+    let read = unsafe {
+        GetRawInputData(
+            input,
+            RID_INPUT,
+            std::ptr::addr_of_mut!(raw).cast(),
+            &raw mut size,
+            size_of::<RAWINPUTHEADER>() as u32,
+        )
+    };
+
+    if read == u32::MAX || raw.header.dwType != RIM_TYPEMOUSE {
+        return None;
+    }
+
+    let mouse = unsafe { raw.data.mouse };
+
+    // tablets and remote desktops report absolute positions instead
+    if mouse.usFlags & MOUSE_MOVE_ABSOLUTE != 0 || (mouse.lLastX == 0 && mouse.lLastY == 0) {
+        return None;
+    }
+
+    Some((mouse.lLastX, mouse.lLastY))
+}
+
+/// Reads the modifiers from the key state of the message, that is currently processed.
+fn current_modifiers() -> Modifiers {
+    const MAPPING: [(u16, Modifiers); 5] = [
+        (VK_SHIFT, Modifiers::SHIFT),
+        (VK_CONTROL, Modifiers::CTRL),
+        (VK_MENU, Modifiers::ALT),
+        (VK_LWIN, Modifiers::SUPER),
+        (VK_RWIN, Modifiers::SUPER),
+    ];
+
+    // the high bit is set while a key is held down, the low bit while it is toggled on
+    let state = |key: u16| unsafe { GetKeyState(i32::from(key)) }.cast_unsigned();
+
+    let mut modifiers = MAPPING
+        .into_iter()
+        .filter(|(key, _)| state(*key) & 0x8000 != 0)
+        .fold(Modifiers::empty(), |modifiers, (_, modifier)| {
+            modifiers | modifier
+        });
+
+    modifiers.set(Modifiers::CAPS_LOCK, state(VK_CAPITAL) & 1 != 0);
+    modifiers.set(Modifiers::NUM_LOCK, state(VK_NUMLOCK) & 1 != 0);
+
+    modifiers
+}
+
 #[inline]
 #[allow(clippy::cast_possible_truncation)]
 #[allow(clippy::cast_sign_loss)]
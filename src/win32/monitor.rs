@@ -0,0 +1,218 @@
+use std::ptr::{null, null_mut};
+
+use windows_sys::Win32::{
+    Foundation::{BOOL, HWND, LPARAM, POINT, RECT},
+    Graphics::Gdi::{
+        CDS_FULLSCREEN, ChangeDisplaySettingsExW, CreateDCW, DEVMODEW, DISP_CHANGE_SUCCESSFUL,
+        DM_BITSPERPEL, DM_DISPLAYFREQUENCY, DM_PELSHEIGHT, DM_PELSWIDTH, DeleteDC,
+        ENUM_CURRENT_SETTINGS, EnumDisplayMonitors, EnumDisplaySettingsW, GET_DEVICE_CAPS_INDEX,
+        GetDeviceCaps, GetMonitorInfoW, HDC, HMONITOR, HORZSIZE, MONITOR_DEFAULTTONEAREST,
+        MONITOR_DEFAULTTOPRIMARY, MONITORINFO, MONITORINFOEXW, MonitorFromPoint, MonitorFromWindow,
+        VERTSIZE,
+    },
+    UI::HiDpi::{GetDpiForMonitor, MDT_EFFECTIVE_DPI},
+};
+
+use crate::{
+    Rect,
+    monitor::{MonitorHandle, VideoMode},
+};
+
+use super::Error;
+
+/// The device name of a monitor, like `\\.\DISPLAY1`, as a NUL terminated string.
+pub type MonitorId = [u16; 32];
+
+/// Returns all monitors of the desktop.
+pub fn monitors() -> Vec<MonitorHandle> {
+    unsafe extern "system" fn collect(
+        monitor: HMONITOR,
+        _: HDC,
+        _: *mut RECT,
+        monitors: LPARAM,
+    ) -> BOOL {
+        let monitors = unsafe { &mut *(monitors as *mut Vec<HMONITOR>) };
+        monitors.push(monitor);
+
+        // continue the enumeration
+        1
+    }
+
+    let mut handles: Vec<HMONITOR> = Vec::new();
+
+    unsafe {
+        EnumDisplayMonitors(
+            null_mut(),
+            null(),
+            Some(collect),
+            (&raw mut handles) as LPARAM,
+        );
+    }
+
+    handles.into_iter().filter_map(monitor_handle).collect()
+}
+
+/// Returns the monitor, that most of the window is on.
+pub fn current_monitor(hwnd: HWND) -> Option<MonitorHandle> {
+    monitor_handle(unsafe { MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST) })
+}
+
+/// Returns the monitor, that has the top left corner of the desktop.
+pub fn primary_monitor() -> Option<MonitorHandle> {
+    let origin = POINT { x: 0, y: 0 };
+
+    monitor_handle(unsafe { MonitorFromPoint(origin, MONITOR_DEFAULTTOPRIMARY) })
+}
+
+/// Returns the area of a monitor on the desktop, which changes with its mode.
+pub fn monitor_rect(id: &MonitorId) -> Option<RECT> {
+    monitors()
+        .into_iter()
+        .find(|monitor| monitor.id == *id)
+        .map(|monitor| {
+            let (left, top) = monitor.position;
+
+            RECT {
+                left,
+                top,
+                right: left + i32::from(monitor.size.x),
+                bottom: top + i32::from(monitor.size.y),
+            }
+        })
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn monitor_handle(monitor: HMONITOR) -> Option<MonitorHandle> {
+    let mut info: MONITORINFOEXW = unsafe { std::mem::zeroed() };
+    info.monitorInfo.cbSize = size_of::<MONITORINFOEXW>() as u32;
+
+    if unsafe { GetMonitorInfoW(monitor, (&raw mut info).cast::<MONITORINFO>()) } == 0 {
+        return None;
+    }
+
+    let id = info.szDevice;
+    let rect = info.monitorInfo.rcMonitor;
+
+    let name_len = id.iter().position(|c| *c == 0).unwrap_or(id.len());
+
+    let mut dpi = (96, 96);
+    unsafe { GetDpiForMonitor(monitor, MDT_EFFECTIVE_DPI, &raw mut dpi.0, &raw mut dpi.1) };
+
+    Some(MonitorHandle {
+        id,
+        name: String::from_utf16_lossy(&id[..name_len]),
+        position: (rect.left, rect.top),
+        size: Rect::new(
+            u16::try_from(rect.right - rect.left).unwrap_or(u16::MAX),
+            u16::try_from(rect.bottom - rect.top).unwrap_or(u16::MAX),
+        ),
+        physical_size_mm: physical_size_mm(&id),
+        scale_factor: f64::from(dpi.0) / 96.0,
+        refresh_rate_millihertz: refresh_rate(&id),
+        video_modes: video_modes(&id),
+    })
+}
+
+/// Returns the size of a display device in mm, as reported by the driver.
+#[allow(clippy::cast_possible_wrap)]
+fn physical_size_mm(id: &MonitorId) -> (u32, u32) {
+    let dc = unsafe { CreateDCW(id.as_ptr(), null(), null(), null()) };
+    if dc.is_null() {
+        return (0, 0);
+    }
+
+    let caps = |index: GET_DEVICE_CAPS_INDEX| {
+        u32::try_from(unsafe { GetDeviceCaps(dc, index as i32) }).unwrap_or(0)
+    };
+
+    let size = (caps(HORZSIZE), caps(VERTSIZE));
+
+    unsafe { DeleteDC(dc) };
+
+    size
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn refresh_rate(id: &MonitorId) -> Option<u32> {
+    let mut devmode: DEVMODEW = unsafe { std::mem::zeroed() };
+    devmode.dmSize = size_of::<DEVMODEW>() as u16;
+
+    if unsafe { EnumDisplaySettingsW(id.as_ptr(), ENUM_CURRENT_SETTINGS, &raw mut devmode) } == 0 {
+        return None;
+    }
+
+    // 0 and 1 stand for the default rate of the hardware
+    (devmode.dmDisplayFrequency > 1).then(|| devmode.dmDisplayFrequency * 1000)
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn video_modes(id: &MonitorId) -> Vec<VideoMode> {
+    let mut modes: Vec<VideoMode> = Vec::new();
+
+    let mut devmode: DEVMODEW = unsafe { std::mem::zeroed() };
+    devmode.dmSize = size_of::<DEVMODEW>() as u16;
+
+    for index in 0.. {
+        if unsafe { EnumDisplaySettingsW(id.as_ptr(), index, &raw mut devmode) } == 0 {
+            break;
+        }
+
+        let mode = VideoMode {
+            monitor: *id,
+            native: 0,
+            size: Rect::new(
+                u16::try_from(devmode.dmPelsWidth).unwrap_or(u16::MAX),
+                u16::try_from(devmode.dmPelsHeight).unwrap_or(u16::MAX),
+            ),
+            bit_depth: u16::try_from(devmode.dmBitsPerPel).unwrap_or(u16::MAX),
+            refresh_rate_millihertz: devmode.dmDisplayFrequency.saturating_mul(1000),
+        };
+
+        // modes only differing in scaling or orientation are listed multiple times
+        if !modes.contains(&mode) {
+            modes.push(mode);
+        }
+    }
+
+    modes
+}
+
+/// Switches the monitor of the mode, until `restore_video_mode` is called.
+#[allow(clippy::cast_possible_truncation)]
+pub fn set_video_mode(mode: &VideoMode) -> Result<(), Error> {
+    let mut devmode: DEVMODEW = unsafe { std::mem::zeroed() };
+    devmode.dmSize = size_of::<DEVMODEW>() as u16;
+    devmode.dmFields = DM_PELSWIDTH | DM_PELSHEIGHT | DM_BITSPERPEL | DM_DISPLAYFREQUENCY;
+    devmode.dmPelsWidth = u32::from(mode.size.x);
+    devmode.dmPelsHeight = u32::from(mode.size.y);
+    devmode.dmBitsPerPel = u32::from(mode.bit_depth);
+    devmode.dmDisplayFrequency = mode.refresh_rate_millihertz / 1000;
+
+    // a fullscreen change is undone by windows, once the application exits
+    let res = unsafe {
+        ChangeDisplaySettingsExW(
+            mode.monitor.as_ptr(),
+            &raw const devmode,
+            null_mut(),
+            CDS_FULLSCREEN,
+            null(),
+        )
+    };
+
+    if res != DISP_CHANGE_SUCCESSFUL {
+        return Err(Error::VideoMode(res));
+    }
+
+    Ok(())
+}
+
+/// Switches a monitor back to the mode stored in the registry.
+pub fn restore_video_mode(id: &MonitorId) -> Result<(), Error> {
+    let res = unsafe { ChangeDisplaySettingsExW(id.as_ptr(), null(), null_mut(), 0, null()) };
+
+    if res != DISP_CHANGE_SUCCESSFUL {
+        return Err(Error::VideoMode(res));
+    }
+
+    Ok(())
+}
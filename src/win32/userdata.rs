@@ -1,17 +1,200 @@
-use std::{borrow::Borrow, sync::Arc};
+use std::{
+    borrow::Borrow,
+    cell::Cell,
+    collections::VecDeque,
+    ptr::null_mut,
+    sync::{Arc, Mutex, PoisonError},
+};
 
-use crate::events::EventSystem;
+use windows_sys::Win32::UI::WindowsAndMessaging::{
+    DestroyCursor, HCURSOR, HICON, IDC_ARROW, LoadCursorW,
+};
+
+use crate::{
+    Rect, WindowId, WindowLevel,
+    cursor::CursorGrabMode,
+    events::{EventSystem, ModifiersChangedEvent, UserEvent},
+    inputs::Modifiers,
+};
+
+use super::icon::destroy_icons;
 
 pub struct UserData {
     events: Arc<dyn EventSystem + 'static>,
+    user_events: Arc<Mutex<VecDeque<UserEvent>>>,
+    high_surrogate: Cell<Option<u16>>,
+    modifiers: Cell<Modifiers>,
+    cursor_grab: Cell<CursorGrabMode>,
+    cursor_visible: Cell<bool>,
+    cursor: Cell<HCURSOR>,
+    /// Created by `set_custom_cursor`, destroyed once it's replaced.
+    custom_cursor: Cell<HCURSOR>,
+    /// The big and small icon created by `set_icon`.
+    icons: Cell<[HICON; 2]>,
+    /// The minimum and maximum size of the client area.
+    size_limits: Cell<(Option<Rect>, Option<Rect>)>,
+    /// The steps, that the client area is resized in by the user.
+    resize_increments: Cell<Option<Rect>>,
+    /// The width and height, that the client area keeps the ratio of.
+    aspect_ratio: Cell<Option<(u32, u32)>>,
+    window_level: Cell<WindowLevel>,
+    /// Set by the first `WM_MOUSEMOVE`, until the `WM_MOUSELEAVE` that was requested for it.
+    cursor_inside: Cell<bool>,
+    minimized: Cell<bool>,
 }
 
 impl UserData {
-    pub fn new(events: Arc<dyn EventSystem>) -> Self {
-        Self { events }
+    pub fn new(events: Arc<dyn EventSystem>, user_events: Arc<Mutex<VecDeque<UserEvent>>>) -> Self {
+        Self {
+            events,
+            user_events,
+            high_surrogate: Cell::new(None),
+            modifiers: Cell::default(),
+            cursor_grab: Cell::default(),
+            cursor_visible: Cell::new(true),
+            cursor: Cell::new(unsafe { LoadCursorW(null_mut(), IDC_ARROW) }),
+            custom_cursor: Cell::new(null_mut()),
+            icons: Cell::new([null_mut(); 2]),
+            size_limits: Cell::new((None, None)),
+            resize_increments: Cell::new(None),
+            aspect_ratio: Cell::new(None),
+            window_level: Cell::default(),
+            cursor_inside: Cell::new(false),
+            minimized: Cell::new(false),
+        }
     }
 
     pub fn events(&self) -> &dyn EventSystem {
         self.events.borrow()
     }
+
+    pub fn take_user_events(&self) -> VecDeque<UserEvent> {
+        std::mem::take(
+            &mut *self
+                .user_events
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner),
+        )
+    }
+
+    /// Combines the UTF-16 code units of `WM_CHAR` messages into characters.
+    ///
+    /// Characters outside of the BMP arrive as two messages, one per surrogate.
+    pub fn decode_char(&self, unit: u16) -> Option<char> {
+        if (0xD800..0xDC00).contains(&unit) {
+            self.high_surrogate.set(Some(unit));
+            return None;
+        }
+
+        let units = self.high_surrogate.take().into_iter().chain([unit]);
+
+        char::decode_utf16(units).next()?.ok()
+    }
+
+    pub const fn modifiers(&self) -> Modifiers {
+        self.modifiers.get()
+    }
+
+    /// Remembers the current modifiers and reports them, if they changed.
+    pub fn update_modifiers(&self, window: WindowId, modifiers: Modifiers) -> Modifiers {
+        if self.modifiers.replace(modifiers) != modifiers {
+            self.events()
+                .invoke_modifiers_changed_event(window, ModifiersChangedEvent { modifiers });
+        }
+
+        modifiers
+    }
+
+    pub const fn cursor_grab(&self) -> CursorGrabMode {
+        self.cursor_grab.get()
+    }
+
+    pub fn set_cursor_grab(&self, mode: CursorGrabMode) {
+        self.cursor_grab.set(mode);
+    }
+
+    pub fn set_cursor_visible(&self, visible: bool) {
+        self.cursor_visible.set(visible);
+    }
+
+    /// Returns the cursor to show over the client area, which is null while hidden.
+    pub const fn cursor(&self) -> HCURSOR {
+        if self.cursor_visible.get() {
+            self.cursor.get()
+        } else {
+            null_mut()
+        }
+    }
+
+    /// Replaces the cursor, `custom` cursors are owned by the window.
+    pub fn set_cursor(&self, cursor: HCURSOR, custom: bool) {
+        self.cursor.set(cursor);
+
+        let previous = self
+            .custom_cursor
+            .replace(if custom { cursor } else { null_mut() });
+
+        if !previous.is_null() {
+            unsafe { DestroyCursor(previous) };
+        }
+    }
+
+    pub const fn size_limits(&self) -> (Option<Rect>, Option<Rect>) {
+        self.size_limits.get()
+    }
+
+    pub fn set_size_limits(&self, min_size: Option<Rect>, max_size: Option<Rect>) {
+        self.size_limits.set((min_size, max_size));
+    }
+
+    pub const fn resize_increments(&self) -> Option<Rect> {
+        self.resize_increments.get()
+    }
+
+    pub fn set_resize_increments(&self, increments: Option<Rect>) {
+        self.resize_increments.set(increments);
+    }
+
+    pub const fn aspect_ratio(&self) -> Option<(u32, u32)> {
+        self.aspect_ratio.get()
+    }
+
+    pub fn set_aspect_ratio(&self, ratio: Option<(u32, u32)>) {
+        self.aspect_ratio.set(ratio);
+    }
+
+    pub const fn window_level(&self) -> WindowLevel {
+        self.window_level.get()
+    }
+
+    pub fn set_window_level(&self, level: WindowLevel) {
+        self.window_level.set(level);
+    }
+
+    /// Remembers whether the cursor is over the client area, and returns `true` if it changed.
+    pub const fn set_cursor_inside(&self, inside: bool) -> bool {
+        self.cursor_inside.replace(inside) != inside
+    }
+
+    /// Remembers whether the window is minimized, and returns `true` if it changed.
+    pub const fn set_minimized(&self, minimized: bool) -> bool {
+        self.minimized.replace(minimized) != minimized
+    }
+
+    /// Replaces the icons of the window, destroying the previous ones.
+    pub fn set_icons(&self, icons: [HICON; 2]) {
+        destroy_icons(self.icons.replace(icons));
+    }
+}
+
+impl Drop for UserData {
+    fn drop(&mut self) {
+        destroy_icons(self.icons.get());
+
+        let custom_cursor = self.custom_cursor.get();
+
+        if !custom_cursor.is_null() {
+            unsafe { DestroyCursor(custom_cursor) };
+        }
+    }
 }
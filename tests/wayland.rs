@@ -0,0 +1,84 @@
+//! Runs the Wayland backend against a real compositor.
+//!
+//! Ignored by default, because it needs a compositor. A headless weston is enough:
+//!
+//! ```sh
+//! weston --backend=headless --socket=pane-test &
+//! WAYLAND_DISPLAY=pane-test cargo test --test wayland -- --ignored
+//! ```
+#![cfg(all(target_os = "linux", not(feature = "headless")))]
+
+use std::time::{Duration, Instant};
+
+use pane::{
+    EventLoop, RawWindowHandle, Rect, Window,
+    dpi::{PhysicalPosition, PhysicalSize},
+    events::{Event, WindowEvent, WindowResizeEvent},
+};
+
+/// Pumps the event loop, until the window received an event matching `predicate`.
+fn wait_for(event_loop: &mut EventLoop, window: &Window, predicate: impl Fn(&Event) -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+
+    while Instant::now() < deadline {
+        event_loop
+            .wait_events(Some(Duration::from_millis(100)))
+            .unwrap();
+
+        if window.events().any(|e| predicate(&e)) {
+            return;
+        }
+    }
+
+    panic!("the compositor did not send the expected event");
+}
+
+#[test]
+#[ignore = "needs a Wayland compositor, like `weston --backend=headless`"]
+fn window_on_compositor() {
+    assert!(
+        std::env::var_os("WAYLAND_DISPLAY").is_some_and(|display| !display.is_empty()),
+        "WAYLAND_DISPLAY has to name the socket of the compositor"
+    );
+
+    let mut event_loop = EventLoop::new().unwrap();
+    let mut window = event_loop
+        .create_queued_window(
+            "pane",
+            PhysicalPosition::new(0, 0),
+            PhysicalSize::new(64, 48),
+        )
+        .unwrap();
+
+    // the event loop falls back to X11, if it can't connect
+    assert!(matches!(
+        window.raw_handle(),
+        RawWindowHandle::Wayland { .. }
+    ));
+
+    // blocks until the first configure, which asks for the first buffer
+    window.show().unwrap();
+    wait_for(&mut event_loop, &window, |e| {
+        matches!(e, Event::Window(WindowEvent::RedrawRequested { .. }))
+    });
+
+    let size = window.inner_size();
+    let red = vec![0x00FF_0000; usize::from(size.x) * usize::from(size.y)];
+    window.present_buffer(&red, size.x, size.y).unwrap();
+
+    window.set_inner_size(PhysicalSize::new(32, 16)).unwrap();
+    wait_for(&mut event_loop, &window, |e| {
+        matches!(
+            e,
+            Event::WindowResize(WindowResizeEvent {
+                width: 32,
+                height: 16
+            })
+        )
+    });
+    assert_eq!(window.inner_size(), Rect::new(32, 16));
+
+    let blue = vec![0x0000_00FF; 32 * 16];
+    window.present_buffer(&blue, 32, 16).unwrap();
+    event_loop.pump_messages().unwrap();
+}